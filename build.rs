@@ -0,0 +1,60 @@
+//! generates `shaders.rs` in `OUT_DIR` from the hlsl files in `resource`,
+//! see `src/shaders.rs`.
+
+#![allow(clippy::redundant_field_names)]
+
+use std::{env, fs, path::Path};
+
+#[allow(dead_code)]
+#[path = "src/shaders/hlsl.rs"]
+mod hlsl;
+#[allow(dead_code)]
+#[path = "src/cbuffer/packing.rs"]
+mod packing;
+
+fn main() {
+    println!("cargo:rerun-if-changed=resource");
+    println!("cargo:rerun-if-changed=src/cbuffer/packing.rs");
+    println!("cargo:rerun-if-changed=src/shaders/hlsl.rs");
+
+    let mut paths: Vec<_> = fs::read_dir("resource")
+        .expect("failed: read resource")
+        .map(|entry| entry.expect("failed: read resource").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "hlsl"))
+        .collect();
+    paths.sort();
+
+    let mut out = String::new();
+    for path in paths {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed: read {}: {}", path.display(), e));
+        // a shader that cannot be bound gets no module, and leaves the rest of
+        // the build alone
+        let shader = match hlsl::parse(&source) {
+            Ok(shader) => shader,
+            Err(e) => {
+                println!("cargo:warning={}: {}; no bindings", path.display(), e);
+                continue;
+            }
+        };
+        for skipped in shader.skipped.iter() {
+            println!("cargo:warning={}: {}", path.display(), skipped);
+        }
+        let bindings = match hlsl::generate(&shader) {
+            Ok(bindings) => bindings,
+            Err(e) => {
+                println!("cargo:warning={}: {}; no bindings", path.display(), e);
+                continue;
+            }
+        };
+        let stem = path.file_stem().unwrap().to_string_lossy();
+        out.push_str(&format!(
+            "#[allow(dead_code)]\npub mod {} {{\n{}}}\n\n",
+            hlsl::snake_case(&stem),
+            bindings
+        ));
+    }
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("shaders.rs"), out).expect("failed: write shaders.rs");
+}
@@ -0,0 +1,171 @@
+//! what is known about the gpus in the machine, and the policy that picks one
+//! of them. `direct3d::adapter` asks dxgi for the list.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// description of one adapter, as reported by dxgi
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdapterInfo {
+    pub name: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub dedicated_video_memory: u64,
+    pub is_software: bool,
+}
+
+impl AdapterInfo {
+    pub fn vendor_name(&self) -> &'static str {
+        match self.vendor_id {
+            0x1002 | 0x1022 => "AMD",
+            0x10de => "NVIDIA",
+            0x8086 => "Intel",
+            0x1414 => "Microsoft",
+            0x5143 => "Qualcomm",
+            _ => "unknown vendor",
+        }
+    }
+}
+
+impl fmt::Display for AdapterInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, {:04x}:{:04x}, {} MiB{})",
+            self.name,
+            self.vendor_name(),
+            self.vendor_id,
+            self.device_id,
+            self.dedicated_video_memory / (1024 * 1024),
+            if self.is_software { ", software" } else { "" }
+        )
+    }
+}
+
+/// how to choose the adapter that the device is created on
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum AdapterPolicy {
+    #[default]
+    HighPerformance,
+    MinimumPower,
+    /// first adapter whose name contains the string, ignoring case
+    NameContains(String),
+    Warp,
+}
+
+impl FromStr for AdapterPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "high-performance" => Ok(AdapterPolicy::HighPerformance),
+            "minimum-power" => Ok(AdapterPolicy::MinimumPower),
+            "warp" => Ok(AdapterPolicy::Warp),
+            _ if s.starts_with("name:") && s.len() > "name:".len() => {
+                Ok(AdapterPolicy::NameContains(s["name:".len()..].to_string()))
+            }
+            _ => Err(format!(
+                "invalid adapter policy '{}': expected high-performance, minimum-power, warp or name:<substring>",
+                s
+            )),
+        }
+    }
+}
+
+impl AdapterPolicy {
+    /// picks an adapter from `adapters`, which are listed in the order of
+    /// the policy's gpu preference. software adapters are only picked when
+    /// asked for.
+    pub fn select(&self, adapters: &[AdapterInfo]) -> Result<usize, String> {
+        let found = match self {
+            AdapterPolicy::HighPerformance | AdapterPolicy::MinimumPower => {
+                adapters.iter().position(|adapter| !adapter.is_software)
+            }
+            AdapterPolicy::NameContains(name) => {
+                let name = name.to_lowercase();
+                adapters
+                    .iter()
+                    .position(|adapter| adapter.name.to_lowercase().contains(&name))
+            }
+            AdapterPolicy::Warp => adapters.iter().position(|adapter| adapter.is_software),
+        };
+        found.ok_or_else(|| format!("no adapter matches policy {:?}", self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(name: &str, is_software: bool) -> AdapterInfo {
+        AdapterInfo {
+            name: name.to_string(),
+            vendor_id: 0x10de,
+            device_id: 0x2684,
+            dedicated_video_memory: 24 << 30,
+            is_software: is_software,
+        }
+    }
+
+    #[test]
+    fn parses_policies() {
+        let cases = [
+            ("high-performance", AdapterPolicy::HighPerformance),
+            ("minimum-power", AdapterPolicy::MinimumPower),
+            ("warp", AdapterPolicy::Warp),
+            (
+                "name:Radeon",
+                AdapterPolicy::NameContains("Radeon".to_string()),
+            ),
+        ];
+        for (text, policy) in cases.iter() {
+            assert_eq!(text.parse::<AdapterPolicy>().as_ref(), Ok(policy));
+        }
+        for text in ["", "name:", "fastest", "High-Performance", " warp"].iter() {
+            assert!(text.parse::<AdapterPolicy>().is_err(), "{:?}", text);
+        }
+        assert_eq!(AdapterPolicy::default(), AdapterPolicy::HighPerformance);
+    }
+
+    #[test]
+    fn selects_by_policy() {
+        let adapters = [
+            adapter("Microsoft Basic Render Driver", true),
+            adapter("NVIDIA GeForce RTX 4090", false),
+            adapter("Intel(R) UHD Graphics 770", false),
+        ];
+        assert_eq!(AdapterPolicy::HighPerformance.select(&adapters), Ok(1));
+        assert_eq!(AdapterPolicy::MinimumPower.select(&adapters), Ok(1));
+        assert_eq!(AdapterPolicy::Warp.select(&adapters), Ok(0));
+        let by_name = |name: &str| AdapterPolicy::NameContains(name.to_string());
+        assert_eq!(by_name("intel").select(&adapters), Ok(2));
+        assert_eq!(by_name("GEFORCE").select(&adapters), Ok(1));
+        // a name may pick a software adapter
+        assert_eq!(by_name("basic render").select(&adapters), Ok(0));
+    }
+
+    #[test]
+    fn no_adapter_matches() {
+        let software = [adapter("Microsoft Basic Render Driver", true)];
+        let hardware = [adapter("NVIDIA GeForce RTX 4090", false)];
+        assert!(AdapterPolicy::HighPerformance.select(&software).is_err());
+        assert!(AdapterPolicy::MinimumPower.select(&software).is_err());
+        assert!(AdapterPolicy::Warp.select(&hardware).is_err());
+        assert!(AdapterPolicy::NameContains("radeon".to_string())
+            .select(&hardware)
+            .is_err());
+        assert!(AdapterPolicy::HighPerformance.select(&[]).is_err());
+    }
+
+    #[test]
+    fn describes_adapters() {
+        assert_eq!(
+            adapter("NVIDIA GeForce RTX 4090", false).to_string(),
+            "NVIDIA GeForce RTX 4090 (NVIDIA, 10de:2684, 24576 MiB)"
+        );
+        let mut warp = adapter("Microsoft Basic Render Driver", true);
+        warp.vendor_id = 0x1414;
+        warp.dedicated_video_memory = 0;
+        assert_eq!(warp.vendor_name(), "Microsoft");
+        assert!(warp.to_string().ends_with("0 MiB, software)"));
+    }
+}
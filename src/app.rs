@@ -0,0 +1,524 @@
+//! the main loop. an application implements `App` and hands it to `run`
+//! together with a platform window and a renderer.
+
+pub mod headless;
+
+use std::time::Duration;
+
+use crate::clock::{Clock, DefaultClock};
+use crate::event::{Event, WindowEvent};
+use crate::input::InputState;
+use crate::replay::{Replay, Schedule};
+use crate::resize::{ResizeAction, Resizers};
+use crate::timestep::{FixedTimestep, Frame};
+use crate::window::id::WindowId;
+
+/// how long the client size has to stay the same before the renderer follows
+pub const RESIZE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// fixed updates per second used by `run`
+pub const UPDATE_RATE: u32 = 60;
+
+/// the window side of the main loop: one or more top-level windows
+pub trait Platform {
+    /// dispatches pending messages and appends their events to `events`.
+    /// returns false once the application should quit.
+    fn poll(&mut self, events: &mut Vec<WindowEvent>) -> bool;
+    /// blocks until there is something to handle, used while minimized
+    fn wait(&mut self);
+    /// appends every open window with its client size, 0x0 while minimized
+    fn client_sizes(&self, sizes: &mut Vec<(WindowId, u32, u32)>);
+    /// physical pixels per logical unit of `window`, for renderers and ui
+    /// that follow the dpi. `Event::DpiChanged` tells when it changes.
+    fn scale_factor(&self, _window: WindowId) -> f32 {
+        1.0
+    }
+    /// called after the renderer has followed a new client size
+    fn resized(&mut self, _window: WindowId, _width: u32, _height: u32) {}
+}
+
+/// the rendering side of the main loop, with a surface per window
+pub trait Renderer {
+    fn resize(&mut self, window: WindowId, width: u32, height: u32) -> Result<(), String>;
+    /// stops drawing into `window` while it is minimized, or starts again
+    fn suspend(&mut self, _window: WindowId, _suspended: bool) {}
+    /// `window` was destroyed, so its surface has to go
+    fn close(&mut self, _window: WindowId) -> Result<(), String> {
+        Ok(())
+    }
+    /// submits and presents a frame to every surface that is not suspended
+    fn present(&mut self) -> Result<(), String>;
+}
+
+pub struct Context<P, R> {
+    pub platform: P,
+    pub renderer: R,
+    /// input as of the events handled so far. edges are kept until a frame
+    /// has run at least one update, so that no update misses them.
+    pub input: InputState,
+    quit: bool,
+}
+
+impl<P, R> Context<P, R> {
+    pub fn new(platform: P, renderer: R) -> Self {
+        Context {
+            platform: platform,
+            renderer: renderer,
+            input: InputState::new(),
+            quit: false,
+        }
+    }
+
+    /// leaves the main loop after the current hook returns
+    pub fn quit(&mut self) {
+        self.quit = true;
+    }
+
+    pub fn is_quitting(&self) -> bool {
+        self.quit
+    }
+}
+
+/// hooks called by `run`. an error from any of them ends the loop.
+pub trait App<P, R> {
+    fn init(&mut self, _context: &mut Context<P, R>) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        _context: &mut Context<P, R>,
+        _window: WindowId,
+        _event: &Event,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// advances the simulation by one fixed step of `dt`
+    fn update(&mut self, _context: &mut Context<P, R>, _dt: Duration) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// records the frame, which the runner presents afterwards. `alpha` is
+    /// how far the frame is between the last update and the next one.
+    fn render(&mut self, _context: &mut Context<P, R>, _alpha: f32) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// called once the loop has ended, also when it ended with an error
+    fn shutdown(&mut self, _context: &mut Context<P, R>) {}
+}
+
+/// runs `app` until the platform or the app asks to quit, updating it
+/// `UPDATE_RATE` times per second
+pub fn run<P, R, A>(context: &mut Context<P, R>, app: &mut A) -> Result<(), String>
+where
+    P: Platform,
+    R: Renderer,
+    A: App<P, R>,
+{
+    run_replay(context, app, &mut Replay::Live)
+}
+
+/// `run` with the input recorded to or played back from `replay`. a
+/// recording has to be made with a step of `UPDATE_RATE`.
+pub fn run_replay<P, R, A>(
+    context: &mut Context<P, R>,
+    app: &mut A,
+    replay: &mut Replay,
+) -> Result<(), String>
+where
+    P: Platform,
+    R: Renderer,
+    A: App<P, R>,
+{
+    let mut clock = DefaultClock::new();
+    run_with_replay(
+        context,
+        app,
+        &mut clock,
+        FixedTimestep::from_rate(UPDATE_RATE)?,
+        replay,
+    )
+}
+
+/// `run` with the time taken from `clock` and updates paced by `timestep`
+pub fn run_with<P, R, A, C>(
+    context: &mut Context<P, R>,
+    app: &mut A,
+    clock: &mut C,
+    timestep: FixedTimestep,
+) -> Result<(), String>
+where
+    P: Platform,
+    R: Renderer,
+    A: App<P, R>,
+    C: Clock,
+{
+    run_with_replay(context, app, clock, timestep, &mut Replay::Live)
+}
+
+/// `run_with` and `run_replay` together
+pub fn run_with_replay<P, R, A, C>(
+    context: &mut Context<P, R>,
+    app: &mut A,
+    clock: &mut C,
+    timestep: FixedTimestep,
+    replay: &mut Replay,
+) -> Result<(), String>
+where
+    P: Platform,
+    R: Renderer,
+    A: App<P, R>,
+    C: Clock,
+{
+    replay.check_step(timestep.step())?;
+    let result = main_loop(context, app, clock, timestep, replay);
+    app.shutdown(context);
+    result
+}
+
+fn main_loop<P, R, A, C>(
+    context: &mut Context<P, R>,
+    app: &mut A,
+    clock: &mut C,
+    mut timestep: FixedTimestep,
+    replay: &mut Replay,
+) -> Result<(), String>
+where
+    P: Platform,
+    R: Renderer,
+    A: App<P, R>,
+    C: Clock,
+{
+    // a frame that handles events but runs no update
+    let idle = Frame {
+        steps: 0,
+        alpha: 0.0,
+        dropped: Duration::from_secs(0),
+    };
+    app.init(context)?;
+    let mut resizers = Resizers::new(RESIZE_DEBOUNCE);
+    let mut sizes = vec![];
+    context.platform.client_sizes(&mut sizes);
+    for &(window, width, height) in sizes.iter() {
+        resizers.observe(window, width, height, clock.now());
+    }
+    let mut events = vec![];
+    while !context.quit {
+        let frame_start = clock.now();
+        events.clear();
+        let mut running = context.platform.poll(&mut events);
+        let schedule = replay.schedule(&mut events);
+        if schedule == Schedule::Finished {
+            running = false;
+        }
+        for event in events.iter() {
+            context.input.handle(&event.event);
+            app.event(context, event.window, &event.event)?;
+            if event.event == Event::Destroyed {
+                resizers.remove(event.window);
+                context.renderer.close(event.window)?;
+            }
+        }
+        if !running || context.quit {
+            replay.record(frame_start, &events, &idle);
+            break;
+        }
+
+        sizes.clear();
+        context.platform.client_sizes(&mut sizes);
+        for &(window, width, height) in sizes.iter() {
+            match resizers.observe(window, width, height, frame_start) {
+                ResizeAction::Resize { width, height } => {
+                    context.renderer.resize(window, width, height)?;
+                    context.platform.resized(window, width, height);
+                }
+                ResizeAction::Suspend => context.renderer.suspend(window, true),
+                ResizeAction::Resume => context.renderer.suspend(window, false),
+                ResizeAction::None => (),
+            }
+        }
+        let suspended = resizers.all_suspended();
+        let frame = match schedule {
+            // a playback keeps going while minimized, without rendering
+            Schedule::Recorded(frame) => frame,
+            _ if suspended => {
+                replay.record(frame_start, &events, &idle);
+                context.platform.wait();
+                // time spent minimized is not simulated
+                timestep.reset();
+                continue;
+            }
+            _ => timestep.advance(frame_start),
+        };
+        replay.record(frame_start, &events, &frame);
+        for _ in 0..frame.steps {
+            app.update(context, timestep.step())?;
+            if context.quit {
+                return Ok(());
+            }
+        }
+        if frame.steps > 0 {
+            context.input.begin_frame();
+        }
+        if !suspended {
+            app.render(context, frame.alpha)?;
+            context.renderer.present()?;
+        }
+
+        if let Some(delay) = timestep.cap_delay(frame_start, clock.now()) {
+            clock.sleep(delay);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::headless::{HeadlessPlatform, HeadlessRenderer};
+    use super::*;
+    use crate::clock::FakeClock;
+
+    type HeadlessContext = Context<HeadlessPlatform, HeadlessRenderer>;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    /// logs its hooks and takes `frame_time` per rendered frame
+    #[derive(Default)]
+    struct Recorder {
+        log: Vec<String>,
+        clock: FakeClock,
+        frame_time: Duration,
+        quit_after_updates: Option<u32>,
+        fail_render: Option<u32>,
+        updates: u32,
+        renders: u32,
+    }
+
+    impl App<HeadlessPlatform, HeadlessRenderer> for Recorder {
+        fn init(&mut self, _context: &mut HeadlessContext) -> Result<(), String> {
+            self.log.push("init".to_string());
+            Ok(())
+        }
+
+        fn event(
+            &mut self,
+            _context: &mut HeadlessContext,
+            _window: WindowId,
+            event: &Event,
+        ) -> Result<(), String> {
+            self.log.push(format!("event {:?}", event));
+            Ok(())
+        }
+
+        fn update(&mut self, context: &mut HeadlessContext, dt: Duration) -> Result<(), String> {
+            assert_eq!(dt, STEP);
+            self.updates += 1;
+            self.log.push("update".to_string());
+            if Some(self.updates) == self.quit_after_updates {
+                context.quit();
+            }
+            Ok(())
+        }
+
+        fn render(&mut self, _context: &mut HeadlessContext, _alpha: f32) -> Result<(), String> {
+            self.renders += 1;
+            self.log.push("render".to_string());
+            self.clock.advance(self.frame_time);
+            if Some(self.renders) == self.fail_render {
+                return Err("render failed".to_string());
+            }
+            Ok(())
+        }
+
+        fn shutdown(&mut self, _context: &mut HeadlessContext) {
+            self.log.push("shutdown".to_string());
+        }
+    }
+
+    fn context(frames: usize) -> HeadlessContext {
+        let mut platform = HeadlessPlatform::new(640, 480);
+        for _ in 0..frames {
+            platform.push_frame(vec![]);
+        }
+        let renderer = HeadlessRenderer::new(&platform);
+        Context::new(platform, renderer)
+    }
+
+    fn run_recorder(context: &mut HeadlessContext, app: &mut Recorder) -> Result<(), String> {
+        let mut clock = app.clock.clone();
+        run_with(context, app, &mut clock, FixedTimestep::new(STEP).unwrap())
+    }
+
+    #[test]
+    fn hooks_run_in_order() {
+        let mut context = context(3);
+        context.platform.push_frame(vec![Event::CloseRequested]);
+        let mut app = Recorder {
+            frame_time: STEP,
+            ..Recorder::default()
+        };
+        run_recorder(&mut context, &mut app).unwrap();
+        // the first frame starts at time 0, before any step is due
+        assert_eq!(
+            app.log,
+            vec![
+                "init",
+                "render",
+                "update",
+                "render",
+                "update",
+                "render",
+                "event CloseRequested",
+                "update",
+                "render",
+                "shutdown",
+            ]
+        );
+        assert_eq!(context.renderer.frames, 4);
+    }
+
+    #[test]
+    fn quit_from_update() {
+        let mut context = context(10);
+        let mut app = Recorder {
+            frame_time: STEP * 3,
+            quit_after_updates: Some(4),
+            ..Recorder::default()
+        };
+        run_recorder(&mut context, &mut app).unwrap();
+        // the frame with the fourth update runs no more updates and does
+        // not render
+        assert_eq!(app.updates, 4);
+        assert_eq!(app.renders, 2);
+        assert_eq!(context.renderer.frames, 2);
+        assert_eq!(app.log.last().map(String::as_str), Some("shutdown"));
+    }
+
+    #[test]
+    fn render_error_still_shuts_down() {
+        let mut context = context(10);
+        let mut app = Recorder {
+            frame_time: STEP,
+            fail_render: Some(2),
+            ..Recorder::default()
+        };
+        assert_eq!(
+            run_recorder(&mut context, &mut app),
+            Err("render failed".to_string())
+        );
+        assert_eq!(app.renders, 2);
+        // the failed frame is not presented
+        assert_eq!(context.renderer.frames, 1);
+        assert_eq!(app.log.last().map(String::as_str), Some("shutdown"));
+        assert_eq!(app.log.iter().filter(|hook| *hook == "shutdown").count(), 1);
+    }
+
+    /// records whether each update saw the press and release of W
+    #[derive(Default)]
+    struct Edges {
+        clock: FakeClock,
+        frame_times: Vec<Duration>,
+        seen: Vec<(bool, bool)>,
+    }
+
+    impl App<HeadlessPlatform, HeadlessRenderer> for Edges {
+        fn update(&mut self, context: &mut HeadlessContext, _dt: Duration) -> Result<(), String> {
+            let w = crate::input::Button::Key(0x57);
+            self.seen
+                .push((context.input.was_pressed(w), context.input.was_released(w)));
+            Ok(())
+        }
+
+        fn render(&mut self, _context: &mut HeadlessContext, _alpha: f32) -> Result<(), String> {
+            if !self.frame_times.is_empty() {
+                self.clock.advance(self.frame_times.remove(0));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn edges_survive_frames_without_updates() {
+        let mut platform = HeadlessPlatform::new(640, 480);
+        platform.push_frame(vec![]);
+        platform.push_frame(vec![Event::KeyDown {
+            key: 0x57,
+            scancode: 0x11,
+            repeat: false,
+            alt: false,
+        }]);
+        platform.push_frame(vec![Event::KeyUp {
+            key: 0x57,
+            scancode: 0x11,
+        }]);
+        for _ in 0..3 {
+            platform.push_frame(vec![]);
+        }
+        let renderer = HeadlessRenderer::new(&platform);
+        let mut context = Context::new(platform, renderer);
+        // the press and the release come in frames too short for a step,
+        // and the first step is due in the fifth frame
+        let mut app = Edges {
+            frame_times: vec![STEP / 4, STEP / 4, STEP / 4, STEP, STEP],
+            ..Edges::default()
+        };
+        let mut clock = app.clock.clone();
+        run_with(
+            &mut context,
+            &mut app,
+            &mut clock,
+            FixedTimestep::new(STEP).unwrap(),
+        )
+        .unwrap();
+        // the first update sees both edges, the next one neither
+        assert_eq!(app.seen, vec![(true, true), (false, false)]);
+    }
+
+    #[test]
+    fn the_end_of_the_script_quits() {
+        let mut context = context(2);
+        let mut app = Recorder::default();
+        run_recorder(&mut context, &mut app).unwrap();
+        assert_eq!(app.renders, 2);
+        assert_eq!(app.log, vec!["init", "render", "render", "shutdown"]);
+    }
+
+    #[test]
+    fn minimized_window_stops_drawing_while_others_go_on() {
+        let mut platform = HeadlessPlatform::new(640, 480);
+        let (a, b) = (platform.primary(), platform.add_window(320, 240));
+        let renderer = HeadlessRenderer::new(&platform);
+        platform.push_window_events(vec![WindowEvent::new(
+            b,
+            Event::Resized {
+                width: 100,
+                height: 50,
+            },
+        )]);
+        platform.push_frame(vec![]);
+        platform.push_frame(vec![]);
+        platform.push_frame(vec![Event::Resized {
+            width: 0,
+            height: 0,
+        }]);
+        platform.push_frame(vec![]);
+        platform.push_window_events(vec![WindowEvent::new(b, Event::Destroyed)]);
+        let mut context = Context::new(platform, renderer);
+        let mut app = Recorder {
+            frame_time: Duration::from_millis(60),
+            ..Recorder::default()
+        };
+        run_recorder(&mut context, &mut app).unwrap();
+
+        assert_eq!(context.renderer.resizes, vec![(b, 100, 50)]);
+        assert!(!context.renderer.surfaces.contains(b));
+        assert!(context.renderer.surfaces.is_suspended(a));
+        // a is drawn in the first three frames and b in all five; once b is
+        // gone, every window is minimized and nothing is drawn
+        assert_eq!(context.renderer.surfaces.get(a).unwrap().frames, 3);
+        assert_eq!(context.renderer.frames, 5);
+        assert_eq!(app.renders, 5);
+    }
+}
@@ -0,0 +1,174 @@
+//! a platform and renderer without windows or gpu, for running apps in tests
+
+use std::collections::VecDeque;
+
+use super::{Platform, Renderer};
+use crate::event::{Event, WindowEvent};
+use crate::surface::SurfaceSet;
+use crate::window::id::WindowId;
+
+/// a window that only has a size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessWindow {
+    pub id: WindowId,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl HeadlessWindow {
+    pub fn new(width: u32, height: u32) -> Self {
+        HeadlessWindow {
+            id: WindowId::next(),
+            width: width,
+            height: height,
+        }
+    }
+}
+
+/// windows that replay a script of events, one batch per frame, and quit
+/// at its end
+pub struct HeadlessPlatform {
+    pub windows: Vec<HeadlessWindow>,
+    primary: WindowId,
+    script: VecDeque<Vec<WindowEvent>>,
+}
+
+impl HeadlessPlatform {
+    /// a platform with one window
+    pub fn new(width: u32, height: u32) -> Self {
+        let window = HeadlessWindow::new(width, height);
+        HeadlessPlatform {
+            windows: vec![window],
+            primary: window.id,
+            script: VecDeque::new(),
+        }
+    }
+
+    /// the window the platform was created with
+    pub fn primary(&self) -> WindowId {
+        self.primary
+    }
+
+    pub fn add_window(&mut self, width: u32, height: u32) -> WindowId {
+        let window = HeadlessWindow::new(width, height);
+        self.windows.push(window);
+        window.id
+    }
+
+    pub fn window(&self, id: WindowId) -> Option<&HeadlessWindow> {
+        self.windows.iter().find(|window| window.id == id)
+    }
+
+    /// queues events of the primary window, delivered by one `poll`
+    pub fn push_frame(&mut self, events: Vec<Event>) {
+        let primary = self.primary;
+        self.script.push_back(
+            events
+                .into_iter()
+                .map(|event| WindowEvent::new(primary, event))
+                .collect(),
+        );
+    }
+
+    /// queues events of any window, delivered by one `poll`
+    pub fn push_window_events(&mut self, events: Vec<WindowEvent>) {
+        self.script.push_back(events);
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn poll(&mut self, events: &mut Vec<WindowEvent>) -> bool {
+        let frame = match self.script.pop_front() {
+            Some(frame) => frame,
+            None => return false,
+        };
+        for event in frame.iter() {
+            match event.event {
+                Event::Resized { width, height } => {
+                    if let Some(window) = self.windows.iter_mut().find(|w| w.id == event.window) {
+                        window.width = width;
+                        window.height = height;
+                    }
+                }
+                Event::Destroyed => self.windows.retain(|w| w.id != event.window),
+                _ => (),
+            }
+        }
+        events.extend(frame);
+        true
+    }
+
+    fn wait(&mut self) {}
+
+    fn client_sizes(&self, sizes: &mut Vec<(WindowId, u32, u32)>) {
+        sizes.extend(
+            self.windows
+                .iter()
+                .map(|window| (window.id, window.width, window.height)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessSurface {
+    pub width: u32,
+    pub height: u32,
+    /// frames presented to this surface
+    pub frames: u32,
+}
+
+/// counts what it is asked to do
+#[derive(Default)]
+pub struct HeadlessRenderer {
+    pub surfaces: SurfaceSet<HeadlessSurface>,
+    /// frames presented, to any number of surfaces
+    pub frames: u32,
+    pub resizes: Vec<(WindowId, u32, u32)>,
+}
+
+impl HeadlessRenderer {
+    /// a renderer with a surface for every window of `platform`
+    pub fn new(platform: &HeadlessPlatform) -> Self {
+        let mut renderer = HeadlessRenderer::default();
+        for window in platform.windows.iter() {
+            renderer.add_surface(window);
+        }
+        renderer
+    }
+
+    pub fn add_surface(&mut self, window: &HeadlessWindow) {
+        let surface = HeadlessSurface {
+            width: window.width,
+            height: window.height,
+            frames: 0,
+        };
+        self.surfaces.insert(window.id, surface);
+    }
+}
+
+impl Renderer for HeadlessRenderer {
+    fn resize(&mut self, window: WindowId, width: u32, height: u32) -> Result<(), String> {
+        let surface = self.surfaces.expect_mut(window)?;
+        surface.width = width;
+        surface.height = height;
+        self.resizes.push((window, width, height));
+        Ok(())
+    }
+
+    fn suspend(&mut self, window: WindowId, suspended: bool) {
+        self.surfaces.set_suspended(window, suspended);
+    }
+
+    fn close(&mut self, window: WindowId) -> Result<(), String> {
+        self.surfaces.remove(window);
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.frames += 1;
+        for (_, surface) in self.surfaces.active_mut() {
+            surface.frames += 1;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! what a device can do, and the optional features that depend on it.
+//! `direct3d::caps` fills `Capabilities` in from `CheckFeatureSupport`.
+
+use std::fmt;
+
+use crate::pipeline::ShaderStage;
+use crate::sync::QueueKind;
+
+/// what the device supports, as reported by `CheckFeatureSupport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub feature_level: FeatureLevel,
+    pub shader_model: ShaderModel,
+    pub resource_binding_tier: u32,
+    pub tiled_resources_tier: u32,
+    pub raytracing_tier: RaytracingTier,
+    pub mesh_shader_tier: MeshShaderTier,
+    pub vrs_tier: VrsTier,
+    pub tearing: bool,
+    /// `DXGI_FORMAT`s that typed uavs can load
+    pub typed_uav_load_formats: Vec<u32>,
+}
+
+/// `D3D_FEATURE_LEVEL` value, e.g. `0xc100` for 12_1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FeatureLevel(pub u32);
+
+impl FeatureLevel {
+    pub const LEVEL_11_0: FeatureLevel = FeatureLevel(0xb000);
+    pub const LEVEL_11_1: FeatureLevel = FeatureLevel(0xb100);
+    pub const LEVEL_12_0: FeatureLevel = FeatureLevel(0xc000);
+    pub const LEVEL_12_1: FeatureLevel = FeatureLevel(0xc100);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ShaderModel {
+    pub major: u32,
+    pub minor: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RaytracingTier {
+    NotSupported,
+    Tier1_0,
+    Tier1_1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MeshShaderTier {
+    NotSupported,
+    Tier1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VrsTier {
+    NotSupported,
+    Tier1,
+    Tier2,
+}
+
+/// optional rendering features, each gated on the capabilities it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    FeatureLevel(FeatureLevel),
+    ShaderModel(ShaderModel),
+    ResourceBindingTier(u32),
+    TiledResources(u32),
+    Raytracing(RaytracingTier),
+    MeshShaders,
+    VariableRateShading(VrsTier),
+    Tearing,
+    /// a `DXGI_FORMAT`
+    TypedUavLoad(u32),
+    /// hull and domain shaders
+    Tessellation,
+    GeometryShaders,
+    ComputeQueue,
+    CopyQueue,
+}
+
+impl Feature {
+    /// the feature a pipeline stage needs, if any
+    pub fn for_stage(stage: ShaderStage) -> Option<Feature> {
+        match stage {
+            ShaderStage::Hull | ShaderStage::Domain => Some(Feature::Tessellation),
+            ShaderStage::Geometry => Some(Feature::GeometryShaders),
+            ShaderStage::Vertex | ShaderStage::Pixel => None,
+        }
+    }
+
+    /// the feature submitting to a queue needs, if any
+    pub fn for_queue(queue: QueueKind) -> Option<Feature> {
+        match queue {
+            QueueKind::Direct => None,
+            QueueKind::Compute => Some(Feature::ComputeQueue),
+            QueueKind::Copy => Some(Feature::CopyQueue),
+        }
+    }
+}
+
+impl fmt::Display for FeatureLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}_{}", self.0 >> 12, (self.0 >> 8) & 0xf)
+    }
+}
+
+impl fmt::Display for ShaderModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl ShaderModel {
+    pub fn new(major: u32, minor: u32) -> Self {
+        ShaderModel {
+            major: major,
+            minor: minor,
+        }
+    }
+}
+
+/// `DXGI_FORMAT_R32_FLOAT`, `_UINT` and `_SINT`, which every device loads
+/// from typed uavs whether or not it has the additional formats
+pub const TYPED_UAV_LOAD_FORMATS: [u32; 3] = [41, 42, 43];
+
+impl Capabilities {
+    /// the least a d3d12 device can offer
+    pub fn minimum() -> Self {
+        Capabilities {
+            feature_level: FeatureLevel::LEVEL_11_0,
+            shader_model: ShaderModel::new(5, 1),
+            resource_binding_tier: 1,
+            tiled_resources_tier: 0,
+            raytracing_tier: RaytracingTier::NotSupported,
+            mesh_shader_tier: MeshShaderTier::NotSupported,
+            vrs_tier: VrsTier::NotSupported,
+            tearing: false,
+            typed_uav_load_formats: TYPED_UAV_LOAD_FORMATS.to_vec(),
+        }
+    }
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.check(feature).is_ok()
+    }
+
+    /// checks that `feature` can be enabled, explaining what is missing if not
+    pub fn check(&self, feature: Feature) -> Result<(), String> {
+        let ok = match feature {
+            Feature::FeatureLevel(level) => self.feature_level >= level,
+            Feature::ShaderModel(model) => self.shader_model >= model,
+            Feature::ResourceBindingTier(tier) => self.resource_binding_tier >= tier,
+            Feature::TiledResources(tier) => tier > 0 && self.tiled_resources_tier >= tier,
+            Feature::Raytracing(tier) => {
+                tier > RaytracingTier::NotSupported && self.raytracing_tier >= tier
+            }
+            Feature::MeshShaders => self.mesh_shader_tier >= MeshShaderTier::Tier1,
+            Feature::VariableRateShading(tier) => {
+                tier > VrsTier::NotSupported && self.vrs_tier >= tier
+            }
+            Feature::Tearing => self.tearing,
+            Feature::TypedUavLoad(format) => self.typed_uav_load_formats.contains(&format),
+            // part of every d3d12 device, which is at least 11_0
+            Feature::Tessellation
+            | Feature::GeometryShaders
+            | Feature::ComputeQueue
+            | Feature::CopyQueue => self.feature_level >= FeatureLevel::LEVEL_11_0,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("unsupported feature: {:?}", feature))
+        }
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "feature level: {}", self.feature_level)?;
+        writeln!(f, "shader model: {}", self.shader_model)?;
+        writeln!(f, "resource binding tier: {}", self.resource_binding_tier)?;
+        writeln!(f, "tiled resources tier: {}", self.tiled_resources_tier)?;
+        writeln!(f, "raytracing tier: {:?}", self.raytracing_tier)?;
+        writeln!(f, "mesh shader tier: {:?}", self.mesh_shader_tier)?;
+        writeln!(f, "variable rate shading tier: {:?}", self.vrs_tier)?;
+        writeln!(f, "tearing: {}", self.tearing)?;
+        write!(
+            f,
+            "typed uav load formats: {:?}",
+            self.typed_uav_load_formats
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM and
+    // DXGI_FORMAT_R32_UINT
+    const RGBA16_FLOAT: u32 = 10;
+    const RGBA8_UNORM: u32 = 28;
+    const R32_UINT: u32 = 42;
+
+    fn high_end() -> Capabilities {
+        Capabilities {
+            feature_level: FeatureLevel::LEVEL_12_1,
+            shader_model: ShaderModel::new(6, 6),
+            resource_binding_tier: 3,
+            tiled_resources_tier: 3,
+            raytracing_tier: RaytracingTier::Tier1_1,
+            mesh_shader_tier: MeshShaderTier::Tier1,
+            vrs_tier: VrsTier::Tier2,
+            tearing: true,
+            typed_uav_load_formats: vec![41, 42, 43, RGBA16_FLOAT],
+        }
+    }
+
+    #[test]
+    fn minimum_supports_what_every_device_has() {
+        let caps = Capabilities::minimum();
+        for feature in [
+            Feature::FeatureLevel(FeatureLevel::LEVEL_11_0),
+            Feature::ShaderModel(ShaderModel::new(5, 1)),
+            Feature::ResourceBindingTier(1),
+            Feature::Tessellation,
+            Feature::GeometryShaders,
+            Feature::ComputeQueue,
+            Feature::CopyQueue,
+            Feature::TypedUavLoad(R32_UINT),
+        ]
+        .iter()
+        {
+            assert_eq!(caps.check(*feature), Ok(()), "{:?}", feature);
+        }
+        for feature in [
+            Feature::FeatureLevel(FeatureLevel::LEVEL_11_1),
+            Feature::ShaderModel(ShaderModel::new(6, 0)),
+            Feature::ResourceBindingTier(2),
+            Feature::TiledResources(1),
+            Feature::Raytracing(RaytracingTier::Tier1_0),
+            Feature::MeshShaders,
+            Feature::VariableRateShading(VrsTier::Tier1),
+            Feature::Tearing,
+            Feature::TypedUavLoad(RGBA16_FLOAT),
+        ]
+        .iter()
+        {
+            assert!(!caps.supports(*feature), "{:?}", feature);
+        }
+    }
+
+    #[test]
+    fn tiers_compare_in_order() {
+        let caps = Capabilities {
+            raytracing_tier: RaytracingTier::Tier1_0,
+            vrs_tier: VrsTier::Tier1,
+            tiled_resources_tier: 2,
+            ..high_end()
+        };
+        assert!(caps.supports(Feature::Raytracing(RaytracingTier::Tier1_0)));
+        assert!(!caps.supports(Feature::Raytracing(RaytracingTier::Tier1_1)));
+        assert!(caps.supports(Feature::VariableRateShading(VrsTier::Tier1)));
+        assert!(!caps.supports(Feature::VariableRateShading(VrsTier::Tier2)));
+        assert!(caps.supports(Feature::TiledResources(2)));
+        assert!(!caps.supports(Feature::TiledResources(3)));
+        assert!(caps.supports(Feature::ShaderModel(ShaderModel::new(5, 1))));
+        assert!(caps.supports(Feature::ShaderModel(ShaderModel::new(6, 6))));
+        assert!(!caps.supports(Feature::ShaderModel(ShaderModel::new(6, 7))));
+        assert!(caps.supports(Feature::FeatureLevel(FeatureLevel::LEVEL_12_0)));
+    }
+
+    #[test]
+    fn not_supported_is_never_a_feature() {
+        let caps = high_end();
+        assert!(!caps.supports(Feature::Raytracing(RaytracingTier::NotSupported)));
+        assert!(!caps.supports(Feature::VariableRateShading(VrsTier::NotSupported)));
+        assert!(!caps.supports(Feature::TiledResources(0)));
+    }
+
+    #[test]
+    fn typed_uav_loads_are_per_format() {
+        let caps = high_end();
+        assert!(caps.supports(Feature::TypedUavLoad(RGBA16_FLOAT)));
+        assert_eq!(
+            caps.check(Feature::TypedUavLoad(RGBA8_UNORM)),
+            Err("unsupported feature: TypedUavLoad(28)".to_string())
+        );
+        // the r32 formats without the additional ones
+        let caps = Capabilities::minimum();
+        for format in TYPED_UAV_LOAD_FORMATS.iter() {
+            assert!(caps.supports(Feature::TypedUavLoad(*format)), "{}", format);
+        }
+        assert!(!caps.supports(Feature::TypedUavLoad(RGBA16_FLOAT)));
+    }
+
+    #[test]
+    fn below_11_0_has_no_optional_stages_or_queues() {
+        let caps = Capabilities {
+            feature_level: FeatureLevel(0xa100),
+            ..Capabilities::minimum()
+        };
+        assert!(!caps.supports(Feature::Tessellation));
+        assert!(!caps.supports(Feature::GeometryShaders));
+        assert!(!caps.supports(Feature::ComputeQueue));
+        assert!(!caps.supports(Feature::CopyQueue));
+    }
+
+    #[test]
+    fn features_of_stages_and_queues() {
+        assert_eq!(Feature::for_stage(ShaderStage::Vertex), None);
+        assert_eq!(Feature::for_stage(ShaderStage::Pixel), None);
+        assert_eq!(
+            Feature::for_stage(ShaderStage::Hull),
+            Some(Feature::Tessellation)
+        );
+        assert_eq!(
+            Feature::for_stage(ShaderStage::Domain),
+            Some(Feature::Tessellation)
+        );
+        assert_eq!(
+            Feature::for_stage(ShaderStage::Geometry),
+            Some(Feature::GeometryShaders)
+        );
+        assert_eq!(Feature::for_queue(QueueKind::Direct), None);
+        assert_eq!(
+            Feature::for_queue(QueueKind::Compute),
+            Some(Feature::ComputeQueue)
+        );
+        assert_eq!(
+            Feature::for_queue(QueueKind::Copy),
+            Some(Feature::CopyQueue)
+        );
+    }
+
+    #[test]
+    fn formats_levels_and_models() {
+        assert_eq!(FeatureLevel::LEVEL_12_1.to_string(), "12_1");
+        assert_eq!(FeatureLevel::LEVEL_11_0.to_string(), "11_0");
+        assert_eq!(ShaderModel::new(6, 5).to_string(), "6.5");
+    }
+}
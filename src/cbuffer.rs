@@ -0,0 +1,499 @@
+//! constant buffer layouts of rust types. `constant_buffer!` describes a
+//! `#[repr(C)]` struct field by field and stops the build when a field is
+//! not where hlsl puts it:
+//!
+//! ```ignore
+//! #[repr(C)]
+//! struct Scene {
+//!     view: Mat4,
+//!     light: Vec3<f32>,
+//!     intensity: f32,
+//!     color: Vec3<f32>,
+//! }
+//! constant_buffer!(Scene { view: Mat4, light: Vec3<f32>, intensity: f32, color: Vec3<f32> });
+//! ```
+//!
+//! the same description checks the layout the compiler reflected into the
+//! shader, see `check`.
+
+pub mod packing;
+
+use self::packing::{Field, Layout, Major, Scalar, Type};
+use crate::color::Color;
+use crate::dxbc;
+use crate::math::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+
+/// a rust type and the hlsl type it uploads as
+pub trait HlslType {
+    /// bytes it takes in a constant buffer
+    const SIZE: u32;
+    /// starts a new register
+    const AGGREGATE: bool;
+    fn hlsl_type() -> Type;
+}
+
+macro_rules! scalar {
+    ($rust:ty, $scalar:expr) => {
+        impl HlslType for $rust {
+            const SIZE: u32 = 4;
+            const AGGREGATE: bool = false;
+            fn hlsl_type() -> Type {
+                Type::Scalar($scalar)
+            }
+        }
+        impl HlslType for Vec2<$rust> {
+            const SIZE: u32 = 8;
+            const AGGREGATE: bool = false;
+            fn hlsl_type() -> Type {
+                Type::Vector($scalar, 2)
+            }
+        }
+        impl HlslType for Vec3<$rust> {
+            const SIZE: u32 = 12;
+            const AGGREGATE: bool = false;
+            fn hlsl_type() -> Type {
+                Type::Vector($scalar, 3)
+            }
+        }
+        impl HlslType for Vec4<$rust> {
+            const SIZE: u32 = 16;
+            const AGGREGATE: bool = false;
+            fn hlsl_type() -> Type {
+                Type::Vector($scalar, 4)
+            }
+        }
+    };
+}
+
+scalar!(f32, Scalar::Float);
+scalar!(i32, Scalar::Int);
+scalar!(u32, Scalar::Uint);
+
+impl HlslType for Quat {
+    const SIZE: u32 = 16;
+    const AGGREGATE: bool = false;
+    fn hlsl_type() -> Type {
+        Type::Vector(Scalar::Float, 4)
+    }
+}
+
+/// linear, as a `float4`
+impl HlslType for Color {
+    const SIZE: u32 = 16;
+    const AGGREGATE: bool = false;
+    fn hlsl_type() -> Type {
+        Type::Vector(Scalar::Float, 4)
+    }
+}
+
+/// rows as they are stored; upload the transpose for a column-major
+/// declaration, which has the same layout
+impl HlslType for Mat4 {
+    const SIZE: u32 = 64;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> Type {
+        Type::Matrix {
+            scalar: Scalar::Float,
+            rows: 4,
+            columns: 4,
+            major: Major::Row,
+        }
+    }
+}
+
+/// 44 bytes in hlsl but 36 in rust, so a struct with one never matches;
+/// it is here so that the mismatch is reported as such
+impl HlslType for Mat3 {
+    const SIZE: u32 = 44;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> Type {
+        Type::Matrix {
+            scalar: Scalar::Float,
+            rows: 3,
+            columns: 3,
+            major: Major::Row,
+        }
+    }
+}
+
+impl<T: HlslType, const N: usize> HlslType for [T; N] {
+    const SIZE: u32 = if N == 0 {
+        0
+    } else {
+        packing::round_up(T::SIZE) * (N as u32 - 1) + T::SIZE
+    };
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> Type {
+        Type::Array(Box::new(T::hlsl_type()), N as u32)
+    }
+}
+
+/// the layout of `T` as the top level of a constant buffer
+pub fn layout<T: HlslType>() -> Layout {
+    match T::hlsl_type() {
+        Type::Struct(fields) => Layout::pack(&fields),
+        ty => Layout::pack(&[Field::new("value", ty)]),
+    }
+}
+
+/// compares `T` with the buffer `name` of a compiled shader
+pub fn check<T: HlslType>(bytecode: &[u8], name: &str) -> Result<(), String> {
+    let buffers = dxbc::constant_buffers(bytecode)?;
+    let buffer = buffers
+        .iter()
+        .find(|buffer| buffer.name == name)
+        .ok_or_else(|| format!("no constant buffer '{}' in the shader", name))?;
+    layout::<T>()
+        .check(&buffer.layout())
+        .map_err(|err| format!("{}: {}", name, err))
+}
+
+/// implements `HlslType` for a `#[repr(C)]` struct from its fields, in
+/// declaration order, and fails to compile if rust places any of them
+/// differently from hlsl. padding fields are left out.
+#[macro_export]
+macro_rules! constant_buffer {
+    ($name:ty { $($field:ident: $ty:ty),* $(,)? }) => {
+        impl $crate::cbuffer::HlslType for $name {
+            const SIZE: u32 = {
+                #[allow(unused_mut)]
+                let mut end = 0;
+                $(
+                    end = $crate::cbuffer::packing::place(
+                        end,
+                        <$ty as $crate::cbuffer::HlslType>::SIZE,
+                        <$ty as $crate::cbuffer::HlslType>::AGGREGATE,
+                    ) + <$ty as $crate::cbuffer::HlslType>::SIZE;
+                )*
+                end
+            };
+            const AGGREGATE: bool = true;
+            fn hlsl_type() -> $crate::cbuffer::packing::Type {
+                $crate::cbuffer::packing::Type::Struct(vec![$(
+                    $crate::cbuffer::packing::Field::new(
+                        stringify!($field),
+                        <$ty as $crate::cbuffer::HlslType>::hlsl_type(),
+                    )
+                ),*])
+            }
+        }
+
+        #[allow(unused_mut, unused_assignments)]
+        const _: () = {
+            let mut end = 0;
+            $(
+                let offset = $crate::cbuffer::packing::place(
+                    end,
+                    <$ty as $crate::cbuffer::HlslType>::SIZE,
+                    <$ty as $crate::cbuffer::HlslType>::AGGREGATE,
+                );
+                assert!(
+                    std::mem::offset_of!($name, $field) == offset as usize,
+                    concat!("`", stringify!($field), "` is not at its hlsl offset; pad before it")
+                );
+                assert!(
+                    std::mem::size_of::<$ty>() == <$ty as $crate::cbuffer::HlslType>::SIZE as usize,
+                    concat!("`", stringify!($field), "` has a different size in hlsl")
+                );
+                end = offset + <$ty as $crate::cbuffer::HlslType>::SIZE;
+            )*
+        };
+
+        // the listed types are the types of the fields
+        const _: fn(&$name) = |_value| {
+            $(let _: &$ty = &_value.$field;)*
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::packing::Member;
+    use super::*;
+
+    #[repr(C)]
+    struct Scene {
+        view: Mat4,
+        light: Vec3<f32>,
+        intensity: f32,
+        color: Vec3<f32>,
+        _pad: f32,
+        weights: [Vec4<f32>; 2],
+    }
+    crate::constant_buffer!(Scene {
+        view: Mat4,
+        light: Vec3<f32>,
+        intensity: f32,
+        color: Vec3<f32>,
+        weights: [Vec4<f32>; 2],
+    });
+
+    #[repr(C)]
+    struct Inner {
+        a: Vec2<f32>,
+        b: u32,
+    }
+    crate::constant_buffer!(Inner { a: Vec2<f32>, b: u32 });
+
+    #[repr(C)]
+    struct Outer {
+        x: f32,
+        _pad: [u32; 3],
+        inner: Inner,
+        y: f32,
+    }
+    crate::constant_buffer!(Outer {
+        x: f32,
+        inner: Inner,
+        y: f32
+    });
+
+    fn offsets(layout: &Layout) -> Vec<u32> {
+        layout.members.iter().map(|member| member.offset).collect()
+    }
+
+    #[test]
+    fn rust_structs() {
+        let scene = layout::<Scene>();
+        assert_eq!(offsets(&scene), vec![0, 64, 76, 80, 96]);
+        assert_eq!(Scene::SIZE, 128);
+        assert_eq!(std::mem::size_of::<Scene>(), 128);
+        // y fills the rest of the register inner started
+        assert_eq!(offsets(&layout::<Outer>()), vec![0, 16, 28]);
+        assert_eq!(Outer::SIZE, 32);
+        assert_eq!(<[f32; 3]>::SIZE, 36);
+        assert_eq!(layout::<Vec4<f32>>().size, 16);
+    }
+
+    fn put(rdef: &mut [u8], at: usize, value: u32) {
+        rdef[at..at + 4].copy_from_slice(&value.to_le_bytes())
+    }
+
+    fn push_string(rdef: &mut Vec<u8>, string: &str) -> u32 {
+        let at = rdef.len() as u32;
+        rdef.extend(string.as_bytes());
+        rdef.push(0);
+        at
+    }
+
+    /// appends `ty` as `D3D12_SHADER_TYPE_DESC` gives it, after the types
+    /// and the member table of a struct's fields, and returns its offset
+    fn push_type(rdef: &mut Vec<u8>, ty: &Type) -> u32 {
+        let scalar_type = |scalar: Scalar| match scalar {
+            Scalar::Bool => 1,
+            Scalar::Int => 2,
+            Scalar::Float => 3,
+            Scalar::Uint => 19,
+        };
+        // class, type, rows, columns, elements and members, and where the
+        // member table is
+        let (desc, table) = match *ty {
+            Type::Scalar(scalar) => ([0, scalar_type(scalar), 1, 1, 0, 0], 0),
+            Type::Vector(scalar, len) => ([1, scalar_type(scalar), 1, len as u16, 0, 0], 0),
+            Type::Matrix {
+                scalar,
+                rows,
+                columns,
+                major,
+            } => {
+                let class = if major == Major::Row { 2 } else { 3 };
+                (
+                    [
+                        class,
+                        scalar_type(scalar),
+                        rows as u16,
+                        columns as u16,
+                        0,
+                        0,
+                    ],
+                    0,
+                )
+            }
+            Type::Array(ref element, count) => {
+                let at = push_type(rdef, element) as usize;
+                let element: Vec<u8> = rdef[at..at + 36].to_vec();
+                let at = rdef.len();
+                rdef.extend(element);
+                rdef[at + 8..at + 10].copy_from_slice(&(count as u16).to_le_bytes());
+                return at as u32;
+            }
+            Type::Struct(ref fields) => {
+                let members: Vec<(u32, u32)> = fields
+                    .iter()
+                    .map(|field| (push_string(rdef, &field.name), push_type(rdef, &field.ty)))
+                    .collect();
+                let table = rdef.len() as u32;
+                let layout = Layout::pack(fields);
+                for ((name, ty), member) in members.iter().zip(layout.members.iter()) {
+                    for value in [*name, *ty, member.offset].iter() {
+                        rdef.extend(value.to_le_bytes());
+                    }
+                }
+                ([5, 0, 1, 0, 0, fields.len() as u16], table)
+            }
+        };
+        let at = rdef.len();
+        for value in desc.iter() {
+            rdef.extend(value.to_le_bytes());
+        }
+        rdef.extend(table.to_le_bytes());
+        rdef.extend([0; 20]);
+        at as u32
+    }
+
+    /// a shader model 5 container with nothing but the reflection of the
+    /// constant buffer `name`
+    fn reflected(name: &str, members: &[Member]) -> Vec<u8> {
+        const BUFFER_AT: usize = 60;
+        const VARIABLES_AT: usize = BUFFER_AT + 24;
+        let mut rdef = vec![0u8; VARIABLES_AT + 40 * members.len()];
+        put(&mut rdef, 0, 1);
+        put(&mut rdef, 4, BUFFER_AT as u32);
+        rdef[28..32].copy_from_slice(b"RD11");
+        for (i, size) in [60, 24, 32, 40, 36, 12].iter().enumerate() {
+            put(&mut rdef, 32 + i * 4, *size);
+        }
+        for (i, member) in members.iter().enumerate() {
+            let variable = VARIABLES_AT + i * 40;
+            let name = push_string(&mut rdef, &member.name);
+            let ty = push_type(&mut rdef, &member.ty);
+            put(&mut rdef, variable, name);
+            put(&mut rdef, variable + 4, member.offset);
+            put(&mut rdef, variable + 8, member.size);
+            put(&mut rdef, variable + 16, ty);
+        }
+        let buffer_name = push_string(&mut rdef, name);
+        put(&mut rdef, BUFFER_AT, buffer_name);
+        put(&mut rdef, BUFFER_AT + 4, members.len() as u32);
+        put(&mut rdef, BUFFER_AT + 8, VARIABLES_AT as u32);
+        let size = members
+            .iter()
+            .map(|member| member.offset + member.size)
+            .max();
+        put(
+            &mut rdef,
+            BUFFER_AT + 12,
+            packing::round_up(size.unwrap_or(0)),
+        );
+
+        let mut container = b"DXBC".to_vec();
+        container.extend([0; 16]);
+        container.extend(1u32.to_le_bytes());
+        container.extend((44 + rdef.len() as u32).to_le_bytes());
+        container.extend(1u32.to_le_bytes());
+        container.extend(36u32.to_le_bytes());
+        container.extend(b"RDEF");
+        container.extend((rdef.len() as u32).to_le_bytes());
+        container.extend(rdef);
+        container
+    }
+
+    /// where the RDEF data starts in a container from `reflected`
+    const RDEF_AT: usize = 44;
+
+    #[test]
+    fn checks_the_reflected_layout() {
+        let members = layout::<Scene>().members;
+        let bytecode = reflected("Scene", &members);
+        let buffers = dxbc::constant_buffers(&bytecode).unwrap();
+        assert_eq!(buffers[0].size, 128);
+        assert_eq!(buffers[0].layout(), layout::<Scene>());
+        assert_eq!(check::<Scene>(&bytecode, "Scene"), Ok(()));
+        assert_eq!(
+            check::<Scene>(&bytecode, "Lights"),
+            Err("no constant buffer 'Lights' in the shader".to_string())
+        );
+    }
+
+    #[test]
+    fn check_fails_on_a_mismatched_layout() {
+        // the shader declares `float4 light` where the struct has a float3
+        let mut members = layout::<Scene>().members;
+        members[1].ty = Type::Vector(Scalar::Float, 4);
+        members[1].size = 16;
+        members[2].offset = 80;
+        members[3].offset = 96;
+        members[4].offset = 112;
+        let bytecode = reflected("Scene", &members);
+        assert_eq!(
+            check::<Scene>(&bytecode, "Scene"),
+            Err(
+                "Scene: constant buffer mismatch: 'light' is 12 bytes, the shader expects 16, \
+                 'intensity' is at offset 76, the shader expects 80, \
+                 'color' is at offset 80, the shader expects 96, \
+                 'weights' is at offset 96, the shader expects 112"
+                    .to_string()
+            )
+        );
+        // and where a member is missing
+        let bytecode = reflected("Scene", &layout::<Scene>().members[..4]);
+        assert_eq!(
+            check::<Scene>(&bytecode, "Scene"),
+            Err("Scene: constant buffer mismatch: 'weights' is not in the shader".to_string())
+        );
+        // the same sizes and offsets, but ints
+        let mut members = layout::<Scene>().members;
+        members[4].ty = Type::Array(Box::new(Type::Vector(Scalar::Int, 4)), 2);
+        let bytecode = reflected("Scene", &members);
+        assert_eq!(
+            check::<Scene>(&bytecode, "Scene"),
+            Err(
+                "Scene: constant buffer mismatch: 'weights' is float4[2], the shader expects int4[2]"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn checks_nested_structs() {
+        let members = layout::<Outer>().members;
+        let bytecode = reflected("Outer", &members);
+        let buffers = dxbc::constant_buffers(&bytecode).unwrap();
+        assert_eq!(buffers[0].layout(), layout::<Outer>());
+        assert_eq!(check::<Outer>(&bytecode, "Outer"), Ok(()));
+        // the shader's struct has an int where ours has a uint
+        let mut members = layout::<Outer>().members;
+        members[1].ty = Type::Struct(vec![
+            Field::new("a", Type::Vector(Scalar::Float, 2)),
+            Field::new("b", Type::Scalar(Scalar::Int)),
+        ]);
+        let bytecode = reflected("Outer", &members);
+        assert_eq!(
+            check::<Outer>(&bytecode, "Outer"),
+            Err(
+                "Outer: constant buffer mismatch: 'inner' is struct { float2 a; uint b; }, \
+                 the shader expects struct { float2 a; int b; }"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn counts_beyond_the_reflection_data() {
+        let members = layout::<Outer>().members;
+        let bytecode = reflected("Outer", &members);
+        let variable_count = RDEF_AT + 60 + 4;
+        let mut bytes = bytecode.clone();
+        put(&mut bytes, variable_count, u32::MAX);
+        assert_eq!(
+            dxbc::constant_buffers(&bytes),
+            Err("invalid dxbc: 4294967295 variables at 84 are out of range".to_string())
+        );
+        let mut bytes = bytecode.clone();
+        put(&mut bytes, RDEF_AT, u32::MAX);
+        assert_eq!(
+            dxbc::constant_buffers(&bytes),
+            Err("invalid dxbc: 4294967295 buffers at 60 are out of range".to_string())
+        );
+        // the member count of inner's struct type
+        let at = RDEF_AT + 84 + 40 + 16;
+        let mut ty = [0; 4];
+        ty.copy_from_slice(&bytecode[at..at + 4]);
+        let mut bytes = bytecode.clone();
+        let members = RDEF_AT + u32::from_le_bytes(ty) as usize + 10;
+        bytes[members..members + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(dxbc::constant_buffers(&bytes)
+            .unwrap_err()
+            .starts_with("Outer.inner: invalid dxbc: 65535 members at"));
+    }
+}
@@ -0,0 +1,560 @@
+//! how hlsl lays out a constant buffer. members fill 16 byte registers in
+//! order, but none straddles two registers; arrays, matrices and structs
+//! start a new register, and so does every array element. unlike
+//! `#[repr(C)]`, the next member may use what is left of an aggregate's last
+//! register.
+//!
+//! this file needs nothing but std, so that the build script can use it too.
+
+use std::fmt;
+
+pub const REGISTER_SIZE: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    Float,
+    Int,
+    Uint,
+    /// 4 bytes, unlike rust's
+    Bool,
+}
+
+/// how a matrix is split into registers; hlsl's default is `Column`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Major {
+    Row,
+    Column,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Scalar(Scalar),
+    Vector(Scalar, u32),
+    Matrix {
+        scalar: Scalar,
+        rows: u32,
+        columns: u32,
+        major: Major,
+    },
+    Array(Box<Type>, u32),
+    Struct(Vec<Field>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+}
+
+/// a member placed by `Layout::pack`, or by the compiler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub ty: Type,
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Layout {
+    pub members: Vec<Member>,
+    /// where the last member ends
+    pub size: u32,
+}
+
+pub const fn round_up(size: u32) -> u32 {
+    size.div_ceil(REGISTER_SIZE) * REGISTER_SIZE
+}
+
+/// the offset of a member of `size` bytes after a member that ended at `end`
+pub const fn place(end: u32, size: u32, aggregate: bool) -> u32 {
+    let used = end % REGISTER_SIZE;
+    if aggregate || (used != 0 && used + size > REGISTER_SIZE) {
+        round_up(end)
+    } else {
+        end
+    }
+}
+
+impl Scalar {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scalar::Float => "float",
+            Scalar::Int => "int",
+            Scalar::Uint => "uint",
+            Scalar::Bool => "bool",
+        }
+    }
+}
+
+impl Field {
+    pub fn new(name: &str, ty: Type) -> Field {
+        Field {
+            name: name.to_string(),
+            ty: ty,
+        }
+    }
+}
+
+impl Type {
+    /// the registers a matrix takes and the scalars in each
+    fn matrix_registers(rows: u32, columns: u32, major: Major) -> (u32, u32) {
+        match major {
+            Major::Row => (rows, columns),
+            Major::Column => (columns, rows),
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        match *self {
+            Type::Scalar(_) => 4,
+            Type::Vector(_, len) => 4 * len,
+            Type::Matrix {
+                rows,
+                columns,
+                major,
+                ..
+            } => {
+                let (registers, len) = Type::matrix_registers(rows, columns, major);
+                (registers.max(1) - 1) * REGISTER_SIZE + 4 * len
+            }
+            Type::Array(_, 0) => 0,
+            Type::Array(ref element, count) => element.stride() * (count - 1) + element.size(),
+            Type::Struct(ref fields) => Layout::pack(fields).size,
+        }
+    }
+
+    /// the distance between array elements of this type
+    pub fn stride(&self) -> u32 {
+        round_up(self.size())
+    }
+
+    pub fn is_aggregate(&self) -> bool {
+        match *self {
+            Type::Scalar(_) | Type::Vector(..) => false,
+            Type::Matrix { .. } | Type::Array(..) | Type::Struct(_) => true,
+        }
+    }
+
+    /// the size without any padding, as a `#[repr(C)]` struct of 4 byte
+    /// fields holds it
+    pub fn tight_size(&self) -> u32 {
+        match *self {
+            Type::Scalar(_) => 4,
+            Type::Vector(_, len) => 4 * len,
+            Type::Matrix { rows, columns, .. } => 4 * rows * columns,
+            Type::Array(ref element, count) => element.tight_size() * count,
+            Type::Struct(ref fields) => fields.iter().map(|field| field.ty.tight_size()).sum(),
+        }
+    }
+
+    /// the hlsl declaration of `name` as this type
+    pub fn declare(&self, name: &str) -> String {
+        match *self {
+            Type::Array(ref element, count) => element.declare(&format!("{}[{}]", name, count)),
+            _ => format!("{} {}", self, name),
+        }
+    }
+
+    /// copies `tight` data of this type to `packed`, which starts where the
+    /// type is placed and is at least `size` bytes
+    fn write(&self, tight: &[u8], packed: &mut [u8]) {
+        match *self {
+            Type::Scalar(_) | Type::Vector(..) => packed[..tight.len()].copy_from_slice(tight),
+            Type::Matrix {
+                rows,
+                columns,
+                major,
+                ..
+            } => {
+                let (_, len) = Type::matrix_registers(rows, columns, major);
+                let len = 4 * len as usize;
+                for (i, register) in tight.chunks(len).enumerate() {
+                    let at = i * REGISTER_SIZE as usize;
+                    packed[at..at + len].copy_from_slice(register);
+                }
+            }
+            Type::Array(ref element, _) => {
+                let (len, stride) = (element.tight_size() as usize, element.stride() as usize);
+                for (i, item) in tight.chunks(len).enumerate() {
+                    element.write(item, &mut packed[i * stride..]);
+                }
+            }
+            Type::Struct(ref fields) => {
+                let mut from = 0;
+                for (field, member) in fields.iter().zip(Layout::pack(fields).members) {
+                    let len = field.ty.tight_size() as usize;
+                    field.ty.write(
+                        &tight[from..from + len],
+                        &mut packed[member.offset as usize..],
+                    );
+                    from += len;
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    /// the hlsl name; `declare` puts an array's count after the name instead
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Scalar(scalar) => write!(f, "{}", scalar.name()),
+            Type::Vector(scalar, len) => write!(f, "{}{}", scalar.name(), len),
+            Type::Matrix {
+                scalar,
+                rows,
+                columns,
+                major,
+            } => {
+                if major == Major::Row {
+                    write!(f, "row_major ")?;
+                }
+                write!(f, "{}{}x{}", scalar.name(), rows, columns)
+            }
+            Type::Array(ref element, count) => write!(f, "{}[{}]", element, count),
+            Type::Struct(ref fields) => {
+                write!(f, "struct {{")?;
+                for field in fields {
+                    write!(f, " {};", field.ty.declare(&field.name))?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+impl Layout {
+    pub fn pack(fields: &[Field]) -> Layout {
+        let mut layout = Layout::default();
+        for field in fields {
+            let size = field.ty.size();
+            let offset = place(layout.size, size, field.ty.is_aggregate());
+            layout.members.push(Member {
+                name: field.name.clone(),
+                ty: field.ty.clone(),
+                offset: offset,
+                size: size,
+            });
+            layout.size = offset + size;
+        }
+        layout
+    }
+
+    /// the size of the buffer to create, in whole registers
+    pub fn buffer_size(&self) -> u32 {
+        round_up(self.size)
+    }
+
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// compares member offsets, sizes and types with `shader`, the layout
+    /// the compiler chose, and lists every difference
+    pub fn check(&self, shader: &Layout) -> Result<(), String> {
+        let mut errors = vec![];
+        for member in self.members.iter() {
+            match shader.member(&member.name) {
+                None => errors.push(format!("'{}' is not in the shader", member.name)),
+                Some(expected) if expected.offset != member.offset => errors.push(format!(
+                    "'{}' is at offset {}, the shader expects {}",
+                    member.name, member.offset, expected.offset
+                )),
+                Some(expected) if expected.size != member.size => errors.push(format!(
+                    "'{}' is {} bytes, the shader expects {}",
+                    member.name, member.size, expected.size
+                )),
+                Some(expected) if expected.ty != member.ty => errors.push(format!(
+                    "'{}' is {}, the shader expects {}",
+                    member.name, member.ty, expected.ty
+                )),
+                Some(_) => (),
+            }
+        }
+        for expected in shader.members.iter() {
+            if self.member(&expected.name).is_none() {
+                errors.push(format!("'{}' of the shader is missing", expected.name));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("constant buffer mismatch: {}", errors.join(", ")))
+        }
+    }
+
+    /// `tight` data, with the members one after another without padding,
+    /// moved to where hlsl reads them in a buffer of `buffer_size` bytes
+    pub fn write(&self, tight: &[u8]) -> Result<Vec<u8>, String> {
+        let size: u32 = self
+            .members
+            .iter()
+            .map(|member| member.ty.tight_size())
+            .sum();
+        if tight.len() != size as usize {
+            return Err(format!(
+                "expected {} bytes of constants, got {}",
+                size,
+                tight.len()
+            ));
+        }
+        let mut packed = vec![0; self.buffer_size() as usize];
+        let mut from = 0;
+        for member in self.members.iter() {
+            let len = member.ty.tight_size() as usize;
+            member.ty.write(
+                &tight[from..from + len],
+                &mut packed[member.offset as usize..],
+            );
+            from += len;
+        }
+        Ok(packed)
+    }
+}
+
+impl fmt::Display for Layout {
+    /// one line per member, like the listing of fxc
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for member in self.members.iter() {
+            writeln!(
+                f,
+                "{}; // offset {} size {}",
+                member.ty.declare(&member.name),
+                member.offset,
+                member.size
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(len: u32) -> Type {
+        match len {
+            1 => Type::Scalar(Scalar::Float),
+            _ => Type::Vector(Scalar::Float, len),
+        }
+    }
+
+    fn matrix(rows: u32, columns: u32, major: Major) -> Type {
+        Type::Matrix {
+            scalar: Scalar::Float,
+            rows: rows,
+            columns: columns,
+            major: major,
+        }
+    }
+
+    fn array(element: Type, count: u32) -> Type {
+        Type::Array(Box::new(element), count)
+    }
+
+    fn pack(types: &[Type]) -> Layout {
+        let fields: Vec<Field> = types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| Field::new(&format!("m{}", i), ty.clone()))
+            .collect();
+        Layout::pack(&fields)
+    }
+
+    /// the offsets of the members and where the last one ends
+    fn offsets(types: &[Type]) -> (Vec<u32>, u32) {
+        let layout = pack(types);
+        let offsets = layout.members.iter().map(|member| member.offset).collect();
+        (offsets, layout.size)
+    }
+
+    // the layouts below are those fxc reports for the same declarations
+
+    #[test]
+    fn vectors_do_not_straddle_registers() {
+        // float4 a; float2 b; float2 c;
+        assert_eq!(
+            offsets(&[float(4), float(2), float(2)]),
+            (vec![0, 16, 24], 32)
+        );
+        // float2 a; float4 b; float2 c;
+        assert_eq!(
+            offsets(&[float(2), float(4), float(2)]),
+            (vec![0, 16, 32], 40)
+        );
+        // float a; float2 b; float c;
+        assert_eq!(
+            offsets(&[float(1), float(2), float(1)]),
+            (vec![0, 4, 12], 16)
+        );
+        // float2 a; float3 b; would straddle at 8
+        assert_eq!(offsets(&[float(2), float(3)]), (vec![0, 16], 28));
+        // float3 a; float2 b; would straddle at 12
+        assert_eq!(offsets(&[float(3), float(2)]), (vec![0, 16], 24));
+        // float a; float4 b;
+        assert_eq!(offsets(&[float(1), float(4)]), (vec![0, 16], 32));
+    }
+
+    #[test]
+    fn float3_then_float_share_a_register() {
+        // float3 a; float b; float3 c; float2 d;
+        assert_eq!(
+            offsets(&[float(3), float(1), float(3), float(2)]),
+            (vec![0, 12, 16, 32], 40)
+        );
+        assert_eq!(pack(&[float(3), float(1)]).buffer_size(), 16);
+    }
+
+    #[test]
+    fn array_elements_take_whole_registers() {
+        // float a[4]: 3 padded elements and a last one of 4 bytes
+        assert_eq!(array(float(1), 4).size(), 52);
+        assert_eq!(float(1).stride(), 16);
+        // float a; float b[4]; float c; c uses the rest of b's last register
+        assert_eq!(
+            offsets(&[float(1), array(float(1), 4), float(1)]),
+            (vec![0, 16, 68], 72)
+        );
+        assert_eq!(array(float(2), 3).size(), 40);
+        assert_eq!(array(float(3), 2).size(), 28);
+        assert_eq!(array(float(4), 3).size(), 48);
+        assert_eq!(array(float(4), 0).size(), 0);
+        // float2 a[2][2]
+        assert_eq!(array(array(float(2), 2), 2).size(), 56);
+    }
+
+    #[test]
+    fn matrices_take_a_register_per_row_or_column() {
+        assert_eq!(matrix(4, 4, Major::Column).size(), 64);
+        assert_eq!(matrix(3, 3, Major::Column).size(), 44);
+        // float4x3 is three columns of four, or four rows of three
+        assert_eq!(matrix(4, 3, Major::Column).size(), 44 + 4);
+        assert_eq!(matrix(4, 3, Major::Row).size(), 60);
+        assert_eq!(matrix(2, 4, Major::Column).size(), 56);
+        // float a; row_major float2x2 m; float2 b;
+        assert_eq!(
+            offsets(&[float(1), matrix(2, 2, Major::Row), float(2)]),
+            (vec![0, 16, 40], 48)
+        );
+    }
+
+    #[test]
+    fn nested_structs_start_a_register() {
+        // struct { float3 x; float2 y; } is 24 bytes, not padded at its end
+        let inner = Type::Struct(vec![Field::new("x", float(3)), Field::new("y", float(2))]);
+        assert_eq!(inner.size(), 24);
+        // float a; S s; float2 b;
+        assert_eq!(
+            offsets(&[float(1), inner.clone(), float(2)]),
+            (vec![0, 16, 40], 48)
+        );
+        // a struct after a partly used register does not fill it
+        let small = Type::Struct(vec![Field::new("x", float(1))]);
+        assert_eq!(offsets(&[float(2), small, float(1)]), (vec![0, 16, 20], 24));
+        assert_eq!(array(inner, 2).size(), 56);
+    }
+
+    #[test]
+    fn placing() {
+        assert_eq!(round_up(0), 0);
+        assert_eq!(round_up(16), 16);
+        assert_eq!(round_up(17), 32);
+        assert_eq!(place(12, 4, false), 12);
+        assert_eq!(place(12, 8, false), 16);
+        assert_eq!(place(4, 4, true), 16);
+        assert_eq!(place(0, 64, true), 0);
+        assert_eq!(place(16, 64, false), 16);
+    }
+
+    #[test]
+    fn declarations() {
+        assert_eq!(array(array(float(3), 3), 2).declare("w"), "float3 w[2][3]");
+        let layout = Layout::pack(&[
+            Field::new("view", matrix(4, 4, Major::Row)),
+            Field::new("w", array(Type::Scalar(Scalar::Uint), 2)),
+        ]);
+        assert_eq!(
+            layout.to_string(),
+            "row_major float4x4 view; // offset 0 size 64\nuint w[2]; // offset 64 size 20\n"
+        );
+        assert_eq!(layout.buffer_size(), 96);
+    }
+
+    #[test]
+    fn write_pads_the_members() {
+        let layout = pack(&[
+            float(1),
+            array(float(1), 2),
+            float(3),
+            matrix(2, 3, Major::Row),
+        ]);
+        let tight: Vec<u8> = (1..=12u32).flat_map(|v| (v as f32).to_le_bytes()).collect();
+        let packed = layout.write(&tight).unwrap();
+        assert_eq!(packed.len() as u32, layout.buffer_size());
+        let floats: Vec<f32> = packed
+            .chunks(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        #[rustfmt::skip]
+        let expected = vec![
+            1.0, 0.0, 0.0, 0.0,
+            2.0, 0.0, 0.0, 0.0,
+            3.0, 4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0, 0.0,
+            10.0, 11.0, 12.0, 0.0,
+        ];
+        assert_eq!(floats, expected);
+        assert_eq!(
+            layout.write(&tight[4..]),
+            Err("expected 48 bytes of constants, got 44".to_string())
+        );
+    }
+
+    #[test]
+    fn check_lists_every_difference() {
+        let fields = |types: &[(&str, Type)]| {
+            let fields: Vec<Field> = types
+                .iter()
+                .map(|(name, ty)| Field::new(name, ty.clone()))
+                .collect();
+            Layout::pack(&fields)
+        };
+        let ours = fields(&[("a", float(3)), ("b", float(2)), ("c", float(1))]);
+        assert_eq!(ours.check(&ours), Ok(()));
+        let shader = fields(&[("a", float(3)), ("b", float(3)), ("d", float(1))]);
+        assert_eq!(
+            ours.check(&shader),
+            Err(
+                "constant buffer mismatch: 'b' is 8 bytes, the shader expects 12, \
+                 'c' is not in the shader, 'd' of the shader is missing"
+                    .to_string()
+            )
+        );
+        // a float2 leaves room for b in the first register
+        let shader = fields(&[("a", float(2)), ("b", float(2)), ("c", float(1))]);
+        assert_eq!(
+            ours.check(&shader),
+            Err(
+                "constant buffer mismatch: 'a' is 12 bytes, the shader expects 8, \
+                 'b' is at offset 16, the shader expects 8, \
+                 'c' is at offset 24, the shader expects 16"
+                    .to_string()
+            )
+        );
+        // the same places, other types
+        let shader = fields(&[
+            ("a", Type::Vector(Scalar::Int, 3)),
+            ("b", float(2)),
+            ("c", Type::Scalar(Scalar::Uint)),
+        ]);
+        assert_eq!(
+            ours.check(&shader),
+            Err(
+                "constant buffer mismatch: 'a' is float3, the shader expects int3, \
+                 'c' is float, the shader expects uint"
+                    .to_string()
+            )
+        );
+    }
+}
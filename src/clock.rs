@@ -0,0 +1,122 @@
+//! where the main loop gets the time from
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+pub trait Clock {
+    /// time since an arbitrary, fixed origin
+    fn now(&self) -> Duration;
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// `Instant` based clock, available everywhere
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// clock backed by `QueryPerformanceCounter`
+#[cfg(windows)]
+pub struct PerformanceCounter {
+    frequency: u64,
+    origin: u64,
+}
+
+#[cfg(windows)]
+impl PerformanceCounter {
+    pub fn new() -> Self {
+        use winapi::um::profileapi::QueryPerformanceFrequency;
+        let mut frequency = unsafe { std::mem::zeroed() };
+        unsafe { QueryPerformanceFrequency(&mut frequency) };
+        PerformanceCounter {
+            frequency: unsafe { *frequency.QuadPart() } as u64,
+            origin: Self::counter(),
+        }
+    }
+
+    fn counter() -> u64 {
+        use winapi::um::profileapi::QueryPerformanceCounter;
+        let mut count = unsafe { std::mem::zeroed() };
+        unsafe { QueryPerformanceCounter(&mut count) };
+        unsafe { *count.QuadPart() as u64 }
+    }
+}
+
+#[cfg(windows)]
+impl Default for PerformanceCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(windows)]
+impl Clock for PerformanceCounter {
+    fn now(&self) -> Duration {
+        let ticks = Self::counter() - self.origin;
+        let secs = ticks / self.frequency;
+        let nanos = (ticks % self.frequency) * 1_000_000_000 / self.frequency;
+        Duration::new(secs, nanos as u32)
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// the most precise clock of the platform
+#[cfg(windows)]
+pub type DefaultClock = PerformanceCounter;
+#[cfg(not(windows))]
+pub type DefaultClock = SystemClock;
+
+/// clock that only moves when told to. clones share the same time, so a
+/// test can keep one and advance the time seen by the loop.
+#[derive(Debug, Clone, Default)]
+pub struct FakeClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl FakeClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    /// sleeping moves the time forward immediately
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration);
+    }
+}
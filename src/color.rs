@@ -0,0 +1,565 @@
+//! colors with linear components, which is what blending and shading want.
+//! srgb, hex strings, hsv and hsl describe the srgb encoded values, as
+//! color pickers do, and convert on the way in and out. the gamut is rec.709
+//! unless `convert` says otherwise.
+//!
+//! for hdr output, `pq_encode` and `hlg_encode` are the transfer functions
+//! of rec.2100, and `to_hdr10` prepares a color for a swap chain in
+//! `DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020`.
+
+use std::ops::Mul;
+
+use crate::math::quantize::{Half4, Unorm8x4};
+use crate::math::{ApproxEq, Mat3, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// the primaries of a color space, all with the d65 white point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gamut {
+    /// srgb and hdtv
+    Rec709,
+    /// uhdtv and hdr10
+    Rec2020,
+    /// dci-p3's primaries with d65 white, as p3 displays and hdr mastering
+    /// use them
+    DciP3,
+}
+
+const D65: (f32, f32) = (0.3127, 0.3290);
+
+impl Gamut {
+    /// the chromaticities of red, green and blue
+    fn primaries(self) -> [(f32, f32); 3] {
+        match self {
+            Gamut::Rec709 => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)],
+            Gamut::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)],
+            Gamut::DciP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+        }
+    }
+
+    /// the matrix that takes linear rgb to xyz, `rgb * m`
+    pub fn to_xyz(self) -> Mat3 {
+        let xyz = |(x, y): (f32, f32)| Vec3::new(x / y, 1.0, (1.0 - x - y) / y);
+        let [r, g, b] = self.primaries();
+        let primaries = Mat3::from_rows(xyz(r), xyz(g), xyz(b));
+        // scale the primaries so that they add up to the white point
+        let inverse = primaries
+            .inverse()
+            .expect("failed: primaries are independent");
+        let scale = xyz(D65) * inverse;
+        Mat3::from_rows(
+            primaries.row(0) * scale.x,
+            primaries.row(1) * scale.y,
+            primaries.row(2) * scale.z,
+        )
+    }
+
+    /// the matrix that takes linear rgb in `self` to linear rgb in `to`
+    pub fn conversion(self, to: Gamut) -> Mat3 {
+        let from_xyz = to
+            .to_xyz()
+            .inverse()
+            .expect("failed: primaries are independent");
+        self.to_xyz() * from_xyz
+    }
+}
+
+/// an srgb encoded component as linear
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// a linear component as srgb encoded
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// the constants of smpte st 2084
+const PQ_M1: f32 = 2610.0 / 16384.0;
+const PQ_M2: f32 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f32 = 3424.0 / 4096.0;
+const PQ_C2: f32 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f32 = 2392.0 / 4096.0 * 32.0;
+
+/// the luminance pq encodes as 1
+pub const PQ_MAX_NITS: f32 = 10000.0;
+
+/// the pq signal for `nits` of display luminance
+pub fn pq_encode(nits: f32) -> f32 {
+    let y = (nits / PQ_MAX_NITS).clamp(0.0, 1.0).powf(PQ_M1);
+    ((PQ_C1 + PQ_C2 * y) / (1.0 + PQ_C3 * y)).powf(PQ_M2)
+}
+
+/// the display luminance in nits of a pq signal
+pub fn pq_decode(signal: f32) -> f32 {
+    let e = signal.clamp(0.0, 1.0).powf(1.0 / PQ_M2);
+    ((e - PQ_C1).max(0.0) / (PQ_C2 - PQ_C3 * e)).powf(1.0 / PQ_M1) * PQ_MAX_NITS
+}
+
+// the constants of arib std-b67, in rec.2100
+const HLG_A: f32 = 0.17883277;
+const HLG_B: f32 = 0.28466892;
+const HLG_C: f32 = 0.559_910_7;
+
+/// the hlg signal of a scene light in [0, 1]
+pub fn hlg_encode(light: f32) -> f32 {
+    let light = light.clamp(0.0, 1.0);
+    if light <= 1.0 / 12.0 {
+        (3.0 * light).sqrt()
+    } else {
+        HLG_A * (12.0 * light - HLG_B).ln() + HLG_C
+    }
+}
+
+/// the scene light of an hlg signal
+pub fn hlg_decode(signal: f32) -> f32 {
+    let signal = signal.clamp(0.0, 1.0);
+    if signal <= 0.5 {
+        signal * signal / 3.0
+    } else {
+        (((signal - HLG_C) / HLG_A).exp() + HLG_B) / 12.0
+    }
+}
+
+fn hex_digits(text: &str, range: std::ops::Range<usize>) -> Option<u8> {
+    u8::from_str_radix(text.get(range)?, 16).ok()
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color {
+            r: r,
+            g: g,
+            b: b,
+            a: a,
+        }
+    }
+
+    /// opaque
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color::new(r, g, b, 1.0)
+    }
+
+    /// from srgb encoded components; alpha is linear either way
+    pub fn from_srgb(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+    }
+
+    /// the srgb encoded components, for a target that is not `_SRGB`
+    pub fn to_srgb(self) -> Color {
+        Color::new(
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        )
+    }
+
+    pub fn from_srgb8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color::from_srgb(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    /// the srgb encoded bytes, rounded and clamped
+    pub fn to_srgb8(self) -> [u8; 4] {
+        let c = self.to_srgb();
+        [c.r, c.g, c.b, c.a].map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// `0xrrggbbaa`, srgb encoded
+    pub fn from_srgb_u32(rgba: u32) -> Color {
+        let [r, g, b, a] = rgba.to_be_bytes();
+        Color::from_srgb8(r, g, b, a)
+    }
+
+    /// `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`, srgb encoded; the `#` may
+    /// be left out
+    pub fn from_hex(text: &str) -> Result<Color, String> {
+        let digits = text.strip_prefix('#').unwrap_or(text);
+        let bytes: Option<Vec<u8>> = match digits.len() {
+            _ if !digits.chars().all(|c| c.is_ascii_hexdigit()) => None,
+            3 | 4 => (0..digits.len())
+                .map(|i| hex_digits(digits, i..i + 1).map(|d| d * 0x11))
+                .collect(),
+            6 | 8 => (0..digits.len() / 2)
+                .map(|i| hex_digits(digits, 2 * i..2 * i + 2))
+                .collect(),
+            _ => None,
+        };
+        match bytes.as_deref() {
+            Some(&[r, g, b]) => Ok(Color::from_srgb8(r, g, b, 0xff)),
+            Some(&[r, g, b, a]) => Ok(Color::from_srgb8(r, g, b, a)),
+            _ => Err(format!("invalid hex color '{}'", text)),
+        }
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when it is not opaque
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.to_srgb8();
+        if a == 0xff {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    /// hue in degrees, saturation and value in [0, 1]
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32, alpha: f32) -> Color {
+        let f = |n: f32| {
+            let k = (n + hue / 60.0).rem_euclid(6.0);
+            value - value * saturation * k.min(4.0 - k).clamp(0.0, 1.0)
+        };
+        Color::from_srgb(f(5.0), f(3.0), f(1.0), alpha)
+    }
+
+    /// `(hue, saturation, value)`; grays have a hue of 0
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// hue in degrees, saturation and lightness in [0, 1]
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Color {
+        let a = saturation * lightness.min(1.0 - lightness);
+        let f = |n: f32| {
+            let k = (n + hue / 30.0).rem_euclid(12.0);
+            lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+        };
+        Color::from_srgb(f(0.0), f(8.0), f(4.0), alpha)
+    }
+
+    /// `(hue, saturation, lightness)`; grays have a hue of 0
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if lightness > 0.0 && lightness < 1.0 {
+            (max - lightness) / lightness.min(1.0 - lightness)
+        } else {
+            0.0
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// the hue of the srgb encoded color, and its largest and smallest
+    /// components
+    fn hue(self) -> (f32, f32, f32) {
+        let c = self.to_srgb();
+        let max = c.r.max(c.g).max(c.b);
+        let min = c.r.min(c.g).min(c.b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == c.r {
+            60.0 * ((c.g - c.b) / chroma).rem_euclid(6.0)
+        } else if max == c.g {
+            60.0 * ((c.b - c.r) / chroma + 2.0)
+        } else {
+            60.0 * ((c.r - c.g) / chroma + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    /// the color times its alpha, for `D3D12_BLEND_ONE` blending
+    pub fn premultiply(self) -> Color {
+        Color::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    /// the inverse of `premultiply`; a transparent color becomes
+    /// `TRANSPARENT`
+    pub fn unpremultiply(self) -> Color {
+        if self.a == 0.0 {
+            Color::TRANSPARENT
+        } else {
+            Color::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+        }
+    }
+
+    pub fn lerp(self, to: Color, t: f32) -> Color {
+        Color::from(Vec4::from(self).lerp(Vec4::from(to), t))
+    }
+
+    /// the same color with rgb in the `to` gamut; components outside of [0,
+    /// 1] are colors `to` cannot show
+    pub fn convert(self, from: Gamut, to: Gamut) -> Color {
+        let rgb = Vec3::new(self.r, self.g, self.b) * from.conversion(to);
+        Color::new(rgb.x, rgb.y, rgb.z, self.a)
+    }
+
+    /// the pq encoded rec.2020 color of an scrgb one, where 1 is
+    /// `white_nits`, for an hdr10 swap chain
+    pub fn to_hdr10(self, white_nits: f32) -> Color {
+        let c = self.convert(Gamut::Rec709, Gamut::Rec2020);
+        Color::new(
+            pq_encode(c.r * white_nits),
+            pq_encode(c.g * white_nits),
+            pq_encode(c.b * white_nits),
+            self.a,
+        )
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+    /// scales rgb, leaving alpha as it is
+    fn mul(self, rhs: f32) -> Color {
+        Color::new(self.r * rhs, self.g * rhs, self.b * rhs, self.a)
+    }
+}
+
+impl From<Color> for Vec4<f32> {
+    fn from(c: Color) -> Vec4<f32> {
+        Vec4::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<Vec4<f32>> for Color {
+    fn from(v: Vec4<f32>) -> Color {
+        Color::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> [f32; 4] {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+/// the linear components as `DXGI_FORMAT_R8G8B8A8_UNORM`, for a vertex color;
+/// input layouts have no srgb formats, so `to_srgb8` is only for textures
+impl From<Color> for Unorm8x4 {
+    fn from(c: Color) -> Unorm8x4 {
+        Unorm8x4::from(Vec4::from(c))
+    }
+}
+
+/// `DXGI_FORMAT_R16G16B16A16_FLOAT`, which keeps hdr values above 1
+impl From<Color> for Half4 {
+    fn from(c: Color) -> Half4 {
+        Half4::from(Vec4::from(c))
+    }
+}
+
+impl ApproxEq for Color {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+        Vec4::from(*self).approx_eq_eps(&Vec4::from(*other), epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::approx::assert_approx_eq;
+
+    #[test]
+    fn srgb_reference_values() {
+        assert_approx_eq!(srgb_to_linear(0.5), 0.214_041_14, 1e-6);
+        assert_approx_eq!(linear_to_srgb(0.214_041_14), 0.5, 1e-6);
+        assert_approx_eq!(linear_to_srgb(0.5), 0.735_357, 1e-6);
+        // the linear segment, and the ends
+        assert_eq!(srgb_to_linear(0.04), 0.04 / 12.92);
+        assert_eq!(linear_to_srgb(0.001), 0.001 * 12.92);
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert_approx_eq!(srgb_to_linear(1.0), 1.0, 1e-6);
+        assert_approx_eq!(linear_to_srgb(1.0), 1.0, 1e-6);
+        for i in 0..=255u8 {
+            assert_eq!(Color::from_srgb8(i, i, i, i).to_srgb8(), [i; 4]);
+        }
+    }
+
+    #[test]
+    fn hex() {
+        let cases = [
+            ("#fff", [0xff, 0xff, 0xff, 0xff]),
+            ("#1234", [0x11, 0x22, 0x33, 0x44]),
+            ("ff8000", [0xff, 0x80, 0x00, 0xff]),
+            ("#00000080", [0x00, 0x00, 0x00, 0x80]),
+            ("#AbCdEf", [0xab, 0xcd, 0xef, 0xff]),
+        ];
+        for &(text, bytes) in cases.iter() {
+            assert_eq!(Color::from_hex(text).unwrap().to_srgb8(), bytes, "{}", text);
+        }
+        assert_eq!(Color::from_hex("#ff8000").unwrap().to_hex(), "#ff8000");
+        assert_eq!(Color::from_hex("#0000ff80").unwrap().to_hex(), "#0000ff80");
+        assert_eq!(Color::from_srgb_u32(0x336699ff).to_hex(), "#336699");
+        for &bad in [
+            "", "#", "#12345", "#ggg", "##fff", "#+f+f+f", "#ééé", " #fff",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                Color::from_hex(bad).unwrap_err(),
+                format!("invalid hex color '{}'", bad)
+            );
+        }
+    }
+
+    #[test]
+    fn hsv_and_hsl_reference_values() {
+        // #4080bf is hsl(210, 50%, 50%) and hsv(210, 66.7%, 75%)
+        let c = Color::from_hsl(210.0, 0.5, 0.5, 1.0);
+        assert_eq!(c.to_hex(), "#4080bf");
+        let (h, s, v) = c.to_hsv();
+        assert_approx_eq!([h, s, v][..], [210.0, 2.0 / 3.0, 0.75][..]);
+        assert_eq!(
+            Color::from_hsv(210.0, 2.0 / 3.0, 0.75, 1.0).to_hex(),
+            "#4080bf"
+        );
+        assert_approx_eq!(Color::from_hsv(120.0, 1.0, 1.0, 1.0), Color::GREEN);
+        assert_approx_eq!(Color::from_hsl(240.0, 1.0, 0.5, 1.0), Color::BLUE);
+        // hues wrap
+        assert_approx_eq!(Color::from_hsv(-360.0, 1.0, 1.0, 1.0), Color::RED);
+        assert_approx_eq!(Color::from_hsl(480.0, 1.0, 0.5, 1.0), Color::GREEN);
+        assert_eq!(
+            Color::from_hsl(0.0, 0.0, 0.5, 1.0).to_srgb8(),
+            [128, 128, 128, 255]
+        );
+        assert_eq!(
+            Color::from_hex("#808080").unwrap().to_hsv(),
+            (0.0, 0.0, 128.0 / 255.0)
+        );
+        assert_eq!(Color::BLACK.to_hsl(), (0.0, 0.0, 0.0));
+        assert_eq!(Color::WHITE.to_hsl().1, 0.0);
+    }
+
+    #[test]
+    fn hsv_and_hsl_round_trip() {
+        for i in 0..36 {
+            for &(s, v) in [(0.6, 0.8), (1.0, 1.0), (0.2, 0.3)].iter() {
+                let c = Color::from_hsv(i as f32 * 10.0, s, v, 0.5);
+                let (h, s, v) = c.to_hsv();
+                assert_approx_eq!(Color::from_hsv(h, s, v, 0.5), c);
+                let (h, s, l) = c.to_hsl();
+                assert_approx_eq!(Color::from_hsl(h, s, l, 0.5), c);
+            }
+        }
+    }
+
+    #[test]
+    fn premultiplied() {
+        let c = Color::new(0.8, 0.4, 0.2, 0.5);
+        assert_eq!(c.premultiply(), Color::new(0.4, 0.2, 0.1, 0.5));
+        assert_approx_eq!(c.premultiply().unpremultiply(), c);
+        assert_eq!(Color::WHITE.premultiply(), Color::WHITE);
+        assert_eq!(
+            Color::new(1.0, 1.0, 1.0, 0.0).unpremultiply(),
+            Color::TRANSPARENT
+        );
+    }
+
+    #[test]
+    fn srgb_to_xyz() {
+        // from iec 61966-2-1
+        let expected = Mat3::from_rows(
+            Vec3::new(0.4124, 0.2126, 0.0193),
+            Vec3::new(0.3576, 0.7152, 0.1192),
+            Vec3::new(0.1805, 0.0722, 0.9505),
+        );
+        assert_approx_eq!(Gamut::Rec709.to_xyz(), expected, 1e-4);
+    }
+
+    #[test]
+    fn rec709_to_rec2020() {
+        // from itu-r bt.2087
+        let expected = Mat3::from_rows(
+            Vec3::new(0.6274, 0.0691, 0.0164),
+            Vec3::new(0.3293, 0.9195, 0.0880),
+            Vec3::new(0.0433, 0.0114, 0.8956),
+        );
+        assert_approx_eq!(Gamut::Rec709.conversion(Gamut::Rec2020), expected, 1e-4);
+        let red = Color::RED.convert(Gamut::Rec709, Gamut::Rec2020);
+        assert_approx_eq!(red, Color::rgb(0.6274, 0.0691, 0.0164), 1e-4);
+        // and srgb to display p3
+        let green = Color::GREEN.convert(Gamut::Rec709, Gamut::DciP3);
+        assert_approx_eq!(green, Color::rgb(0.1775, 0.9669, 0.0724), 1e-4);
+    }
+
+    #[test]
+    fn conversions_keep_white_and_invert() {
+        let pairs = [
+            (Gamut::Rec709, Gamut::Rec2020),
+            (Gamut::DciP3, Gamut::Rec709),
+            (Gamut::Rec2020, Gamut::DciP3),
+        ];
+        let c = Color::new(0.2, 0.5, 0.9, 0.3);
+        for &(a, b) in pairs.iter() {
+            assert_approx_eq!(Color::WHITE.convert(a, b), Color::WHITE, 1e-5);
+            assert_approx_eq!(c.convert(a, b).convert(b, a), c, 1e-5);
+        }
+    }
+
+    #[test]
+    fn pq_reference_values() {
+        assert_approx_eq!(pq_encode(100.0), 0.508_078, 1e-5);
+        assert_approx_eq!(pq_encode(1000.0), 0.751_827, 1e-5);
+        assert_approx_eq!(pq_encode(PQ_MAX_NITS), 1.0, 1e-6);
+        assert_approx_eq!(pq_encode(20000.0), 1.0, 1e-6);
+        assert_approx_eq!(pq_encode(0.0), 7.309e-7, 1e-9);
+        for &nits in [0.01, 1.0, 100.0, 400.0, 1000.0, 4000.0, 10000.0].iter() {
+            assert_approx_eq!(pq_decode(pq_encode(nits)), nits, 1e-4);
+        }
+        assert_eq!(pq_decode(0.0), 0.0);
+        let white = Color::WHITE.to_hdr10(80.0);
+        assert_approx_eq!(white, Color::rgb(1.0, 1.0, 1.0) * pq_encode(80.0), 1e-5);
+    }
+
+    #[test]
+    fn hlg_reference_values() {
+        assert_eq!(hlg_encode(0.0), 0.0);
+        // the two segments meet at 1/12
+        assert_approx_eq!(hlg_encode(1.0 / 12.0), 0.5, 1e-6);
+        assert_approx_eq!(hlg_encode(1.0 / 12.0 + 1e-6), 0.5, 1e-5);
+        assert_approx_eq!(hlg_decode(0.5), 1.0 / 12.0, 1e-6);
+        assert_approx_eq!(hlg_encode(1.0), 1.0, 1e-6);
+        // the 75% signal is reference white
+        assert_approx_eq!(hlg_decode(0.75), 0.264_962, 1e-5);
+        for i in 0..=100 {
+            let light = i as f32 / 100.0;
+            assert_approx_eq!(hlg_decode(hlg_encode(light)), light, 1e-5);
+        }
+    }
+
+    #[test]
+    fn vertex_colors() {
+        let c = Color::new(1.0, 0.5, 0.0, 0.25);
+        // linear, unlike the srgb bytes
+        assert_eq!(Unorm8x4::from(c), Unorm8x4([255, 128, 0, 64]));
+        assert_eq!(c.to_srgb8(), [255, 188, 0, 64]);
+        assert_eq!(
+            Vec4::from(Half4::from(c * 4.0)),
+            Vec4::new(4.0, 2.0, 0.0, 0.25)
+        );
+        assert_eq!(<[f32; 4]>::from(Color::RED), [1.0, 0.0, 0.0, 1.0]);
+        assert_approx_eq!(
+            Color::BLACK.lerp(Color::WHITE, 0.25),
+            Color::rgb(0.25, 0.25, 0.25)
+        );
+    }
+}
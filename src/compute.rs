@@ -0,0 +1,99 @@
+//! how many thread groups a dispatch needs, from the group size a compute
+//! shader declares.
+
+use crate::dxbc;
+
+/// thread group size declared by `[numthreads(x, y, z)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumThreads {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+/// number of groups of `group_size` threads needed to cover `count` items
+pub fn group_count(count: u32, group_size: u32) -> Result<u32, String> {
+    if group_size == 0 {
+        Err("invalid group size 0".to_string())
+    } else {
+        Ok(count.div_ceil(group_size))
+    }
+}
+
+impl NumThreads {
+    pub fn new(x: u32, y: u32, z: u32) -> Self {
+        NumThreads { x: x, y: y, z: z }
+    }
+
+    pub fn from_bytecode(bytes: &[u8]) -> Result<NumThreads, String> {
+        match dxbc::num_threads(bytes)? {
+            Some([x, y, z]) if x == 0 || y == 0 || z == 0 => Err(format!(
+                "invalid compute shader: numthreads({}, {}, {})",
+                x, y, z
+            )),
+            Some([x, y, z]) => Ok(NumThreads::new(x, y, z)),
+            None => Err("invalid compute shader: no numthreads declaration".to_string()),
+        }
+    }
+
+    /// group counts to dispatch so that `width * height * depth` threads are covered
+    pub fn groups_for(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+    ) -> Result<(u32, u32, u32), String> {
+        Ok((
+            group_count(width, self.x)?,
+            group_count(height, self.y)?,
+            group_count(depth, self.z)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_counts() {
+        assert_eq!(group_count(0, 64), Ok(0));
+        assert_eq!(group_count(128, 64), Ok(2));
+        assert_eq!(group_count(129, 64), Ok(3));
+        assert_eq!(group_count(1, 64), Ok(1));
+        assert_eq!(group_count(u32::MAX, 1), Ok(u32::MAX));
+        assert_eq!(group_count(u32::MAX, 2), Ok(1 << 31));
+        assert_eq!(group_count(10, 0), Err("invalid group size 0".to_string()));
+        assert_eq!(group_count(0, 0), Err("invalid group size 0".to_string()));
+    }
+
+    #[test]
+    fn groups_for_each_axis() {
+        let threads = NumThreads::new(8, 4, 2);
+        assert_eq!(threads.groups_for(0, 0, 0), Ok((0, 0, 0)));
+        assert_eq!(threads.groups_for(64, 16, 2), Ok((8, 4, 1)));
+        assert_eq!(threads.groups_for(65, 17, 3), Ok((9, 5, 2)));
+        assert!(NumThreads::new(8, 0, 1).groups_for(1, 1, 1).is_err());
+    }
+
+    #[test]
+    fn from_bytecode() {
+        let blob = include_bytes!("../tests/fixtures/numthreads.dxbc");
+        assert_eq!(
+            NumThreads::from_bytecode(blob),
+            Ok(NumThreads::new(8, 4, 2))
+        );
+        assert!(NumThreads::from_bytecode(b"DXBC").is_err());
+        // the same shader declaring numthreads(8, 0, 2)
+        let mut zero = blob.to_vec();
+        let at = zero
+            .windows(12)
+            .position(|w| w == [8, 0, 0, 0, 4, 0, 0, 0, 2, 0, 0, 0])
+            .unwrap();
+        zero[at + 4] = 0;
+        assert_eq!(
+            NumThreads::from_bytecode(&zero).unwrap_err(),
+            "invalid compute shader: numthreads(8, 0, 2)"
+        );
+    }
+}
@@ -1,35 +1,46 @@
+pub mod adapter;
+pub mod caps;
 pub mod command;
+pub mod compute;
+pub mod display;
+pub mod engine;
+pub mod list_pool;
+pub mod pipeline;
+pub mod shader;
+pub mod surface;
+
+pub use crate::present;
 
 use std::mem::{size_of_val, zeroed};
 use std::ptr::{null, null_mut};
-use widestring::U16CString;
 use winapi::{
     ctypes::c_void,
-    shared::{
-        dxgi::DXGI_SWAP_CHAIN_DESC,
-        dxgi1_2::IDXGISwapChain1,
-        dxgi1_5::IDXGISwapChain4,
-        dxgi1_6::IDXGIFactory6,
-        minwindef::{FALSE, TRUE, UINT},
-    },
+    shared::dxgi1_6::IDXGIFactory6,
     um::{d3d12::*, unknwnbase::IUnknown},
     Interface,
 };
 
-use crate::{math, util::*, window::Window};
+use crate::{
+    app::Renderer,
+    color::Color,
+    math,
+    surface::SurfaceSet,
+    sync::QueueKind,
+    util::*,
+    window::{id::WindowId, mode::WindowMode, Window},
+};
 
 pub struct Direct3D {
+    adapter: adapter::Adapter,
+    capabilities: caps::Capabilities,
     device: *mut ID3D12Device,
-    swapchain: *mut IDXGISwapChain4,
-    rtv_heaps: *mut ID3D12DescriptorHeap,
-    back_buffers: Vec<*mut ID3D12Resource>,
-    command_manager: command::CommandManager,
+    factory: *mut IDXGIFactory6,
+    engine: engine::Engine,
+    surfaces: SurfaceSet<surface::Surface>,
 
-    pipeline_state: *mut ID3D12PipelineState,
+    pipeline: pipeline::Pipeline,
     root_signature: *mut ID3D12RootSignature,
 
-    viewport: D3D12_VIEWPORT,
-    scissorrect: D3D12_RECT,
     frame: usize,
     vb_view: D3D12_VERTEX_BUFFER_VIEW,
     ib_view: D3D12_INDEX_BUFFER_VIEW,
@@ -63,7 +74,7 @@ fn create_factory() -> Result<*mut IDXGIFactory6, String> {
     }
 }
 
-fn create_device() -> Result<*mut ID3D12Device, String> {
+fn create_device(adapter: &adapter::Adapter) -> Result<*mut ID3D12Device, String> {
     let mut device: *mut ID3D12Device = null_mut();
     use winapi::um::d3dcommon::*;
     let feature_levels = [
@@ -75,137 +86,63 @@ fn create_device() -> Result<*mut ID3D12Device, String> {
     for level in feature_levels.iter() {
         let result = unsafe {
             D3D12CreateDevice(
-                null_mut(),
+                adapter.adapter as *mut IUnknown,
                 *level,
                 &ID3D12Device::uuidof(),
                 &mut device as *mut *mut _ as *mut *mut c_void,
             )
         };
         if is_succeeded(result) {
+            eprintln!(
+                "created device on {} with feature level {}",
+                adapter.info,
+                caps::FeatureLevel(*level)
+            );
             return Ok(device);
         }
     }
-    Err("failed: create D3D12CreateDevice".to_string())
-}
-
-fn create_swapchain(
-    factory: *mut IDXGIFactory6,
-    command_manager: &command::CommandManager,
-    window: &Window,
-) -> Result<*mut IDXGISwapChain4, String> {
-    use winapi::shared::{
-        dxgi::{DXGI_SWAP_CHAIN_FLAG_ALLOW_MODE_SWITCH, DXGI_SWAP_EFFECT_FLIP_DISCARD},
-        dxgi1_2::{DXGI_ALPHA_MODE_UNSPECIFIED, DXGI_SCALING_STRETCH, DXGI_SWAP_CHAIN_DESC1},
-        dxgiformat::DXGI_FORMAT_R8G8B8A8_UNORM,
-        dxgitype::{DXGI_SAMPLE_DESC, DXGI_USAGE_BACK_BUFFER},
-    };
-    let desc = DXGI_SWAP_CHAIN_DESC1 {
-        Width: window.width as UINT,
-        Height: window.height as UINT,
-        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
-        Stereo: FALSE,
-        SampleDesc: DXGI_SAMPLE_DESC {
-            Count: 1 as UINT,
-            Quality: 0 as UINT,
-        },
-        BufferUsage: DXGI_USAGE_BACK_BUFFER,
-        BufferCount: 2 as UINT,
-        Scaling: DXGI_SCALING_STRETCH,
-        SwapEffect: DXGI_SWAP_EFFECT_FLIP_DISCARD,
-        AlphaMode: DXGI_ALPHA_MODE_UNSPECIFIED,
-        Flags: DXGI_SWAP_CHAIN_FLAG_ALLOW_MODE_SWITCH,
-    };
-
-    let mut swapchain: *mut IDXGISwapChain4 = null_mut();
-    let result = unsafe {
-        (*factory).CreateSwapChainForHwnd(
-            command_manager.get_queue() as *mut _ as *mut IUnknown,
-            window.handle,
-            &desc,
-            null(),
-            null_mut(),
-            &mut swapchain as *mut *mut _ as *mut *mut IDXGISwapChain1,
-        )
-    };
-    if is_succeeded(result) {
-        Ok(swapchain)
-    } else {
-        Err("failed: create IDXGIFactory6".to_string())
-    }
+    Err(format!(
+        "failed: create D3D12CreateDevice on {}",
+        adapter.info
+    ))
 }
 
-fn create_rtv_heaps(dev: *mut ID3D12Device) -> Result<*mut ID3D12DescriptorHeap, String> {
-    let desc = D3D12_DESCRIPTOR_HEAP_DESC {
-        Type: D3D12_DESCRIPTOR_HEAP_TYPE_RTV, // render target view
-        NumDescriptors: 2,
-        Flags: D3D12_DESCRIPTOR_HEAP_FLAG_NONE,
-        NodeMask: 0,
-    };
-    let mut rtv_heaps: *mut ID3D12DescriptorHeap = null_mut();
-    let result = unsafe {
-        (*dev).CreateDescriptorHeap(
-            &desc,
-            &ID3D12DescriptorHeap::uuidof(),
-            &mut rtv_heaps as *mut *mut _ as *mut *mut c_void,
-        )
-    };
-    if is_succeeded(result) {
-        Ok(rtv_heaps)
-    } else {
-        Err("failed: create ID3D12DescriptorHeap".to_string())
-    }
-}
-
-fn create_back_buffers(
-    device: *mut ID3D12Device,
-    swapchain: *mut IDXGISwapChain4,
-    rtv_heaps: *mut ID3D12DescriptorHeap,
-) -> Result<Vec<*mut ID3D12Resource>, String> {
-    let mut swapchain_desc: DXGI_SWAP_CHAIN_DESC = unsafe { zeroed() };
-    let result = unsafe { (*swapchain).GetDesc(&mut swapchain_desc) };
-    if is_failed(result) {
-        return Err("failed: get swapchain descriptor".to_string());
-    }
-    let mut back_buffers: Vec<*mut ID3D12Resource> = vec![];
-    back_buffers.resize(swapchain_desc.BufferCount as usize, null_mut());
-    let mut handle: D3D12_CPU_DESCRIPTOR_HANDLE =
-        unsafe { (*rtv_heaps).GetCPUDescriptorHandleForHeapStart() };
-    for i in { 0..swapchain_desc.BufferCount } {
-        let result = unsafe {
-            (*swapchain).GetBuffer(
-                i,
-                &ID3D12Resource::uuidof(),
-                &mut back_buffers[i as usize] as *mut *mut _ as *mut *mut c_void,
-            )
-        };
-        if is_failed(result) {
-            return Err("failed: get buffer".to_string());
-        }
-        unsafe { (*device).CreateRenderTargetView(back_buffers[i as usize], null(), handle) }
-        handle.ptr +=
-            unsafe { (*device).GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_RTV) }
-                as usize;
-    }
-    Ok(back_buffers)
+/// describes the outputs of the adapter that `policy` chooses
+pub fn list_displays(
+    policy: &adapter::AdapterPolicy,
+) -> Result<(adapter::AdapterInfo, Vec<display::OutputInfo>), String> {
+    let factory = create_factory()?;
+    let adapter = adapter::choose(factory, policy);
+    let result = adapter.and_then(|adapter| {
+        let outputs = display::outputs(adapter.adapter);
+        unsafe { (*adapter.adapter).Release() };
+        outputs.map(|outputs| (adapter.info, outputs))
+    });
+    unsafe { (*factory).Release() };
+    result
 }
 
 impl Direct3D {
-    pub fn create(window: &Window) -> Result<Direct3D, String> {
+    pub fn create(
+        window: &Window,
+        policy: &adapter::AdapterPolicy,
+        present: present::PresentConfig,
+    ) -> Result<Direct3D, String> {
         let factory = create_factory()?;
-        let device = create_device()?;
-        let command_manager = command::CommandManager::create(device)?;
-        let swapchain = create_swapchain(factory, &command_manager, window)?;
-        let rtv_heaps = create_rtv_heaps(device)?;
-        let back_buffers = create_back_buffers(device, swapchain, rtv_heaps)?;
+        let adapter = adapter::choose(factory, policy)?;
+        let device = create_device(&adapter)?;
+        let capabilities = caps::query(device, factory)?;
+        eprintln!("{}", capabilities);
+        let engine = engine::Engine::create(device)?;
+        present.validate(capabilities.tearing)?;
+        let mut surfaces = SurfaceSet::new();
+        let surface = surface::Surface::create(device, factory, &engine, window, present)?;
+        surfaces.insert(window.id(), surface);
 
         use winapi::shared::{
-            dxgiformat::{
-                DXGI_FORMAT_R16_UINT, DXGI_FORMAT_R32G32B32_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM,
-                DXGI_FORMAT_UNKNOWN,
-            },
+            dxgiformat::{DXGI_FORMAT_R16_UINT, DXGI_FORMAT_R32G32B32_FLOAT, DXGI_FORMAT_UNKNOWN},
             dxgitype::DXGI_SAMPLE_DESC,
         };
-        use winapi::um::d3dcommon::ID3DBlob;
         let vertices = [
             math::Vec3::new(-1.0f32, -1.0f32, 0.0f32),
             math::Vec3::new(-1.0f32, 1.0f32, 0.0f32),
@@ -310,52 +247,10 @@ impl Direct3D {
             SizeInBytes: size_of_val(&indices) as u32,
         };
 
-        use winapi::um::d3dcompiler::{
-            D3DCompileFromFile, D3DCOMPILE_DEBUG, D3DCOMPILE_SKIP_OPTIMIZATION,
-            D3D_COMPILE_STANDARD_FILE_INCLUDE,
-        };
-        let mut vs_blob: *mut ID3DBlob = null_mut();
-        let mut ps_blob: *mut ID3DBlob = null_mut();
-        let mut error_blob: *mut ID3DBlob = null_mut();
-
-        let result = unsafe {
-            D3DCompileFromFile(
-                U16CString::from_str("resource/VertexShader.hlsl")
-                    .unwrap()
-                    .as_ptr(),
-                null(),
-                D3D_COMPILE_STANDARD_FILE_INCLUDE,
-                "main\0".as_ptr() as *const _,
-                "vs_5_0\0".as_ptr() as *const _,
-                D3DCOMPILE_DEBUG | D3DCOMPILE_SKIP_OPTIMIZATION,
-                0,
-                &mut vs_blob,
-                &mut error_blob,
-            )
-        };
-        if is_failed(result) {
-            // TODO: dump more detail
-            return Err("failed: compile vertex shader file".to_string());
-        }
-        let result = unsafe {
-            D3DCompileFromFile(
-                U16CString::from_str("resource/PixelShader.hlsl")
-                    .unwrap()
-                    .as_ptr(),
-                null(),
-                D3D_COMPILE_STANDARD_FILE_INCLUDE,
-                "main\0".as_ptr() as *const _,
-                "ps_5_0\0".as_ptr() as *const _,
-                D3DCOMPILE_DEBUG | D3DCOMPILE_SKIP_OPTIMIZATION,
-                0,
-                &mut ps_blob,
-                &mut error_blob,
-            )
-        };
-        if is_failed(result) {
-            // TODO: dump more detail
-            return Err("failed: compile pixel shader file".to_string());
-        }
+        let vertex_shader =
+            shader::Shader::compile_from_file("resource/VertexShader.hlsl", "main", "vs_5_0")?;
+        let pixel_shader =
+            shader::Shader::compile_from_file("resource/PixelShader.hlsl", "main", "ps_5_0")?;
 
         let semantic_name = "POSITION\0";
         let input_layout: [D3D12_INPUT_ELEMENT_DESC; 1] = [D3D12_INPUT_ELEMENT_DESC {
@@ -368,195 +263,214 @@ impl Direct3D {
             InstanceDataStepRate: 0,
         }];
 
-        let mut graphics_pipeline: D3D12_GRAPHICS_PIPELINE_STATE_DESC = unsafe { zeroed() };
-        graphics_pipeline.pRootSignature = null_mut();
-        unsafe {
-            graphics_pipeline.VS.pShaderBytecode = (*vs_blob).GetBufferPointer();
-            graphics_pipeline.VS.BytecodeLength = (*vs_blob).GetBufferSize();
-            graphics_pipeline.PS.pShaderBytecode = (*ps_blob).GetBufferPointer();
-            graphics_pipeline.PS.BytecodeLength = (*vs_blob).GetBufferSize();
-        }
-        graphics_pipeline.SampleMask = D3D12_DEFAULT_SAMPLE_MASK;
-        graphics_pipeline.BlendState.AlphaToCoverageEnable = FALSE;
-        graphics_pipeline.BlendState.IndependentBlendEnable = FALSE;
-
-        let mut render_target_blend_desc: D3D12_RENDER_TARGET_BLEND_DESC = unsafe { zeroed() };
-        render_target_blend_desc.BlendEnable = FALSE;
-        render_target_blend_desc.RenderTargetWriteMask = D3D12_COLOR_WRITE_ENABLE_ALL as u8;
+        let mut root_signature_desc: D3D12_ROOT_SIGNATURE_DESC = unsafe { zeroed() };
+        root_signature_desc.Flags = D3D12_ROOT_SIGNATURE_FLAG_ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT;
+        let root_signature = pipeline::create_root_signature(device, &root_signature_desc)?;
 
-        render_target_blend_desc.LogicOpEnable = FALSE;
+        let pipeline_desc =
+            pipeline::GraphicsPipelineDesc::new(&vertex_shader, &pixel_shader, &input_layout);
+        for feature in pipeline_desc.features() {
+            capabilities.check(feature)?;
+        }
+        let pipeline = pipeline::Pipeline::create(device, root_signature, &pipeline_desc)?;
 
-        graphics_pipeline.BlendState.RenderTarget[0] = render_target_blend_desc;
+        Ok(Direct3D {
+            adapter: adapter,
+            capabilities: capabilities,
+            device: device,
+            factory: factory,
+            engine: engine,
+            surfaces: surfaces,
 
-        graphics_pipeline.RasterizerState.MultisampleEnable = FALSE;
-        graphics_pipeline.RasterizerState.CullMode = D3D12_CULL_MODE_NONE;
-        graphics_pipeline.RasterizerState.FillMode = D3D12_FILL_MODE_SOLID;
-        graphics_pipeline.RasterizerState.DepthClipEnable = TRUE;
+            pipeline: pipeline,
+            root_signature: root_signature,
+            frame: 0,
+            vb_view: vb_view,
+            ib_view: ib_view,
+        })
+    }
 
-        graphics_pipeline.RasterizerState.FrontCounterClockwise = FALSE;
-        graphics_pipeline.RasterizerState.DepthBias = D3D12_DEFAULT_DEPTH_BIAS as i32;
-        graphics_pipeline.RasterizerState.DepthBiasClamp = D3D12_DEFAULT_DEPTH_BIAS_CLAMP;
-        graphics_pipeline.RasterizerState.SlopeScaledDepthBias =
-            D3D12_DEFAULT_SLOPE_SCALED_DEPTH_BIAS;
-        graphics_pipeline.RasterizerState.AntialiasedLineEnable = FALSE;
-        graphics_pipeline.RasterizerState.ForcedSampleCount = 0;
-        graphics_pipeline.RasterizerState.ConservativeRaster =
-            D3D12_CONSERVATIVE_RASTERIZATION_MODE_OFF;
+    pub fn adapter(&self) -> &adapter::AdapterInfo {
+        &self.adapter.info
+    }
 
-        graphics_pipeline.DepthStencilState.DepthEnable = FALSE;
-        graphics_pipeline.DepthStencilState.StencilEnable = FALSE;
+    pub fn capabilities(&self) -> &caps::Capabilities {
+        &self.capabilities
+    }
 
-        graphics_pipeline.InputLayout.pInputElementDescs = input_layout.as_ptr();
-        graphics_pipeline.InputLayout.NumElements = input_layout.len() as u32;
+    /// fails unless the device supports `feature`
+    pub fn require(&self, feature: caps::Feature) -> Result<(), String> {
+        self.capabilities.check(feature)
+    }
 
-        graphics_pipeline.IBStripCutValue = D3D12_INDEX_BUFFER_STRIP_CUT_VALUE_DISABLED;
-        graphics_pipeline.PrimitiveTopologyType = D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE;
+    /// builds a graphics pipeline, once the device supports its stages
+    pub fn create_pipeline(
+        &self,
+        root_signature: *mut ID3D12RootSignature,
+        desc: &pipeline::GraphicsPipelineDesc,
+    ) -> Result<pipeline::Pipeline, String> {
+        for feature in desc.features() {
+            self.require(feature)?;
+        }
+        pipeline::Pipeline::create(self.device, root_signature, desc)
+    }
 
-        graphics_pipeline.NumRenderTargets = 1;
-        graphics_pipeline.RTVFormats[0] = DXGI_FORMAT_R8G8B8A8_UNORM;
+    /// the queue of `kind`, once the device supports it
+    pub fn queue(&mut self, kind: QueueKind) -> Result<&mut command::CommandManager, String> {
+        if let Some(feature) = caps::Feature::for_queue(kind) {
+            self.require(feature)?;
+        }
+        Ok(self.engine.get_mut(kind))
+    }
 
-        graphics_pipeline.SampleDesc.Count = 1;
-        graphics_pipeline.SampleDesc.Quality = 0;
+    /// draws into `window` too, sharing the device and queue with the
+    /// other surfaces
+    pub fn create_surface(
+        &mut self,
+        window: &Window,
+        config: present::PresentConfig,
+    ) -> Result<(), String> {
+        config.validate(self.capabilities.tearing)?;
+        let surface =
+            surface::Surface::create(self.device, self.factory, &self.engine, window, config)?;
+        if self.surfaces.insert(window.id(), surface).is_some() {
+            // the replaced surface may still be in use
+            self.engine.flush()?;
+        }
+        Ok(())
+    }
 
-        let mut root_signature: *mut ID3D12RootSignature = null_mut();
+    /// stops drawing into `window`, waiting for the gpu first
+    pub fn remove_surface(&mut self, window: WindowId) -> Result<(), String> {
+        if self.surfaces.contains(window) {
+            self.engine.flush()?;
+            self.surfaces.remove(window);
+        }
+        Ok(())
+    }
 
-        let mut root_signature_desc: D3D12_ROOT_SIGNATURE_DESC = unsafe { zeroed() };
-        root_signature_desc.Flags = D3D12_ROOT_SIGNATURE_FLAG_ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT;
+    pub fn surface(&self, window: WindowId) -> Option<&surface::Surface> {
+        self.surfaces.get(window)
+    }
 
-        let mut root_sig_blob: *mut ID3DBlob = null_mut();
-        let result = unsafe {
-            D3D12SerializeRootSignature(
-                &root_signature_desc,
-                D3D_ROOT_SIGNATURE_VERSION_1_0,
-                &mut root_sig_blob,
-                &mut error_blob,
-            )
-        };
-        if is_failed(result) {
-            return Err("failed: D3D12SerializeRootSignature".to_string());
-        }
-        let result = unsafe {
-            (*device).CreateRootSignature(
-                0,
-                (*root_sig_blob).GetBufferPointer(),
-                (*root_sig_blob).GetBufferSize(),
-                &ID3D12RootSignature::uuidof(),
-                &mut root_signature as *mut *mut _ as *mut *mut c_void,
-            )
-        };
-        if is_failed(result) {
-            return Err("failed: CreateRootSignature".to_string());
-        }
-        unsafe { (*root_sig_blob).Release() };
+    /// resizes the back buffers of `window`, waiting for the gpu first
+    pub fn resize(&mut self, window: WindowId, width: u32, height: u32) -> Result<(), String> {
+        self.engine.flush()?;
+        self.surfaces
+            .expect_mut(window)?
+            .resize(self.device, width, height)
+    }
 
-        graphics_pipeline.pRootSignature = root_signature;
+    pub fn present_config(&self, window: WindowId) -> Option<&present::PresentConfig> {
+        self.surfaces
+            .get(window)
+            .map(|surface| surface.present_config())
+    }
 
-        let mut pipeline_state: *mut ID3D12PipelineState = null_mut();
-        let result = unsafe {
-            (*device).CreateGraphicsPipelineState(
-                &graphics_pipeline,
-                &ID3D12PipelineState::uuidof(),
-                &mut pipeline_state as *mut *mut _ as *mut *mut c_void,
-            )
-        };
-        if is_failed(result) {
-            return Err("failed: CreateGraphicsPipelineState".to_string());
-        }
+    /// switches the surface of `window` to `config`
+    pub fn set_present_config(
+        &mut self,
+        window: &Window,
+        config: present::PresentConfig,
+    ) -> Result<(), String> {
+        config.validate(self.capabilities.tearing)?;
+        self.surfaces.expect_mut(window.id())?.set_present_config(
+            self.device,
+            self.factory,
+            self.adapter.adapter,
+            &self.engine,
+            window,
+            config,
+        )
+    }
 
-        let viewport = D3D12_VIEWPORT {
-            Width: window.width as f32,
-            Height: window.height as f32,
-            TopLeftX: 0.0f32,
-            TopLeftY: 0.0f32,
-            MaxDepth: 1.0f32,
-            MinDepth: 0.0f32,
-        };
+    pub fn window_mode(&self, window: WindowId) -> WindowMode {
+        self.surfaces
+            .get(window)
+            .map_or(WindowMode::Windowed, |surface| surface.window_mode())
+    }
 
-        let scissorrect = D3D12_RECT {
-            top: 0,
-            left: 0,
-            right: window.width as i32,
-            bottom: window.height as i32,
-        };
+    /// switches `window` between windowed, borderless and exclusive fullscreen
+    pub fn set_window_mode(&mut self, window: &mut Window, to: WindowMode) -> Result<(), String> {
+        let adapter = self.adapter.adapter;
+        self.surfaces
+            .expect_mut(window.id())?
+            .set_window_mode(adapter, window, to)
+    }
 
-        Ok(Direct3D {
-            device: device,
-            swapchain: swapchain,
-            rtv_heaps: rtv_heaps,
-            back_buffers: back_buffers,
-            command_manager: command_manager,
-
-            viewport: viewport,
-            scissorrect: scissorrect,
-            pipeline_state: pipeline_state,
-            root_signature: root_signature,
-            frame: 0,
-            vb_view: vb_view,
-            ib_view: ib_view,
-        })
+    /// what alt+enter does
+    pub fn toggle_window_mode(&mut self, window: &mut Window) -> Result<(), String> {
+        let adapter = self.adapter.adapter;
+        self.surfaces
+            .expect_mut(window.id())?
+            .toggle_window_mode(adapter, window)
     }
 
-    pub fn update(&mut self) {
-        use winapi::um::d3dcommon::D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST;
-        let backbuffer_idx = unsafe { (*self.swapchain).GetCurrentBackBufferIndex() } as usize;
-        self.command_manager.resource_barrier(
-            self.back_buffers[backbuffer_idx],
-            D3D12_RESOURCE_STATE_PRESENT,
-            D3D12_RESOURCE_STATE_RENDER_TARGET,
-        );
-        unsafe {
-            self.command_manager
-                .get_list()
-                .SetPipelineState(self.pipeline_state)
-        };
-        let mut rtv_handle = unsafe { (*self.rtv_heaps).GetCPUDescriptorHandleForHeapStart() };
-        rtv_handle.ptr += backbuffer_idx
-            * unsafe {
-                (*self.device).GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_RTV)
-            } as usize;
-        self.command_manager.set_rtv(&rtv_handle);
+    /// draws a frame into every surface that is not suspended and presents
+    /// them. surfaces left unusable by a failed resize are skipped.
+    pub fn update(&mut self) -> Result<(), String> {
+        let clear = Color::from_srgb_u32((self.frame as u32) << 8 | 0xff);
+        self.frame += 1;
 
-        let r = (0xff & self.frame >> 16) as f32 / 255.0f32;
-        let g = (0xff & self.frame >> 8) as f32 / 255.0f32;
-        let b = (0xff & self.frame >> 0) as f32 / 255.0f32;
+        let list = self.engine.direct.get_list();
+        for (_, surface) in self.surfaces.active_mut() {
+            if !surface.is_usable() {
+                continue;
+            }
+            surface.sync_window_mode();
+            surface.wait_for_frame();
+            let (back_buffer, rtv_handle) = surface.current_target(self.device)?;
+            self.engine.direct.resource_barrier(
+                back_buffer,
+                D3D12_RESOURCE_STATE_PRESENT,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+            );
+            self.engine.direct.set_pipeline(&self.pipeline);
+            self.engine.direct.set_rtv(&rtv_handle);
+            self.engine
+                .direct
+                // the back buffers are R8G8B8A8_UNORM, which take encoded values
+                .clear_render_target_view(rtv_handle, clear.to_srgb());
+            unsafe {
+                list.RSSetViewports(1, surface.viewport());
+                list.RSSetScissorRects(1, surface.scissor_rect());
+                list.SetGraphicsRootSignature(self.root_signature);
+                list.IASetVertexBuffers(0, 1, &self.vb_view);
+                list.IASetIndexBuffer(&self.ib_view);
+                list.DrawIndexedInstanced(6, 1, 0, 0, 0);
+            }
+            self.engine.direct.resource_barrier(
+                back_buffer,
+                D3D12_RESOURCE_STATE_RENDER_TARGET,
+                D3D12_RESOURCE_STATE_PRESENT,
+            );
+        }
 
-        self.command_manager
-            .clear_render_target_view(rtv_handle, r, g, b, 1.0f32);
+        self.engine.submit(QueueKind::Direct, &[])?;
 
-        self.frame += 1;
-        unsafe {
-            self.command_manager
-                .get_list()
-                .RSSetViewports(1, &self.viewport);
-            self.command_manager
-                .get_list()
-                .RSSetScissorRects(1, &self.scissorrect);
-            self.command_manager
-                .get_list()
-                .SetGraphicsRootSignature(self.root_signature);
-            self.command_manager
-                .get_list()
-                .IASetPrimitiveTopology(D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
-            self.command_manager
-                .get_list()
-                .IASetVertexBuffers(0, 1, &self.vb_view);
-            self.command_manager
-                .get_list()
-                .IASetIndexBuffer(&self.ib_view);
-
-            self.command_manager
-                .get_list()
-                .DrawIndexedInstanced(6, 1, 0, 0, 0);
+        for (_, surface) in self.surfaces.active_mut() {
+            if surface.is_usable() {
+                surface.present()?;
+            }
         }
+        Ok(())
+    }
+}
 
-        self.command_manager.resource_barrier(
-            self.back_buffers[backbuffer_idx],
-            D3D12_RESOURCE_STATE_RENDER_TARGET,
-            D3D12_RESOURCE_STATE_PRESENT,
-        );
+impl Renderer for Direct3D {
+    fn resize(&mut self, window: WindowId, width: u32, height: u32) -> Result<(), String> {
+        Direct3D::resize(self, window, width, height)
+    }
 
-        self.command_manager.run();
+    fn suspend(&mut self, window: WindowId, suspended: bool) {
+        self.surfaces.set_suspended(window, suspended);
+    }
+
+    fn close(&mut self, window: WindowId) -> Result<(), String> {
+        self.remove_surface(window)
+    }
 
-        unsafe { (*self.swapchain).Present(1, 0) };
+    fn present(&mut self) -> Result<(), String> {
+        self.update()
     }
 }
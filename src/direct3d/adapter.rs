@@ -0,0 +1,154 @@
+use std::ptr::null_mut;
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        dxgi::{
+            IDXGIAdapter1, IDXGIOutput, DXGI_ADAPTER_DESC1, DXGI_ADAPTER_FLAG_SOFTWARE,
+            DXGI_OUTPUT_DESC,
+        },
+        dxgi1_4::IDXGIFactory4,
+        dxgi1_6::{
+            IDXGIFactory6, DXGI_GPU_PREFERENCE, DXGI_GPU_PREFERENCE_HIGH_PERFORMANCE,
+            DXGI_GPU_PREFERENCE_MINIMUM_POWER,
+        },
+        windef::HMONITOR,
+    },
+    Interface,
+};
+
+use crate::util::*;
+
+pub use crate::adapter::{AdapterInfo, AdapterPolicy};
+
+impl AdapterPolicy {
+    /// order in which dxgi should list adapters for this policy
+    pub fn gpu_preference(&self) -> DXGI_GPU_PREFERENCE {
+        match self {
+            AdapterPolicy::MinimumPower => DXGI_GPU_PREFERENCE_MINIMUM_POWER,
+            _ => DXGI_GPU_PREFERENCE_HIGH_PERFORMANCE,
+        }
+    }
+}
+
+pub struct Adapter {
+    pub info: AdapterInfo,
+    pub adapter: *mut IDXGIAdapter1,
+}
+
+fn release_all(adapters: Vec<Adapter>) {
+    for adapter in adapters {
+        unsafe { (*adapter.adapter).Release() };
+    }
+}
+
+/// pairs `adapter` with its description, releasing it if there is none
+fn described(adapter: *mut IDXGIAdapter1) -> Result<Adapter, String> {
+    match describe(adapter) {
+        Ok(info) => Ok(Adapter {
+            info: info,
+            adapter: adapter,
+        }),
+        Err(e) => {
+            unsafe { (*adapter).Release() };
+            Err(e)
+        }
+    }
+}
+
+fn describe(adapter: *mut IDXGIAdapter1) -> Result<AdapterInfo, String> {
+    let mut desc: DXGI_ADAPTER_DESC1 = unsafe { std::mem::zeroed() };
+    let result = unsafe { (*adapter).GetDesc1(&mut desc) };
+    if is_failed(result) {
+        return Err("failed: IDXGIAdapter1::GetDesc1".to_string());
+    }
+    let len = desc
+        .Description
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(desc.Description.len());
+    Ok(AdapterInfo {
+        name: String::from_utf16_lossy(&desc.Description[..len]),
+        vendor_id: desc.VendorId,
+        device_id: desc.DeviceId,
+        dedicated_video_memory: desc.DedicatedVideoMemory as u64,
+        is_software: desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE != 0,
+    })
+}
+
+/// lists every adapter in the order of `preference`
+pub fn enumerate(
+    factory: *mut IDXGIFactory6,
+    preference: DXGI_GPU_PREFERENCE,
+) -> Result<Vec<Adapter>, String> {
+    let mut adapters = vec![];
+    for i in 0.. {
+        let mut adapter: *mut IDXGIAdapter1 = null_mut();
+        let result = unsafe {
+            (*factory).EnumAdapterByGpuPreference(
+                i,
+                preference,
+                &IDXGIAdapter1::uuidof(),
+                &mut adapter as *mut *mut _ as *mut *mut c_void,
+            )
+        };
+        if is_failed(result) {
+            // DXGI_ERROR_NOT_FOUND marks the end of the list
+            break;
+        }
+        match described(adapter) {
+            Ok(adapter) => adapters.push(adapter),
+            Err(e) => {
+                release_all(adapters);
+                return Err(e);
+            }
+        }
+    }
+    Ok(adapters)
+}
+
+fn warp_adapter(factory: *mut IDXGIFactory6) -> Result<Adapter, String> {
+    let mut adapter: *mut IDXGIAdapter1 = null_mut();
+    let result = unsafe {
+        (*(factory as *mut IDXGIFactory4)).EnumWarpAdapter(
+            &IDXGIAdapter1::uuidof(),
+            &mut adapter as *mut *mut _ as *mut *mut c_void,
+        )
+    };
+    if is_failed(result) {
+        return Err("failed: EnumWarpAdapter".to_string());
+    }
+    described(adapter)
+}
+
+pub fn choose(factory: *mut IDXGIFactory6, policy: &AdapterPolicy) -> Result<Adapter, String> {
+    let mut adapters = enumerate(factory, policy.gpu_preference())?;
+    let infos: Vec<AdapterInfo> = adapters
+        .iter()
+        .map(|adapter| adapter.info.clone())
+        .collect();
+    let selected = policy
+        .select(&infos)
+        .map(|index| adapters.swap_remove(index));
+    release_all(adapters);
+    match selected {
+        Err(_) if *policy == AdapterPolicy::Warp => warp_adapter(factory),
+        selected => selected,
+    }
+}
+
+/// the output of `adapter` that shows `monitor`, if the adapter drives it
+pub fn find_output(adapter: *mut IDXGIAdapter1, monitor: HMONITOR) -> Option<*mut IDXGIOutput> {
+    for i in 0.. {
+        let mut output: *mut IDXGIOutput = null_mut();
+        if is_failed(unsafe { (*adapter).EnumOutputs(i, &mut output) }) {
+            break;
+        }
+        let mut desc: DXGI_OUTPUT_DESC = unsafe { std::mem::zeroed() };
+        let result = unsafe { (*output).GetDesc(&mut desc) };
+        if is_succeeded(result) && desc.Monitor == monitor {
+            return Some(output);
+        }
+        unsafe { (*output).Release() };
+    }
+    None
+}
@@ -0,0 +1,166 @@
+use std::mem::{size_of, zeroed};
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        dxgi1_5::{IDXGIFactory5, DXGI_FEATURE_PRESENT_ALLOW_TEARING},
+        dxgi1_6::IDXGIFactory6,
+        dxgiformat::*,
+        minwindef::{BOOL, FALSE, UINT},
+    },
+    um::{d3d12::*, d3dcommon::*},
+};
+
+use crate::util::*;
+
+pub use crate::caps::{
+    Capabilities, Feature, FeatureLevel, MeshShaderTier, RaytracingTier, ShaderModel, VrsTier,
+};
+
+impl ShaderModel {
+    fn from_d3d(model: u32) -> Self {
+        ShaderModel::new(model >> 4, model & 0xf)
+    }
+}
+
+// not declared by winapi 0.3.8
+const D3D12_FEATURE_D3D12_OPTIONS5: D3D12_FEATURE = 27;
+const D3D12_FEATURE_D3D12_OPTIONS6: D3D12_FEATURE = 30;
+const D3D12_FEATURE_D3D12_OPTIONS7: D3D12_FEATURE = 32;
+
+#[repr(C)]
+struct FeatureDataOptions5 {
+    srv_only_tiled_resource_tier3: BOOL,
+    render_passes_tier: UINT,
+    raytracing_tier: UINT,
+}
+
+#[repr(C)]
+struct FeatureDataOptions6 {
+    additional_shading_rates_supported: BOOL,
+    per_primitive_shading_rate_supported_with_viewport_indexing: BOOL,
+    variable_shading_rate_tier: UINT,
+    shading_rate_image_tile_size: UINT,
+    background_processing_supported: BOOL,
+}
+
+#[repr(C)]
+struct FeatureDataOptions7 {
+    mesh_shader_tier: UINT,
+    sampler_feedback_tier: UINT,
+}
+
+/// formats beyond `caps::TYPED_UAV_LOAD_FORMATS` that are worth loading from
+/// typed uavs
+const TYPED_UAV_LOAD_CANDIDATES: [DXGI_FORMAT; 9] = [
+    DXGI_FORMAT_R32G32B32A32_FLOAT,
+    DXGI_FORMAT_R32G32B32A32_UINT,
+    DXGI_FORMAT_R16G16B16A16_FLOAT,
+    DXGI_FORMAT_R16G16B16A16_UNORM,
+    DXGI_FORMAT_R8G8B8A8_UNORM,
+    DXGI_FORMAT_R10G10B10A2_UNORM,
+    DXGI_FORMAT_R11G11B10_FLOAT,
+    DXGI_FORMAT_R16_FLOAT,
+    DXGI_FORMAT_R8_UNORM,
+];
+
+unsafe fn check_feature<T>(
+    device: *mut ID3D12Device,
+    feature: D3D12_FEATURE,
+    data: &mut T,
+) -> bool {
+    is_succeeded((*device).CheckFeatureSupport(
+        feature,
+        data as *mut T as *mut c_void,
+        size_of::<T>() as UINT,
+    ))
+}
+
+pub fn query(
+    device: *mut ID3D12Device,
+    factory: *mut IDXGIFactory6,
+) -> Result<Capabilities, String> {
+    let mut caps = Capabilities::minimum();
+    unsafe {
+        let levels = [
+            D3D_FEATURE_LEVEL_12_1,
+            D3D_FEATURE_LEVEL_12_0,
+            D3D_FEATURE_LEVEL_11_1,
+            D3D_FEATURE_LEVEL_11_0,
+        ];
+        let mut feature_levels = D3D12_FEATURE_DATA_FEATURE_LEVELS {
+            NumFeatureLevels: levels.len() as UINT,
+            pFeatureLevelsRequested: levels.as_ptr(),
+            MaxSupportedFeatureLevel: 0,
+        };
+        if !check_feature(device, D3D12_FEATURE_FEATURE_LEVELS, &mut feature_levels) {
+            return Err("failed: CheckFeatureSupport (feature levels)".to_string());
+        }
+        caps.feature_level = FeatureLevel(feature_levels.MaxSupportedFeatureLevel);
+
+        // the runtime rejects models newer than itself, so walk down from 6.7
+        for model in (0x60..=0x67).rev() {
+            let mut shader_model = D3D12_FEATURE_DATA_SHADER_MODEL {
+                HighestShaderModel: model,
+            };
+            if check_feature(device, D3D12_FEATURE_SHADER_MODEL, &mut shader_model) {
+                caps.shader_model = ShaderModel::from_d3d(shader_model.HighestShaderModel);
+                break;
+            }
+        }
+
+        let mut options: D3D12_FEATURE_DATA_D3D12_OPTIONS = zeroed();
+        if check_feature(device, D3D12_FEATURE_D3D12_OPTIONS, &mut options) {
+            caps.resource_binding_tier = options.ResourceBindingTier;
+            caps.tiled_resources_tier = options.TiledResourcesTier;
+            if options.TypedUAVLoadAdditionalFormats != FALSE {
+                for format in TYPED_UAV_LOAD_CANDIDATES.iter() {
+                    let mut support = D3D12_FEATURE_DATA_FORMAT_SUPPORT {
+                        Format: *format,
+                        Support1: 0,
+                        Support2: 0,
+                    };
+                    if check_feature(device, D3D12_FEATURE_FORMAT_SUPPORT, &mut support)
+                        && support.Support2 & D3D12_FORMAT_SUPPORT2_UAV_TYPED_LOAD != 0
+                    {
+                        caps.typed_uav_load_formats.push(*format);
+                    }
+                }
+            }
+        }
+
+        let mut options5: FeatureDataOptions5 = zeroed();
+        if check_feature(device, D3D12_FEATURE_D3D12_OPTIONS5, &mut options5) {
+            caps.raytracing_tier = match options5.raytracing_tier {
+                0 => RaytracingTier::NotSupported,
+                10 => RaytracingTier::Tier1_0,
+                _ => RaytracingTier::Tier1_1,
+            };
+        }
+
+        let mut options6: FeatureDataOptions6 = zeroed();
+        if check_feature(device, D3D12_FEATURE_D3D12_OPTIONS6, &mut options6) {
+            caps.vrs_tier = match options6.variable_shading_rate_tier {
+                0 => VrsTier::NotSupported,
+                1 => VrsTier::Tier1,
+                _ => VrsTier::Tier2,
+            };
+        }
+
+        let mut options7: FeatureDataOptions7 = zeroed();
+        if check_feature(device, D3D12_FEATURE_D3D12_OPTIONS7, &mut options7) {
+            caps.mesh_shader_tier = match options7.mesh_shader_tier {
+                0 => MeshShaderTier::NotSupported,
+                _ => MeshShaderTier::Tier1,
+            };
+        }
+
+        let mut allow_tearing: BOOL = FALSE;
+        let result = (*(factory as *mut IDXGIFactory5)).CheckFeatureSupport(
+            DXGI_FEATURE_PRESENT_ALLOW_TEARING,
+            &mut allow_tearing as *mut BOOL as *mut c_void,
+            size_of::<BOOL>() as UINT,
+        );
+        caps.tearing = is_succeeded(result) && allow_tearing != FALSE;
+    }
+    Ok(caps)
+}
@@ -4,37 +4,76 @@ use winapi::{
     shared::{basetsd::UINT64, winerror::S_OK},
     um::d3d12::{
         ID3D12CommandAllocator, ID3D12CommandQueue, ID3D12Device, ID3D12Fence,
-        ID3D12GraphicsCommandList, ID3D12Resource, D3D12_CPU_DESCRIPTOR_HANDLE,
+        ID3D12GraphicsCommandList, ID3D12Resource, D3D12_COMMAND_LIST_TYPE,
+        D3D12_COMMAND_LIST_TYPE_COMPUTE, D3D12_COMMAND_LIST_TYPE_COPY,
+        D3D12_COMMAND_LIST_TYPE_DIRECT, D3D12_CPU_DESCRIPTOR_HANDLE, D3D12_GPU_VIRTUAL_ADDRESS,
         D3D12_RESOURCE_STATES,
     },
     Interface,
 };
 
+use crate::color::Color;
+use crate::sync::pool::FencedPool;
+use crate::sync::{QueueKind, SyncPoint};
+
+use super::compute::ComputePipeline;
+use super::list_pool::{record_parallel, CommandListPool, NativeList, NativeListFactory};
+use super::pipeline::{check_draw_topology, Pipeline, Topology};
+
 pub struct CommandManager {
+    kind: QueueKind,
+    device: *mut ID3D12Device,
     allocator: *mut ID3D12CommandAllocator,
+    allocators: FencedPool<*mut ID3D12CommandAllocator>,
     list: *mut ID3D12GraphicsCommandList,
     queue: *mut ID3D12CommandQueue,
     fence: *mut ID3D12Fence,
     fence_val: UINT64,
+    lists: CommandListPool<NativeListFactory>,
+}
+
+pub fn list_type(kind: QueueKind) -> D3D12_COMMAND_LIST_TYPE {
+    match kind {
+        QueueKind::Direct => D3D12_COMMAND_LIST_TYPE_DIRECT,
+        QueueKind::Compute => D3D12_COMMAND_LIST_TYPE_COMPUTE,
+        QueueKind::Copy => D3D12_COMMAND_LIST_TYPE_COPY,
+    }
 }
 
 impl CommandManager {
-    pub fn create(device: *mut ID3D12Device) -> Result<CommandManager, String> {
-        let allocator = create_allocator(device)?;
-        let list = create_list(device, allocator)?;
-        let queue = create_queue(device)?;
+    pub fn create(device: *mut ID3D12Device, kind: QueueKind) -> Result<CommandManager, String> {
+        let allocator = create_allocator(device, list_type(kind))?;
+        let list = create_list(device, allocator, list_type(kind))?;
+        let queue = create_queue(device, list_type(kind))?;
         let fence_val: UINT64 = 0;
         let fence = create_fence(device, fence_val)?;
 
         Ok(CommandManager {
+            kind: kind,
+            device: device,
             allocator: allocator,
+            allocators: FencedPool::new(),
             list: list,
             queue: queue,
             fence: fence,
             fence_val: fence_val,
+            lists: CommandListPool::new(NativeListFactory::new(device, list_type(kind))),
         })
     }
 
+    pub fn kind(&self) -> QueueKind {
+        self.kind
+    }
+    pub fn get_fence(&self) -> *mut ID3D12Fence {
+        self.fence
+    }
+    pub fn completed_value(&self) -> UINT64 {
+        unsafe { (*self.fence).GetCompletedValue() }
+    }
+    /// sync point of the most recently submitted work
+    pub fn last_sync_point(&self) -> SyncPoint {
+        SyncPoint::new(self.kind, self.fence_val)
+    }
     pub fn get_queue(&self) -> *mut ID3D12CommandQueue {
         self.queue
     }
@@ -70,41 +109,159 @@ impl CommandManager {
         }
     }
 
+    pub fn set_pipeline(&self, pipeline: &Pipeline) {
+        unsafe {
+            (*self.list).SetPipelineState(pipeline.state);
+            (*self.list).IASetPrimitiveTopology(pipeline.topology.to_d3d());
+        }
+    }
+
+    pub fn set_topology(&self, pipeline: &Pipeline, topology: Topology) -> Result<(), String> {
+        check_draw_topology(pipeline.topology_type(), topology)?;
+        unsafe { (*self.list).IASetPrimitiveTopology(topology.to_d3d()) };
+        Ok(())
+    }
+
+    pub fn uav_barrier(&self, resource: *mut ID3D12Resource) {
+        use winapi::um::d3d12::{
+            D3D12_RESOURCE_BARRIER, D3D12_RESOURCE_BARRIER_FLAG_NONE,
+            D3D12_RESOURCE_BARRIER_TYPE_UAV, D3D12_RESOURCE_UAV_BARRIER,
+        };
+        unsafe {
+            let mut barrier_desc = D3D12_RESOURCE_BARRIER {
+                Type: D3D12_RESOURCE_BARRIER_TYPE_UAV,
+                Flags: D3D12_RESOURCE_BARRIER_FLAG_NONE,
+                ..std::mem::zeroed()
+            };
+            *barrier_desc.u.UAV_mut() = D3D12_RESOURCE_UAV_BARRIER {
+                pResource: resource,
+            };
+            (*self.list).ResourceBarrier(1, &barrier_desc)
+        }
+    }
+
+    pub fn set_compute_pipeline(&self, pipeline: &ComputePipeline) {
+        unsafe {
+            (*self.list).SetComputeRootSignature(pipeline.root_signature);
+            (*self.list).SetPipelineState(pipeline.state);
+        }
+    }
+
+    pub fn set_compute_root_uav(&self, index: u32, resource: *mut ID3D12Resource) {
+        let address: D3D12_GPU_VIRTUAL_ADDRESS = unsafe { (*resource).GetGPUVirtualAddress() };
+        unsafe { (*self.list).SetComputeRootUnorderedAccessView(index, address) };
+    }
+
+    pub fn dispatch(&self, groups: (u32, u32, u32)) {
+        unsafe { (*self.list).Dispatch(groups.0, groups.1, groups.2) };
+    }
+
     pub fn set_rtv(&self, rtv_handle: *const D3D12_CPU_DESCRIPTOR_HANDLE) {
         unsafe { (*self.list).OMSetRenderTargets(1, rtv_handle, 0, null()) };
     }
 
-    pub fn clear_render_target_view(
-        &self,
-        rtv_handle: D3D12_CPU_DESCRIPTOR_HANDLE,
-        r: f32,
-        g: f32,
-        b: f32,
-        a: f32,
-    ) {
-        let color = [r, g, b, a];
+    /// `color` is written as it is; a target that is not `_SRGB` wants
+    /// `color.to_srgb()`
+    pub fn clear_render_target_view(&self, rtv_handle: D3D12_CPU_DESCRIPTOR_HANDLE, color: Color) {
+        let color: [f32; 4] = color.into();
         unsafe { (*self.list).ClearRenderTargetView(rtv_handle, &color as *const _, 0, null()) }
     }
 
-    pub fn run(&mut self) {
+    /// makes the gpu wait on this queue until `fence` reaches `value`
+    pub fn gpu_wait(&self, fence: *mut ID3D12Fence, value: UINT64) {
+        unsafe { (*self.queue).Wait(fence, value) };
+    }
+
+    /// blocks until the gpu has finished everything up to `value`
+    pub fn cpu_wait(&self, value: UINT64) -> Result<(), String> {
+        use winapi::um::{
+            handleapi::CloseHandle,
+            synchapi::{CreateEventW, WaitForSingleObject},
+            winbase::INFINITE,
+        };
+        if self.completed_value() >= value {
+            return Ok(());
+        }
+        unsafe {
+            let event = CreateEventW(null_mut(), 0, 0, null());
+            if event.is_null() {
+                return Err("failed: CreateEventW".to_string());
+            }
+            (*self.fence).SetEventOnCompletion(value, event);
+            WaitForSingleObject(event, INFINITE);
+            CloseHandle(event);
+        }
+        Ok(())
+    }
+
+    /// blocks until all submitted work has finished
+    pub fn flush(&self) -> Result<(), String> {
+        self.cpu_wait(self.fence_val)
+    }
+
+    fn signal(&mut self) -> SyncPoint {
+        self.fence_val += 1;
+        unsafe { (*self.queue).Signal(self.fence, self.fence_val) };
+        self.last_sync_point()
+    }
+
+    /// records each job on its own thread into a pooled command list.
+    /// the lists come back in job order, ready for `execute_recorded`.
+    pub fn record_parallel<J>(&self, jobs: Vec<J>) -> Result<Vec<NativeList>, String>
+    where
+        J: FnOnce(&mut NativeList) + Send,
+    {
+        record_parallel(&self.lists, self.completed_value(), jobs)
+    }
+
+    /// submits pooled lists in the given order with one `ExecuteCommandLists`
+    pub fn execute_recorded(&mut self, lists: Vec<NativeList>) -> SyncPoint {
+        let command_lists: Vec<*mut ID3D12GraphicsCommandList> = lists
+            .iter()
+            .map(|list| {
+                list.close();
+                list.list.0
+            })
+            .collect();
+        unsafe {
+            (*self.queue).ExecuteCommandLists(
+                command_lists.len() as u32,
+                command_lists.as_ptr() as *const _,
+            )
+        };
+        let point = self.signal();
+        self.lists.recycle(lists, point.value);
+        point
+    }
+
+    pub fn run(&mut self) -> Result<SyncPoint, String> {
         unsafe {
             (*self.list).Close();
-            let command_lists = vec![self.list];
+            let command_lists = [self.list];
             (*self.queue).ExecuteCommandLists(1, command_lists.as_ptr() as *const _);
-            self.fence_val += 1;
-            (*self.queue).Signal(self.fence, self.fence_val);
-            (*self.allocator).Reset();
-            (*self.list).Reset(self.allocator, null_mut());
         }
+        self.signal();
+        self.allocators.release(self.allocator, self.fence_val);
+        self.allocator = match self.allocators.acquire(self.completed_value()) {
+            Some(allocator) => {
+                unsafe { (*allocator).Reset() };
+                allocator
+            }
+            None => create_allocator(self.device, list_type(self.kind))?,
+        };
+        unsafe { (*self.list).Reset(self.allocator, null_mut()) };
+        Ok(self.last_sync_point())
     }
 }
 
-fn create_allocator(device: *mut ID3D12Device) -> Result<*mut ID3D12CommandAllocator, String> {
-    use winapi::um::d3d12::D3D12_COMMAND_LIST_TYPE_DIRECT;
+pub(super) fn create_allocator(
+    device: *mut ID3D12Device,
+    list_type: D3D12_COMMAND_LIST_TYPE,
+) -> Result<*mut ID3D12CommandAllocator, String> {
     let mut allocator: *mut ID3D12CommandAllocator = null_mut();
     let result = unsafe {
         (*device).CreateCommandAllocator(
-            D3D12_COMMAND_LIST_TYPE_DIRECT,
+            list_type,
             &ID3D12CommandAllocator::uuidof(),
             &mut allocator as *mut *mut _ as *mut *mut c_void,
         )
@@ -116,16 +273,16 @@ fn create_allocator(device: *mut ID3D12Device) -> Result<*mut ID3D12CommandAlloc
     }
 }
 
-fn create_list(
+pub(super) fn create_list(
     device: *mut ID3D12Device,
     allocator: *mut ID3D12CommandAllocator,
+    list_type: D3D12_COMMAND_LIST_TYPE,
 ) -> Result<*mut ID3D12GraphicsCommandList, String> {
-    use winapi::um::d3d12::D3D12_COMMAND_LIST_TYPE_DIRECT;
     let mut list: *mut ID3D12GraphicsCommandList = null_mut();
     let result = unsafe {
         (*device).CreateCommandList(
             0,
-            D3D12_COMMAND_LIST_TYPE_DIRECT,
+            list_type,
             allocator,
             null_mut(),
             &ID3D12GraphicsCommandList::uuidof(),
@@ -139,16 +296,19 @@ fn create_list(
     }
 }
 
-fn create_queue(device: *mut ID3D12Device) -> Result<*mut ID3D12CommandQueue, String> {
+fn create_queue(
+    device: *mut ID3D12Device,
+    list_type: D3D12_COMMAND_LIST_TYPE,
+) -> Result<*mut ID3D12CommandQueue, String> {
     use winapi::um::d3d12::{
-        D3D12_COMMAND_LIST_TYPE_DIRECT, D3D12_COMMAND_QUEUE_DESC, D3D12_COMMAND_QUEUE_FLAG_NONE,
+        D3D12_COMMAND_QUEUE_DESC, D3D12_COMMAND_QUEUE_FLAG_NONE,
         D3D12_COMMAND_QUEUE_PRIORITY_NORMAL,
     };
     let queue_desc = D3D12_COMMAND_QUEUE_DESC {
         Flags: D3D12_COMMAND_QUEUE_FLAG_NONE,
         NodeMask: 0,
         Priority: D3D12_COMMAND_QUEUE_PRIORITY_NORMAL as i32,
-        Type: D3D12_COMMAND_LIST_TYPE_DIRECT,
+        Type: list_type,
     };
     let mut queue: *mut ID3D12CommandQueue = null_mut();
     let result = unsafe {
@@ -0,0 +1,135 @@
+use std::mem::zeroed;
+use std::ptr::{null, null_mut};
+use winapi::{
+    ctypes::c_void,
+    shared::{dxgiformat::DXGI_FORMAT_UNKNOWN, dxgitype::DXGI_SAMPLE_DESC},
+    um::d3d12::*,
+    Interface,
+};
+
+use super::{pipeline, shader::Shader};
+use crate::util::*;
+
+pub use crate::compute::{group_count, NumThreads};
+
+pub struct ComputePipelineDesc<'a> {
+    pub shader: &'a Shader,
+}
+
+pub struct ComputePipeline {
+    pub state: *mut ID3D12PipelineState,
+    pub root_signature: *mut ID3D12RootSignature,
+    pub num_threads: NumThreads,
+}
+
+impl ComputePipeline {
+    pub fn create(
+        device: *mut ID3D12Device,
+        root_signature: *mut ID3D12RootSignature,
+        desc: &ComputePipelineDesc,
+    ) -> Result<ComputePipeline, String> {
+        let num_threads = NumThreads::from_bytecode(desc.shader.bytes())?;
+
+        let mut compute_pipeline: D3D12_COMPUTE_PIPELINE_STATE_DESC = unsafe { zeroed() };
+        compute_pipeline.pRootSignature = root_signature;
+        compute_pipeline.CS = desc.shader.bytecode();
+
+        let mut pipeline_state: *mut ID3D12PipelineState = null_mut();
+        let result = unsafe {
+            (*device).CreateComputePipelineState(
+                &compute_pipeline,
+                &ID3D12PipelineState::uuidof(),
+                &mut pipeline_state as *mut *mut _ as *mut *mut c_void,
+            )
+        };
+        if is_failed(result) {
+            return Err("failed: CreateComputePipelineState".to_string());
+        }
+        Ok(ComputePipeline {
+            state: pipeline_state,
+            root_signature: root_signature,
+            num_threads: num_threads,
+        })
+    }
+
+    pub fn groups_for(
+        &self,
+        width: u32,
+        height: u32,
+        depth: u32,
+    ) -> Result<(u32, u32, u32), String> {
+        self.num_threads.groups_for(width, height, depth)
+    }
+}
+
+/// root signature with one root UAV per register `u0..u{count - 1}`
+pub fn create_uav_root_signature(
+    device: *mut ID3D12Device,
+    count: u32,
+) -> Result<*mut ID3D12RootSignature, String> {
+    let parameters: Vec<D3D12_ROOT_PARAMETER> = (0..count)
+        .map(|register| unsafe {
+            let mut parameter = D3D12_ROOT_PARAMETER {
+                ParameterType: D3D12_ROOT_PARAMETER_TYPE_UAV,
+                ShaderVisibility: D3D12_SHADER_VISIBILITY_ALL,
+                ..zeroed()
+            };
+            *parameter.u.Descriptor_mut() = D3D12_ROOT_DESCRIPTOR {
+                ShaderRegister: register,
+                RegisterSpace: 0,
+            };
+            parameter
+        })
+        .collect();
+
+    let mut root_signature_desc: D3D12_ROOT_SIGNATURE_DESC = unsafe { zeroed() };
+    root_signature_desc.NumParameters = parameters.len() as u32;
+    root_signature_desc.pParameters = parameters.as_ptr();
+    root_signature_desc.Flags = D3D12_ROOT_SIGNATURE_FLAG_NONE;
+    pipeline::create_root_signature(device, &root_signature_desc)
+}
+
+/// buffer in the default heap that can be bound as an unordered access view
+pub fn create_uav_buffer(
+    device: *mut ID3D12Device,
+    size: u64,
+) -> Result<*mut ID3D12Resource, String> {
+    let heapprop = D3D12_HEAP_PROPERTIES {
+        Type: D3D12_HEAP_TYPE_DEFAULT,
+        CPUPageProperty: D3D12_CPU_PAGE_PROPERTY_UNKNOWN,
+        MemoryPoolPreference: D3D12_MEMORY_POOL_UNKNOWN,
+        CreationNodeMask: 0,
+        VisibleNodeMask: 0,
+    };
+    let resource_desc = D3D12_RESOURCE_DESC {
+        Dimension: D3D12_RESOURCE_DIMENSION_BUFFER,
+        Alignment: 0,
+        Width: size,
+        Height: 1,
+        DepthOrArraySize: 1,
+        MipLevels: 1,
+        Format: DXGI_FORMAT_UNKNOWN,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Flags: D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+        Layout: D3D12_TEXTURE_LAYOUT_ROW_MAJOR,
+    };
+    let mut buffer: *mut ID3D12Resource = null_mut();
+    let result = unsafe {
+        (*device).CreateCommittedResource(
+            &heapprop,
+            D3D12_HEAP_FLAG_NONE,
+            &resource_desc,
+            D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+            null(),
+            &ID3D12Resource::uuidof(),
+            &mut buffer as *mut *mut _ as *mut *mut c_void,
+        )
+    };
+    if is_failed(result) {
+        return Err("failed: CreateCommittedResource (uav buffer)".to_string());
+    }
+    Ok(buffer)
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::ptr::null_mut;
+use winapi::{
+    shared::{
+        dxgi::{IDXGIAdapter1, IDXGIOutput, DXGI_OUTPUT_DESC},
+        dxgi1_6::{IDXGIOutput6, DXGI_OUTPUT_DESC1},
+        dxgiformat::*,
+        dxgitype::*,
+        minwindef::{FALSE, UINT},
+    },
+    Interface,
+};
+
+use crate::util::*;
+use crate::window::rect::Rect;
+
+pub use crate::display::{closest_mode, DisplayMode, ModeRequest, Rational};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Unspecified,
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl Rotation {
+    fn from_d3d(rotation: DXGI_MODE_ROTATION) -> Self {
+        match rotation {
+            DXGI_MODE_ROTATION_IDENTITY => Rotation::Identity,
+            DXGI_MODE_ROTATION_ROTATE90 => Rotation::Rotate90,
+            DXGI_MODE_ROTATION_ROTATE180 => Rotation::Rotate180,
+            DXGI_MODE_ROTATION_ROTATE270 => Rotation::Rotate270,
+            _ => Rotation::Unspecified,
+        }
+    }
+}
+
+/// a monitor attached to the adapter
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    pub name: String,
+    pub desktop: Rect,
+    pub attached: bool,
+    pub rotation: Rotation,
+    /// the desktop is in hdr10 (st.2084 / bt.2020) mode
+    pub hdr: bool,
+    pub max_luminance: f32,
+    pub modes: Vec<DisplayMode>,
+}
+
+impl fmt::Display for OutputInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {}x{} at ({}, {}){}, {:?}, hdr: {} ({} nits)",
+            self.name,
+            self.desktop.width(),
+            self.desktop.height(),
+            self.desktop.left,
+            self.desktop.top,
+            if self.attached { "" } else { ", detached" },
+            self.rotation,
+            self.hdr,
+            self.max_luminance
+        )?;
+        for mode in self.modes.iter() {
+            writeln!(f, "    {} {}", format_name(mode.format), mode)?;
+        }
+        Ok(())
+    }
+}
+
+/// formats whose display modes are listed
+pub const MODE_FORMATS: [DXGI_FORMAT; 4] = [
+    DXGI_FORMAT_R8G8B8A8_UNORM,
+    DXGI_FORMAT_B8G8R8A8_UNORM,
+    DXGI_FORMAT_R10G10B10A2_UNORM,
+    DXGI_FORMAT_R16G16B16A16_FLOAT,
+];
+
+fn format_name(format: DXGI_FORMAT) -> &'static str {
+    match format {
+        DXGI_FORMAT_R8G8B8A8_UNORM => "R8G8B8A8_UNORM",
+        DXGI_FORMAT_B8G8R8A8_UNORM => "B8G8R8A8_UNORM",
+        DXGI_FORMAT_R10G10B10A2_UNORM => "R10G10B10A2_UNORM",
+        DXGI_FORMAT_R16G16B16A16_FLOAT => "R16G16B16A16_FLOAT",
+        _ => "other format",
+    }
+}
+
+pub fn display_modes(
+    output: *mut IDXGIOutput,
+    format: DXGI_FORMAT,
+) -> Result<Vec<DisplayMode>, String> {
+    let mut count: UINT = 0;
+    let result = unsafe { (*output).GetDisplayModeList(format, 0, &mut count, null_mut()) };
+    if is_failed(result) {
+        return Err("failed: GetDisplayModeList".to_string());
+    }
+    let mut descs: Vec<DXGI_MODE_DESC> = vec![unsafe { std::mem::zeroed() }; count as usize];
+    let result = unsafe { (*output).GetDisplayModeList(format, 0, &mut count, descs.as_mut_ptr()) };
+    if is_failed(result) {
+        return Err("failed: GetDisplayModeList".to_string());
+    }
+    descs.truncate(count as usize);
+    Ok(descs
+        .iter()
+        .map(|desc| DisplayMode {
+            width: desc.Width,
+            height: desc.Height,
+            refresh_rate: Rational {
+                numerator: desc.RefreshRate.Numerator,
+                denominator: desc.RefreshRate.Denominator,
+            },
+            format: desc.Format,
+        })
+        .collect())
+}
+
+/// the hdr description of `output`, which needs windows 10 1803
+fn describe1(output: *mut IDXGIOutput) -> Option<DXGI_OUTPUT_DESC1> {
+    let mut output6: *mut IDXGIOutput6 = null_mut();
+    let result = unsafe {
+        (*output).QueryInterface(
+            &IDXGIOutput6::uuidof(),
+            &mut output6 as *mut *mut _ as *mut *mut _,
+        )
+    };
+    if is_failed(result) {
+        return None;
+    }
+    let mut desc: DXGI_OUTPUT_DESC1 = unsafe { std::mem::zeroed() };
+    let result = unsafe { (*output6).GetDesc1(&mut desc) };
+    unsafe { (*output6).Release() };
+    if is_failed(result) {
+        return None;
+    }
+    Some(desc)
+}
+
+fn describe(output: *mut IDXGIOutput) -> Result<OutputInfo, String> {
+    let mut modes = vec![];
+    for format in MODE_FORMATS.iter() {
+        modes.extend(display_modes(output, *format)?);
+    }
+    let name = |device_name: &[u16]| {
+        let len = device_name
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(device_name.len());
+        String::from_utf16_lossy(&device_name[..len])
+    };
+    if let Some(desc) = describe1(output) {
+        let rect = desc.DesktopCoordinates;
+        return Ok(OutputInfo {
+            name: name(&desc.DeviceName),
+            desktop: Rect::new(rect.left, rect.top, rect.right, rect.bottom),
+            attached: desc.AttachedToDesktop != FALSE,
+            rotation: Rotation::from_d3d(desc.Rotation),
+            hdr: desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020,
+            max_luminance: desc.MaxLuminance,
+            modes: modes,
+        });
+    }
+    // without IDXGIOutput6 there is no hdr information
+    let mut desc: DXGI_OUTPUT_DESC = unsafe { std::mem::zeroed() };
+    let result = unsafe { (*output).GetDesc(&mut desc) };
+    if is_failed(result) {
+        return Err("failed: IDXGIOutput::GetDesc".to_string());
+    }
+    let rect = desc.DesktopCoordinates;
+    Ok(OutputInfo {
+        name: name(&desc.DeviceName),
+        desktop: Rect::new(rect.left, rect.top, rect.right, rect.bottom),
+        attached: desc.AttachedToDesktop != FALSE,
+        rotation: Rotation::from_d3d(desc.Rotation),
+        hdr: false,
+        max_luminance: 0.0,
+        modes: modes,
+    })
+}
+
+/// describes every output of `adapter`
+pub fn outputs(adapter: *mut IDXGIAdapter1) -> Result<Vec<OutputInfo>, String> {
+    let mut infos = vec![];
+    for i in 0.. {
+        let mut output: *mut IDXGIOutput = null_mut();
+        if is_failed(unsafe { (*adapter).EnumOutputs(i, &mut output) }) {
+            // DXGI_ERROR_NOT_FOUND marks the end of the list
+            break;
+        }
+        let info = describe(output);
+        unsafe { (*output).Release() };
+        infos.push(info?);
+    }
+    Ok(infos)
+}
@@ -0,0 +1,124 @@
+use winapi::um::d3d12::{ID3D12Device, ID3D12Resource};
+
+use crate::sync::{OwnershipTracker, QueueKind, SyncPoint, WaitTracker, QUEUE_COUNT};
+
+use super::command::CommandManager;
+use super::list_pool::NativeList;
+
+/// the direct, compute and copy queues of one device. submissions name the
+/// sync points they depend on, and the engine inserts the needed `Wait`s.
+pub struct Engine {
+    pub direct: CommandManager,
+    pub compute: CommandManager,
+    pub copy: CommandManager,
+    waits: WaitTracker,
+    ownership: OwnershipTracker<*mut ID3D12Resource>,
+    pending: [Vec<SyncPoint>; QUEUE_COUNT],
+}
+
+impl Engine {
+    pub fn create(device: *mut ID3D12Device) -> Result<Engine, String> {
+        Ok(Engine {
+            direct: CommandManager::create(device, QueueKind::Direct)?,
+            compute: CommandManager::create(device, QueueKind::Compute)?,
+            copy: CommandManager::create(device, QueueKind::Copy)?,
+            waits: WaitTracker::new(),
+            ownership: OwnershipTracker::new(),
+            pending: [vec![], vec![], vec![]],
+        })
+    }
+
+    pub fn get(&self, kind: QueueKind) -> &CommandManager {
+        match kind {
+            QueueKind::Direct => &self.direct,
+            QueueKind::Compute => &self.compute,
+            QueueKind::Copy => &self.copy,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: QueueKind) -> &mut CommandManager {
+        match kind {
+            QueueKind::Direct => &mut self.direct,
+            QueueKind::Compute => &mut self.compute,
+            QueueKind::Copy => &mut self.copy,
+        }
+    }
+
+    fn completed_values(&self) -> [u64; QUEUE_COUNT] {
+        [
+            self.direct.completed_value(),
+            self.compute.completed_value(),
+            self.copy.completed_value(),
+        ]
+    }
+
+    fn insert_waits(&mut self, kind: QueueKind, deps: &[SyncPoint]) {
+        let mut deps = deps.to_vec();
+        deps.append(&mut self.pending[kind.index()]);
+        let completed = self.completed_values();
+        for wait in self.waits.resolve(kind, &deps, completed) {
+            let fence = self.get(wait.queue).get_fence();
+            self.get(kind).gpu_wait(fence, wait.value);
+        }
+    }
+
+    /// executes what was recorded on `kind` after the work behind `deps`
+    /// (and any acquired resources) has finished on the other queues
+    pub fn submit(&mut self, kind: QueueKind, deps: &[SyncPoint]) -> Result<SyncPoint, String> {
+        self.insert_waits(kind, deps);
+        self.get_mut(kind).run()
+    }
+
+    /// like `submit`, for lists recorded with `CommandManager::record_parallel`
+    pub fn submit_recorded(
+        &mut self,
+        kind: QueueKind,
+        deps: &[SyncPoint],
+        lists: Vec<NativeList>,
+    ) -> SyncPoint {
+        self.insert_waits(kind, deps);
+        self.get_mut(kind).execute_recorded(lists)
+    }
+
+    /// blocks until every queue is idle
+    pub fn flush(&self) -> Result<(), String> {
+        for kind in QueueKind::ALL.iter() {
+            self.get(*kind).flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn register(&mut self, resource: *mut ID3D12Resource, owner: QueueKind) {
+        self.ownership.register(resource, owner);
+    }
+
+    pub fn unregister(&mut self, resource: *mut ID3D12Resource) {
+        self.ownership.unregister(resource);
+    }
+
+    pub fn check_owner(
+        &self,
+        resource: *mut ID3D12Resource,
+        queue: QueueKind,
+    ) -> Result<(), String> {
+        self.ownership.check(resource, queue)
+    }
+
+    /// hands `resource` over to `to` after the last submission on `from`
+    pub fn release(
+        &mut self,
+        resource: *mut ID3D12Resource,
+        from: QueueKind,
+        to: QueueKind,
+    ) -> Result<(), String> {
+        let at = self.get(from).last_sync_point();
+        self.ownership.release(resource, from, to, at)
+    }
+
+    /// takes over a released resource; the next submission on `to` waits for it
+    pub fn acquire(&mut self, resource: *mut ID3D12Resource, to: QueueKind) -> Result<(), String> {
+        let at = self.ownership.acquire(resource, to)?;
+        self.pending[to.index()].push(at);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::ptr::null_mut;
+use winapi::um::d3d12::{
+    ID3D12CommandAllocator, ID3D12Device, ID3D12GraphicsCommandList, D3D12_COMMAND_LIST_TYPE,
+};
+
+pub use crate::list_pool::{
+    record_parallel, submission_order, CommandListPool, ListFactory, Recording,
+};
+
+use super::command::{create_allocator, create_list};
+
+/// raw com pointer that may be moved to another thread.
+/// the pointee must only be used by one thread at a time, which the pool
+/// guarantees by handing every list out to exactly one recorder.
+pub struct SendPtr<T>(pub *mut T);
+
+unsafe impl<T> Send for SendPtr<T> {}
+
+impl<T> Clone for SendPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SendPtr<T> {}
+
+/// an allocator and the command list recording into it
+pub struct NativeList {
+    pub allocator: SendPtr<ID3D12CommandAllocator>,
+    pub list: SendPtr<ID3D12GraphicsCommandList>,
+}
+
+impl NativeList {
+    pub fn get_list(&self) -> &ID3D12GraphicsCommandList {
+        unsafe { &*self.list.0 }
+    }
+
+    pub fn close(&self) {
+        unsafe { (*self.list.0).Close() };
+    }
+}
+
+pub struct NativeListFactory {
+    device: SendPtr<ID3D12Device>,
+    list_type: D3D12_COMMAND_LIST_TYPE,
+}
+
+impl NativeListFactory {
+    pub fn new(device: *mut ID3D12Device, list_type: D3D12_COMMAND_LIST_TYPE) -> Self {
+        NativeListFactory {
+            device: SendPtr(device),
+            list_type: list_type,
+        }
+    }
+}
+
+impl ListFactory for NativeListFactory {
+    type List = NativeList;
+
+    fn create(&mut self) -> Result<NativeList, String> {
+        let allocator = create_allocator(self.device.0, self.list_type)?;
+        let list = create_list(self.device.0, allocator, self.list_type)?;
+        Ok(NativeList {
+            allocator: SendPtr(allocator),
+            list: SendPtr(list),
+        })
+    }
+
+    fn reset(&mut self, list: &mut NativeList) -> Result<(), String> {
+        use crate::util::is_failed;
+        let result = unsafe { (*list.allocator.0).Reset() };
+        if is_failed(result) {
+            return Err("failed: reset ID3D12CommandAllocator".to_string());
+        }
+        let result = unsafe { (*list.list.0).Reset(list.allocator.0, null_mut()) };
+        if is_failed(result) {
+            return Err("failed: reset ID3D12GraphicsCommandList".to_string());
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, list: &mut NativeList) {
+        list.close();
+    }
+}
@@ -0,0 +1,238 @@
+use std::mem::zeroed;
+use std::ptr::null_mut;
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        dxgiformat::DXGI_FORMAT_R8G8B8A8_UNORM,
+        minwindef::{FALSE, TRUE},
+    },
+    um::{
+        d3d12::*,
+        d3dcommon::{
+            ID3DBlob, D3D_PRIMITIVE_TOPOLOGY, D3D_PRIMITIVE_TOPOLOGY_1_CONTROL_POINT_PATCHLIST,
+            D3D_PRIMITIVE_TOPOLOGY_LINELIST, D3D_PRIMITIVE_TOPOLOGY_LINESTRIP,
+            D3D_PRIMITIVE_TOPOLOGY_POINTLIST, D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+            D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+        },
+    },
+    Interface,
+};
+
+use super::shader::{blob_to_string, Shader};
+use crate::caps::Feature;
+use crate::util::*;
+
+pub use crate::pipeline::{
+    check_draw_topology, validate_stages, ShaderStage, Topology, TopologyType,
+    MAX_PATCH_CONTROL_POINTS,
+};
+
+impl Topology {
+    pub fn to_d3d(self) -> D3D_PRIMITIVE_TOPOLOGY {
+        match self {
+            Topology::PointList => D3D_PRIMITIVE_TOPOLOGY_POINTLIST,
+            Topology::LineList => D3D_PRIMITIVE_TOPOLOGY_LINELIST,
+            Topology::LineStrip => D3D_PRIMITIVE_TOPOLOGY_LINESTRIP,
+            Topology::TriangleList => D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+            Topology::TriangleStrip => D3D_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+            Topology::PatchList(n) => D3D_PRIMITIVE_TOPOLOGY_1_CONTROL_POINT_PATCHLIST + (n - 1),
+        }
+    }
+}
+
+impl TopologyType {
+    pub fn to_d3d(self) -> D3D12_PRIMITIVE_TOPOLOGY_TYPE {
+        match self {
+            TopologyType::Point => D3D12_PRIMITIVE_TOPOLOGY_TYPE_POINT,
+            TopologyType::Line => D3D12_PRIMITIVE_TOPOLOGY_TYPE_LINE,
+            TopologyType::Triangle => D3D12_PRIMITIVE_TOPOLOGY_TYPE_TRIANGLE,
+            TopologyType::Patch => D3D12_PRIMITIVE_TOPOLOGY_TYPE_PATCH,
+        }
+    }
+}
+
+pub struct GraphicsPipelineDesc<'a> {
+    pub vertex: &'a Shader,
+    pub hull: Option<&'a Shader>,
+    pub domain: Option<&'a Shader>,
+    pub geometry: Option<&'a Shader>,
+    pub pixel: Option<&'a Shader>,
+    pub topology: Topology,
+    pub input_layout: &'a [D3D12_INPUT_ELEMENT_DESC],
+}
+
+impl<'a> GraphicsPipelineDesc<'a> {
+    pub fn new(
+        vertex: &'a Shader,
+        pixel: &'a Shader,
+        input_layout: &'a [D3D12_INPUT_ELEMENT_DESC],
+    ) -> Self {
+        GraphicsPipelineDesc {
+            vertex: vertex,
+            hull: None,
+            domain: None,
+            geometry: None,
+            pixel: Some(pixel),
+            topology: Topology::TriangleList,
+            input_layout: input_layout,
+        }
+    }
+
+    pub fn stages(&self) -> Vec<ShaderStage> {
+        let mut stages = vec![ShaderStage::Vertex];
+        if self.hull.is_some() {
+            stages.push(ShaderStage::Hull);
+        }
+        if self.domain.is_some() {
+            stages.push(ShaderStage::Domain);
+        }
+        if self.geometry.is_some() {
+            stages.push(ShaderStage::Geometry);
+        }
+        if self.pixel.is_some() {
+            stages.push(ShaderStage::Pixel);
+        }
+        stages
+    }
+
+    /// the optional features the stages need
+    pub fn features(&self) -> Vec<Feature> {
+        self.stages()
+            .into_iter()
+            .filter_map(Feature::for_stage)
+            .collect()
+    }
+}
+
+pub struct Pipeline {
+    pub state: *mut ID3D12PipelineState,
+    pub topology: Topology,
+}
+
+impl Pipeline {
+    pub fn create(
+        device: *mut ID3D12Device,
+        root_signature: *mut ID3D12RootSignature,
+        desc: &GraphicsPipelineDesc,
+    ) -> Result<Pipeline, String> {
+        validate_stages(&desc.stages(), desc.topology)?;
+
+        let mut graphics_pipeline: D3D12_GRAPHICS_PIPELINE_STATE_DESC = unsafe { zeroed() };
+        graphics_pipeline.pRootSignature = root_signature;
+        graphics_pipeline.VS = desc.vertex.bytecode();
+        if let Some(hull) = desc.hull {
+            graphics_pipeline.HS = hull.bytecode();
+        }
+        if let Some(domain) = desc.domain {
+            graphics_pipeline.DS = domain.bytecode();
+        }
+        if let Some(geometry) = desc.geometry {
+            graphics_pipeline.GS = geometry.bytecode();
+        }
+        if let Some(pixel) = desc.pixel {
+            graphics_pipeline.PS = pixel.bytecode();
+        }
+        graphics_pipeline.SampleMask = D3D12_DEFAULT_SAMPLE_MASK;
+        graphics_pipeline.BlendState.AlphaToCoverageEnable = FALSE;
+        graphics_pipeline.BlendState.IndependentBlendEnable = FALSE;
+
+        let mut render_target_blend_desc: D3D12_RENDER_TARGET_BLEND_DESC = unsafe { zeroed() };
+        render_target_blend_desc.BlendEnable = FALSE;
+        render_target_blend_desc.RenderTargetWriteMask = D3D12_COLOR_WRITE_ENABLE_ALL as u8;
+
+        render_target_blend_desc.LogicOpEnable = FALSE;
+
+        graphics_pipeline.BlendState.RenderTarget[0] = render_target_blend_desc;
+
+        graphics_pipeline.RasterizerState.MultisampleEnable = FALSE;
+        graphics_pipeline.RasterizerState.CullMode = D3D12_CULL_MODE_NONE;
+        graphics_pipeline.RasterizerState.FillMode = D3D12_FILL_MODE_SOLID;
+        graphics_pipeline.RasterizerState.DepthClipEnable = TRUE;
+
+        graphics_pipeline.RasterizerState.FrontCounterClockwise = FALSE;
+        graphics_pipeline.RasterizerState.DepthBias = D3D12_DEFAULT_DEPTH_BIAS as i32;
+        graphics_pipeline.RasterizerState.DepthBiasClamp = D3D12_DEFAULT_DEPTH_BIAS_CLAMP;
+        graphics_pipeline.RasterizerState.SlopeScaledDepthBias =
+            D3D12_DEFAULT_SLOPE_SCALED_DEPTH_BIAS;
+        graphics_pipeline.RasterizerState.AntialiasedLineEnable = FALSE;
+        graphics_pipeline.RasterizerState.ForcedSampleCount = 0;
+        graphics_pipeline.RasterizerState.ConservativeRaster =
+            D3D12_CONSERVATIVE_RASTERIZATION_MODE_OFF;
+
+        graphics_pipeline.DepthStencilState.DepthEnable = FALSE;
+        graphics_pipeline.DepthStencilState.StencilEnable = FALSE;
+
+        graphics_pipeline.InputLayout.pInputElementDescs = desc.input_layout.as_ptr();
+        graphics_pipeline.InputLayout.NumElements = desc.input_layout.len() as u32;
+
+        graphics_pipeline.IBStripCutValue = D3D12_INDEX_BUFFER_STRIP_CUT_VALUE_DISABLED;
+        graphics_pipeline.PrimitiveTopologyType = desc.topology.topology_type().to_d3d();
+
+        graphics_pipeline.NumRenderTargets = 1;
+        graphics_pipeline.RTVFormats[0] = DXGI_FORMAT_R8G8B8A8_UNORM;
+
+        graphics_pipeline.SampleDesc.Count = 1;
+        graphics_pipeline.SampleDesc.Quality = 0;
+
+        let mut pipeline_state: *mut ID3D12PipelineState = null_mut();
+        let result = unsafe {
+            (*device).CreateGraphicsPipelineState(
+                &graphics_pipeline,
+                &ID3D12PipelineState::uuidof(),
+                &mut pipeline_state as *mut *mut _ as *mut *mut c_void,
+            )
+        };
+        if is_failed(result) {
+            return Err("failed: CreateGraphicsPipelineState".to_string());
+        }
+        Ok(Pipeline {
+            state: pipeline_state,
+            topology: desc.topology,
+        })
+    }
+
+    pub fn topology_type(&self) -> TopologyType {
+        self.topology.topology_type()
+    }
+}
+
+pub fn create_root_signature(
+    device: *mut ID3D12Device,
+    root_signature_desc: &D3D12_ROOT_SIGNATURE_DESC,
+) -> Result<*mut ID3D12RootSignature, String> {
+    let mut root_signature: *mut ID3D12RootSignature = null_mut();
+    let mut root_sig_blob: *mut ID3DBlob = null_mut();
+    let mut error_blob: *mut ID3DBlob = null_mut();
+    let result = unsafe {
+        D3D12SerializeRootSignature(
+            root_signature_desc,
+            D3D_ROOT_SIGNATURE_VERSION_1_0,
+            &mut root_sig_blob,
+            &mut error_blob,
+        )
+    };
+    if !error_blob.is_null() {
+        let detail = unsafe { blob_to_string(error_blob) };
+        unsafe { (*error_blob).Release() };
+        if is_failed(result) {
+            return Err(format!("failed: D3D12SerializeRootSignature: {}", detail));
+        }
+    }
+    if is_failed(result) {
+        return Err("failed: D3D12SerializeRootSignature".to_string());
+    }
+    let result = unsafe {
+        (*device).CreateRootSignature(
+            0,
+            (*root_sig_blob).GetBufferPointer(),
+            (*root_sig_blob).GetBufferSize(),
+            &ID3D12RootSignature::uuidof(),
+            &mut root_signature as *mut *mut _ as *mut *mut c_void,
+        )
+    };
+    unsafe { (*root_sig_blob).Release() };
+    if is_failed(result) {
+        return Err("failed: CreateRootSignature".to_string());
+    }
+    Ok(root_signature)
+}
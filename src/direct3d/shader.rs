@@ -0,0 +1,88 @@
+use std::ptr::{null, null_mut};
+use widestring::U16CString;
+use winapi::um::{
+    d3d12::D3D12_SHADER_BYTECODE,
+    d3dcommon::ID3DBlob,
+    d3dcompiler::{
+        D3DCompileFromFile, D3DCOMPILE_DEBUG, D3DCOMPILE_SKIP_OPTIMIZATION,
+        D3D_COMPILE_STANDARD_FILE_INCLUDE,
+    },
+};
+
+use crate::util::*;
+
+pub struct Shader {
+    blob: *mut ID3DBlob,
+}
+
+impl Shader {
+    pub fn compile_from_file(path: &str, entry: &str, target: &str) -> Result<Shader, String> {
+        let wide_path = U16CString::from_str(path).unwrap();
+        let entry_c = format!("{}\0", entry);
+        let target_c = format!("{}\0", target);
+        let mut blob: *mut ID3DBlob = null_mut();
+        let mut error_blob: *mut ID3DBlob = null_mut();
+        let result = unsafe {
+            D3DCompileFromFile(
+                wide_path.as_ptr(),
+                null(),
+                D3D_COMPILE_STANDARD_FILE_INCLUDE,
+                entry_c.as_ptr() as *const _,
+                target_c.as_ptr() as *const _,
+                D3DCOMPILE_DEBUG | D3DCOMPILE_SKIP_OPTIMIZATION,
+                0,
+                &mut blob,
+                &mut error_blob,
+            )
+        };
+        if is_failed(result) {
+            let detail = if error_blob.is_null() {
+                String::new()
+            } else {
+                let detail = unsafe { blob_to_string(error_blob) };
+                unsafe { (*error_blob).Release() };
+                detail
+            };
+            return Err(format!(
+                "failed: compile {} ({}, {}): {}",
+                path, entry, target, detail
+            ));
+        }
+        Ok(Shader { blob: blob })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                (*self.blob).GetBufferPointer() as *const u8,
+                (*self.blob).GetBufferSize(),
+            )
+        }
+    }
+
+    pub fn bytecode(&self) -> D3D12_SHADER_BYTECODE {
+        unsafe {
+            D3D12_SHADER_BYTECODE {
+                pShaderBytecode: (*self.blob).GetBufferPointer(),
+                BytecodeLength: (*self.blob).GetBufferSize(),
+            }
+        }
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe { (*self.blob).Release() };
+    }
+}
+
+/// the text of an error blob, without its terminator
+pub(super) unsafe fn blob_to_string(blob: *mut ID3DBlob) -> String {
+    let bytes = std::slice::from_raw_parts(
+        (*blob).GetBufferPointer() as *const u8,
+        (*blob).GetBufferSize(),
+    );
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
@@ -0,0 +1,532 @@
+//! the swapchain of one window, with its back buffers and window mode.
+//! every surface presents from the device and direct queue of `Direct3D`.
+
+use std::mem::zeroed;
+use std::ptr::{null, null_mut};
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        dxgi::{IDXGIAdapter1, IDXGIOutput, DXGI_SWAP_CHAIN_DESC},
+        dxgi1_2::IDXGISwapChain1,
+        dxgi1_5::IDXGISwapChain4,
+        dxgi1_6::IDXGIFactory6,
+        minwindef::{FALSE, TRUE, UINT},
+    },
+    um::{
+        d3d12::*, handleapi::CloseHandle, synchapi::WaitForSingleObjectEx, unknwnbase::IUnknown,
+        winnt::HANDLE,
+    },
+    Interface,
+};
+
+use super::{adapter, command, display, engine, present};
+use crate::{
+    util::*,
+    window::{
+        id::WindowId,
+        mode::{ModeState, ModeStep, WindowMode},
+        Window,
+    },
+};
+
+pub struct Surface {
+    window: WindowId,
+    swapchain: *mut IDXGISwapChain4,
+    present: present::PresentConfig,
+    frame_latency_waitable: HANDLE,
+    rtv_heaps: *mut ID3D12DescriptorHeap,
+    back_buffers: Vec<*mut ID3D12Resource>,
+    viewport: D3D12_VIEWPORT,
+    scissorrect: D3D12_RECT,
+    mode: ModeState,
+}
+
+fn swapchain_flags(config: &present::PresentConfig) -> UINT {
+    use winapi::shared::dxgi::{
+        DXGI_SWAP_CHAIN_FLAG_ALLOW_MODE_SWITCH, DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING,
+        DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT,
+    };
+    let mut flags =
+        DXGI_SWAP_CHAIN_FLAG_ALLOW_MODE_SWITCH | DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT;
+    if config.tearing {
+        flags |= DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING;
+    }
+    flags
+}
+
+// not declared by winapi 0.3.8
+const DXGI_MWA_NO_ALT_ENTER: UINT = 1 << 1;
+
+fn create_swapchain(
+    factory: *mut IDXGIFactory6,
+    command_manager: &command::CommandManager,
+    window: &Window,
+    config: &present::PresentConfig,
+) -> Result<*mut IDXGISwapChain4, String> {
+    use winapi::shared::{
+        dxgi::DXGI_SWAP_EFFECT_FLIP_DISCARD,
+        dxgi1_2::{DXGI_ALPHA_MODE_UNSPECIFIED, DXGI_SCALING_STRETCH, DXGI_SWAP_CHAIN_DESC1},
+        dxgiformat::DXGI_FORMAT_R8G8B8A8_UNORM,
+        dxgitype::{DXGI_SAMPLE_DESC, DXGI_USAGE_BACK_BUFFER},
+    };
+    let desc = DXGI_SWAP_CHAIN_DESC1 {
+        Width: window.width as UINT,
+        Height: window.height as UINT,
+        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        Stereo: FALSE,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1 as UINT,
+            Quality: 0 as UINT,
+        },
+        BufferUsage: DXGI_USAGE_BACK_BUFFER,
+        BufferCount: config.buffer_count,
+        Scaling: DXGI_SCALING_STRETCH,
+        SwapEffect: DXGI_SWAP_EFFECT_FLIP_DISCARD,
+        AlphaMode: DXGI_ALPHA_MODE_UNSPECIFIED,
+        Flags: swapchain_flags(config),
+    };
+
+    let mut swapchain: *mut IDXGISwapChain4 = null_mut();
+    let result = unsafe {
+        (*factory).CreateSwapChainForHwnd(
+            command_manager.get_queue() as *mut _ as *mut IUnknown,
+            window.handle,
+            &desc,
+            null(),
+            null_mut(),
+            &mut swapchain as *mut *mut _ as *mut *mut IDXGISwapChain1,
+        )
+    };
+    if is_failed(result) {
+        return Err("failed: create IDXGIFactory6".to_string());
+    }
+    // alt+enter is handled by the window so that it can switch to borderless too
+    let result = unsafe { (*factory).MakeWindowAssociation(window.handle, DXGI_MWA_NO_ALT_ENTER) };
+    if is_failed(result) {
+        return Err("failed: MakeWindowAssociation".to_string());
+    }
+    Ok(swapchain)
+}
+
+/// sets the frame latency of `swapchain` and returns the object that is
+/// signaled when another frame may be started
+fn set_frame_latency(
+    swapchain: *mut IDXGISwapChain4,
+    config: &present::PresentConfig,
+) -> Result<HANDLE, String> {
+    let result = unsafe { (*swapchain).SetMaximumFrameLatency(config.max_latency) };
+    if is_failed(result) {
+        return Err("failed: SetMaximumFrameLatency".to_string());
+    }
+    let waitable = unsafe { (*swapchain).GetFrameLatencyWaitableObject() };
+    if waitable.is_null() {
+        return Err("failed: GetFrameLatencyWaitableObject".to_string());
+    }
+    Ok(waitable)
+}
+
+// one view per back buffer, enough for any buffer count
+fn create_rtv_heaps(dev: *mut ID3D12Device) -> Result<*mut ID3D12DescriptorHeap, String> {
+    let desc = D3D12_DESCRIPTOR_HEAP_DESC {
+        Type: D3D12_DESCRIPTOR_HEAP_TYPE_RTV, // render target view
+        NumDescriptors: present::MAX_BUFFER_COUNT,
+        Flags: D3D12_DESCRIPTOR_HEAP_FLAG_NONE,
+        NodeMask: 0,
+    };
+    let mut rtv_heaps: *mut ID3D12DescriptorHeap = null_mut();
+    let result = unsafe {
+        (*dev).CreateDescriptorHeap(
+            &desc,
+            &ID3D12DescriptorHeap::uuidof(),
+            &mut rtv_heaps as *mut *mut _ as *mut *mut c_void,
+        )
+    };
+    if is_succeeded(result) {
+        Ok(rtv_heaps)
+    } else {
+        Err("failed: create ID3D12DescriptorHeap".to_string())
+    }
+}
+
+fn create_back_buffers(
+    device: *mut ID3D12Device,
+    swapchain: *mut IDXGISwapChain4,
+    rtv_heaps: *mut ID3D12DescriptorHeap,
+) -> Result<Vec<*mut ID3D12Resource>, String> {
+    let mut swapchain_desc: DXGI_SWAP_CHAIN_DESC = unsafe { zeroed() };
+    let result = unsafe { (*swapchain).GetDesc(&mut swapchain_desc) };
+    if is_failed(result) {
+        return Err("failed: get swapchain descriptor".to_string());
+    }
+    let mut back_buffers: Vec<*mut ID3D12Resource> = vec![];
+    back_buffers.resize(swapchain_desc.BufferCount as usize, null_mut());
+    let mut handle: D3D12_CPU_DESCRIPTOR_HANDLE =
+        unsafe { (*rtv_heaps).GetCPUDescriptorHandleForHeapStart() };
+    for i in 0..swapchain_desc.BufferCount {
+        let result = unsafe {
+            (*swapchain).GetBuffer(
+                i,
+                &ID3D12Resource::uuidof(),
+                &mut back_buffers[i as usize] as *mut *mut _ as *mut *mut c_void,
+            )
+        };
+        if is_failed(result) {
+            return Err("failed: get buffer".to_string());
+        }
+        unsafe { (*device).CreateRenderTargetView(back_buffers[i as usize], null(), handle) }
+        handle.ptr +=
+            unsafe { (*device).GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_RTV) }
+                as usize;
+    }
+    Ok(back_buffers)
+}
+
+fn full_viewport(width: u32, height: u32) -> (D3D12_VIEWPORT, D3D12_RECT) {
+    let viewport = D3D12_VIEWPORT {
+        Width: width as f32,
+        Height: height as f32,
+        TopLeftX: 0.0f32,
+        TopLeftY: 0.0f32,
+        MaxDepth: 1.0f32,
+        MinDepth: 0.0f32,
+    };
+
+    let scissorrect = D3D12_RECT {
+        top: 0,
+        left: 0,
+        right: width as i32,
+        bottom: height as i32,
+    };
+    (viewport, scissorrect)
+}
+
+impl Surface {
+    /// a swapchain for `window` of its current size. `config` must have
+    /// been validated against the device.
+    pub fn create(
+        device: *mut ID3D12Device,
+        factory: *mut IDXGIFactory6,
+        engine: &engine::Engine,
+        window: &Window,
+        config: present::PresentConfig,
+    ) -> Result<Surface, String> {
+        let swapchain = create_swapchain(factory, &engine.direct, window, &config)?;
+        let frame_latency_waitable = set_frame_latency(swapchain, &config)?;
+        let rtv_heaps = create_rtv_heaps(device)?;
+        let back_buffers = create_back_buffers(device, swapchain, rtv_heaps)?;
+        let (viewport, scissorrect) = full_viewport(window.width as u32, window.height as u32);
+        Ok(Surface {
+            window: window.id(),
+            swapchain: swapchain,
+            present: config,
+            frame_latency_waitable: frame_latency_waitable,
+            rtv_heaps: rtv_heaps,
+            back_buffers: back_buffers,
+            viewport: viewport,
+            scissorrect: scissorrect,
+            mode: ModeState::new(),
+        })
+    }
+
+    pub fn window(&self) -> WindowId {
+        self.window
+    }
+
+    pub fn viewport(&self) -> &D3D12_VIEWPORT {
+        &self.viewport
+    }
+
+    pub fn scissor_rect(&self) -> &D3D12_RECT {
+        &self.scissorrect
+    }
+
+    pub fn present_config(&self) -> &present::PresentConfig {
+        &self.present
+    }
+
+    /// false after a failed resize or reconfiguration left the surface
+    /// without back buffers, until a later resize succeeds
+    pub fn is_usable(&self) -> bool {
+        !self.back_buffers.is_empty()
+    }
+
+    fn release_back_buffers(&mut self) {
+        for back_buffer in self.back_buffers.drain(..) {
+            unsafe { (*back_buffer).Release() };
+        }
+    }
+
+    /// resizes the back buffers. the gpu must be done with them. on failure
+    /// the surface is not usable until it is resized again.
+    pub fn resize(
+        &mut self,
+        device: *mut ID3D12Device,
+        width: u32,
+        height: u32,
+    ) -> Result<(), String> {
+        self.release_back_buffers();
+        use winapi::shared::dxgiformat::DXGI_FORMAT_UNKNOWN;
+        let result = unsafe {
+            (*self.swapchain).ResizeBuffers(
+                0,
+                width,
+                height,
+                DXGI_FORMAT_UNKNOWN,
+                swapchain_flags(&self.present),
+            )
+        };
+        if is_failed(result) {
+            return Err("failed: ResizeBuffers".to_string());
+        }
+        self.back_buffers = create_back_buffers(device, self.swapchain, self.rtv_heaps)?;
+        let (viewport, scissorrect) = full_viewport(width, height);
+        self.viewport = viewport;
+        self.scissorrect = scissorrect;
+        Ok(())
+    }
+
+    /// switches to `config`, which must have been validated, recreating the
+    /// swapchain if its flags change
+    pub fn set_present_config(
+        &mut self,
+        device: *mut ID3D12Device,
+        factory: *mut IDXGIFactory6,
+        adapter: *mut IDXGIAdapter1,
+        engine: &engine::Engine,
+        window: &Window,
+        config: present::PresentConfig,
+    ) -> Result<(), String> {
+        let reconfigure = self.present.reconfigure(&config);
+        if reconfigure >= present::Reconfigure::ResizeBuffers {
+            engine.flush()?;
+            self.release_back_buffers();
+        }
+        match reconfigure {
+            present::Reconfigure::Recreate => {
+                // a swapchain must not be released in exclusive fullscreen
+                let exclusive = self.mode.mode() == WindowMode::Exclusive;
+                if exclusive {
+                    self.set_fullscreen_state(adapter, window, false)?;
+                }
+                unsafe {
+                    CloseHandle(self.frame_latency_waitable);
+                    (*self.swapchain).Release();
+                }
+                // cleared before anything below can fail, so that `drop` does
+                // not release them a second time
+                self.frame_latency_waitable = null_mut();
+                self.swapchain = null_mut();
+                self.swapchain = create_swapchain(factory, &engine.direct, window, &config)?;
+                // the new swapchain has the window's size, as after `resize`
+                let (viewport, scissorrect) =
+                    full_viewport(window.width as u32, window.height as u32);
+                self.viewport = viewport;
+                self.scissorrect = scissorrect;
+                if exclusive {
+                    self.set_fullscreen_state(adapter, window, true)?;
+                }
+            }
+            present::Reconfigure::ResizeBuffers => {
+                use winapi::shared::dxgiformat::DXGI_FORMAT_UNKNOWN;
+                let result = unsafe {
+                    (*self.swapchain).ResizeBuffers(
+                        config.buffer_count,
+                        self.viewport.Width as UINT,
+                        self.viewport.Height as UINT,
+                        DXGI_FORMAT_UNKNOWN,
+                        swapchain_flags(&config),
+                    )
+                };
+                if is_failed(result) {
+                    return Err("failed: ResizeBuffers".to_string());
+                }
+            }
+            _ => (),
+        }
+        if reconfigure >= present::Reconfigure::ResizeBuffers {
+            self.back_buffers = create_back_buffers(device, self.swapchain, self.rtv_heaps)?;
+        }
+        if reconfigure == present::Reconfigure::Recreate {
+            self.frame_latency_waitable = set_frame_latency(self.swapchain, &config)?;
+        } else if reconfigure >= present::Reconfigure::SetLatency {
+            // the waitable object stays the same
+            set_frame_latency(self.swapchain, &config)?;
+        }
+        self.present = config;
+        Ok(())
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        self.mode.mode()
+    }
+
+    /// switches `window` between windowed, borderless and exclusive fullscreen.
+    /// the back buffers follow once the new client size has been observed.
+    pub fn set_window_mode(
+        &mut self,
+        adapter: *mut IDXGIAdapter1,
+        window: &mut Window,
+        to: WindowMode,
+    ) -> Result<(), String> {
+        self.sync_window_mode();
+        let mut placement = None;
+        for step in self.mode.plan(to) {
+            match step {
+                ModeStep::SavePlacement => placement = Some(window.placement()),
+                ModeStep::RestorePlacement(placement) => window.restore_placement(&placement),
+                ModeStep::Borderless => window.set_borderless(),
+                ModeStep::EnterExclusive => self.set_fullscreen_state(adapter, window, true)?,
+                ModeStep::LeaveExclusive => self.set_fullscreen_state(adapter, window, false)?,
+            }
+        }
+        self.mode.finish(to, placement);
+        Ok(())
+    }
+
+    /// what alt+enter does
+    pub fn toggle_window_mode(
+        &mut self,
+        adapter: *mut IDXGIAdapter1,
+        window: &mut Window,
+    ) -> Result<(), String> {
+        self.sync_window_mode();
+        let to = self.mode.toggle_target();
+        self.set_window_mode(adapter, window, to)
+    }
+
+    /// notices when dxgi has left exclusive fullscreen by itself, e.g. on alt+tab
+    pub fn sync_window_mode(&mut self) {
+        if self.mode.mode() != WindowMode::Exclusive {
+            return;
+        }
+        let mut fullscreen = FALSE;
+        let result = unsafe { (*self.swapchain).GetFullscreenState(&mut fullscreen, null_mut()) };
+        if is_succeeded(result) && fullscreen == FALSE {
+            self.mode.exclusive_lost();
+        }
+    }
+
+    fn set_fullscreen_state(
+        &mut self,
+        adapter: *mut IDXGIAdapter1,
+        window: &Window,
+        fullscreen: bool,
+    ) -> Result<(), String> {
+        // without an output of our adapter on that monitor, dxgi picks one itself
+        let output = if fullscreen {
+            adapter::find_output(adapter, window.monitor()).unwrap_or(null_mut())
+        } else {
+            null_mut()
+        };
+        let result = unsafe {
+            (*self.swapchain).SetFullscreenState(if fullscreen { TRUE } else { FALSE }, output)
+        };
+        let result = if is_failed(result) {
+            Err("failed: SetFullscreenState".to_string())
+        } else if !output.is_null() {
+            self.switch_display_mode(output)
+        } else {
+            Ok(())
+        };
+        if !output.is_null() {
+            unsafe { (*output).Release() };
+        }
+        result
+    }
+
+    /// switches `output` to the mode closest to the back buffers, at the
+    /// highest refresh rate it offers for that size
+    fn switch_display_mode(&mut self, output: *mut IDXGIOutput) -> Result<(), String> {
+        use winapi::shared::{
+            dxgiformat::DXGI_FORMAT_R8G8B8A8_UNORM,
+            dxgitype::{
+                DXGI_MODE_DESC, DXGI_MODE_SCALING_UNSPECIFIED,
+                DXGI_MODE_SCANLINE_ORDER_UNSPECIFIED, DXGI_RATIONAL,
+            },
+        };
+        let modes = display::display_modes(output, DXGI_FORMAT_R8G8B8A8_UNORM)?;
+        let request = display::ModeRequest {
+            width: self.viewport.Width as u32,
+            height: self.viewport.Height as u32,
+            refresh_millihertz: None,
+        };
+        let mode = match display::closest_mode(&modes, &request) {
+            Some(index) => modes[index],
+            None => return Ok(()),
+        };
+        let desc = DXGI_MODE_DESC {
+            Width: mode.width,
+            Height: mode.height,
+            RefreshRate: DXGI_RATIONAL {
+                Numerator: mode.refresh_rate.numerator,
+                Denominator: mode.refresh_rate.denominator,
+            },
+            Format: mode.format,
+            ScanlineOrdering: DXGI_MODE_SCANLINE_ORDER_UNSPECIFIED,
+            Scaling: DXGI_MODE_SCALING_UNSPECIFIED,
+        };
+        let result = unsafe { (*self.swapchain).ResizeTarget(&desc) };
+        if is_failed(result) {
+            return Err("failed: ResizeTarget".to_string());
+        }
+        Ok(())
+    }
+
+    /// blocks until the display has caught up to `max_latency` frames
+    pub fn wait_for_frame(&self) {
+        unsafe { WaitForSingleObjectEx(self.frame_latency_waitable, 1000, TRUE) };
+    }
+
+    /// the back buffer to draw into now and its render target view
+    pub fn current_target(
+        &self,
+        device: *mut ID3D12Device,
+    ) -> Result<(*mut ID3D12Resource, D3D12_CPU_DESCRIPTOR_HANDLE), String> {
+        if !self.is_usable() {
+            return Err(format!("no back buffers for {}", self.window));
+        }
+        let index = unsafe { (*self.swapchain).GetCurrentBackBufferIndex() } as usize;
+        let mut rtv_handle = unsafe { (*self.rtv_heaps).GetCPUDescriptorHandleForHeapStart() };
+        rtv_handle.ptr += index
+            * unsafe { (*device).GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_RTV) }
+                as usize;
+        Ok((self.back_buffers[index], rtv_handle))
+    }
+
+    pub fn present(&mut self) -> Result<(), String> {
+        use winapi::shared::dxgi::DXGI_PRESENT_ALLOW_TEARING;
+        let exclusive = self.mode.mode() == WindowMode::Exclusive;
+        let flags = if self.present.present_with_tearing(exclusive) {
+            DXGI_PRESENT_ALLOW_TEARING
+        } else {
+            0
+        };
+        let result = unsafe { (*self.swapchain).Present(self.present.sync_interval, flags) };
+        if is_failed(result) {
+            return Err("failed: Present".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// the gpu must be done with the back buffers
+impl Drop for Surface {
+    fn drop(&mut self) {
+        // a failed `set_present_config` can leave no swapchain behind
+        let swapchain = !self.swapchain.is_null();
+        // a swapchain must not be released in exclusive fullscreen
+        if swapchain && self.mode.mode() == WindowMode::Exclusive {
+            unsafe { (*self.swapchain).SetFullscreenState(FALSE, null_mut()) };
+        }
+        self.release_back_buffers();
+        unsafe {
+            if !self.frame_latency_waitable.is_null() {
+                CloseHandle(self.frame_latency_waitable);
+            }
+            (*self.rtv_heaps).Release();
+            if swapchain {
+                (*self.swapchain).Release();
+            }
+        }
+    }
+}
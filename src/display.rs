@@ -0,0 +1,180 @@
+//! display modes, and picking the one exclusive fullscreen switches to.
+//! `direct3d::display` lists the modes dxgi offers.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Rational {
+    /// the rate in thousandths of a hertz, so that modes compare exactly
+    pub fn millihertz(&self) -> u64 {
+        if self.denominator == 0 {
+            0
+        } else {
+            self.numerator as u64 * 1000 / self.denominator as u64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: Rational,
+    /// a `DXGI_FORMAT`
+    pub format: u32,
+}
+
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mhz = self.refresh_rate.millihertz();
+        write!(
+            f,
+            "{}x{} @ {}.{:03} Hz",
+            self.width,
+            self.height,
+            mhz / 1000,
+            mhz % 1000
+        )
+    }
+}
+
+/// what exclusive fullscreen should switch the display to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeRequest {
+    pub width: u32,
+    pub height: u32,
+    /// preferred refresh rate in millihertz, or the highest available if `None`
+    pub refresh_millihertz: Option<u64>,
+}
+
+/// index of the mode in `modes` closest to `request`: the nearest resolution
+/// first, then the nearest aspect ratio, then the nearest (or highest)
+/// refresh rate among those
+pub fn closest_mode(modes: &[DisplayMode], request: &ModeRequest) -> Option<usize> {
+    let distance = |a: u32, b: u32| (a as i64 - b as i64).unsigned_abs();
+    // width / height in millionths, so that ratios compare exactly enough
+    let aspect = |width: u32, height: u32| width as u64 * 1_000_000 / height.max(1) as u64;
+    let wanted_aspect = aspect(request.width, request.height);
+    modes
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, mode)| {
+            let resolution =
+                distance(mode.width, request.width) + distance(mode.height, request.height);
+            let aspect = aspect(mode.width, mode.height).abs_diff(wanted_aspect);
+            let refresh = mode.refresh_rate.millihertz();
+            let refresh = match request.refresh_millihertz {
+                Some(wanted) => wanted.abs_diff(refresh),
+                None => u64::MAX - refresh,
+            };
+            (resolution, aspect, refresh)
+        })
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // DXGI_FORMAT_R8G8B8A8_UNORM
+    const RGBA8_UNORM: u32 = 28;
+
+    fn mode(width: u32, height: u32, numerator: u32, denominator: u32) -> DisplayMode {
+        DisplayMode {
+            width: width,
+            height: height,
+            refresh_rate: Rational {
+                numerator: numerator,
+                denominator: denominator,
+            },
+            format: RGBA8_UNORM,
+        }
+    }
+
+    fn request(width: u32, height: u32, refresh_millihertz: Option<u64>) -> ModeRequest {
+        ModeRequest {
+            width: width,
+            height: height,
+            refresh_millihertz: refresh_millihertz,
+        }
+    }
+
+    fn modes() -> Vec<DisplayMode> {
+        vec![
+            mode(1280, 720, 60, 1),
+            mode(1920, 1080, 60000, 1001),
+            mode(1920, 1080, 60, 1),
+            mode(1920, 1080, 144, 1),
+            mode(2560, 1440, 60, 1),
+        ]
+    }
+
+    #[test]
+    fn exact_match() {
+        let modes = modes();
+        assert_eq!(
+            closest_mode(&modes, &request(1920, 1080, Some(60_000))),
+            Some(2)
+        );
+        assert_eq!(
+            closest_mode(&modes, &request(1920, 1080, Some(59_940))),
+            Some(1)
+        );
+        assert_eq!(closest_mode(&modes, &request(1280, 720, None)), Some(0));
+    }
+
+    #[test]
+    fn nearest_refresh_rate() {
+        let modes = modes();
+        assert_eq!(
+            closest_mode(&modes, &request(1920, 1080, Some(120_000))),
+            Some(3)
+        );
+        assert_eq!(
+            closest_mode(&modes, &request(1920, 1080, Some(75_000))),
+            Some(2)
+        );
+        // the highest when none is asked for
+        assert_eq!(closest_mode(&modes, &request(1920, 1080, None)), Some(3));
+        // the resolution comes first
+        assert_eq!(
+            closest_mode(&modes, &request(1280, 720, Some(144_000))),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn nearest_resolution() {
+        let modes = modes();
+        assert_eq!(closest_mode(&modes, &request(1900, 1000, None)), Some(3));
+        assert_eq!(closest_mode(&modes, &request(4000, 3000, None)), Some(4));
+        assert_eq!(closest_mode(&[], &request(1920, 1080, None)), None);
+    }
+
+    #[test]
+    fn aspect_ratio_breaks_ties() {
+        // both are 200 pixels away from 1400x1000 (7:5)
+        let modes = [mode(1600, 1000, 144, 1), mode(1300, 900, 60, 1)];
+        // 1300x900 is 1.444 against 1.4, 1600x1000 is 1.6, which wins over
+        // the higher refresh rate
+        assert_eq!(closest_mode(&modes, &request(1400, 1000, None)), Some(1));
+        // 1500x1100 is 1.364, closer still
+        let modes = [mode(1300, 900, 144, 1), mode(1500, 1100, 60, 1)];
+        assert_eq!(closest_mode(&modes, &request(1400, 1000, None)), Some(1));
+    }
+
+    #[test]
+    fn refresh_rates() {
+        assert_eq!(mode(1, 1, 60000, 1001).refresh_rate.millihertz(), 59_940);
+        assert_eq!(mode(1, 1, 0, 0).refresh_rate.millihertz(), 0);
+        assert_eq!(
+            mode(1920, 1080, 60000, 1001).to_string(),
+            "1920x1080 @ 59.940 Hz"
+        );
+    }
+}
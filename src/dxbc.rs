@@ -0,0 +1,397 @@
+//! minimal reader for DXBC shader containers, as produced by `D3DCompile`.
+//! only pure byte parsing lives here so that it does not depend on d3d.
+
+use crate::cbuffer::packing::{Field, Layout, Major, Member, Scalar, Type};
+
+const HEADER_SIZE: usize = 32;
+const CHUNK_HEADER_SIZE: usize = 8;
+
+const OPCODE_CUSTOMDATA: u32 = 53;
+const OPCODE_DCL_THREAD_GROUP: u32 = 155;
+
+// sizes of the RDEF records before shader model 5, which adds an 'RD11'
+// header that gives the sizes itself
+const RDEF_HEADER_SIZE: usize = 28;
+const RDEF_BUFFER_SIZE: usize = 24;
+const RDEF_VARIABLE_SIZE: usize = 24;
+const RDEF_MEMBER_SIZE: usize = 12;
+
+// D3D_CBUFFER_TYPE
+const CT_CBUFFER: u32 = 0;
+
+// D3D_SHADER_VARIABLE_CLASS
+const SVC_SCALAR: u16 = 0;
+const SVC_VECTOR: u16 = 1;
+const SVC_MATRIX_ROWS: u16 = 2;
+const SVC_MATRIX_COLUMNS: u16 = 3;
+const SVC_STRUCT: u16 = 5;
+
+// D3D_SHADER_VARIABLE_TYPE
+const SVT_BOOL: u16 = 1;
+const SVT_INT: u16 = 2;
+const SVT_FLOAT: u16 = 3;
+const SVT_UINT: u16 = 19;
+
+/// a constant buffer as the compiler laid it out
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantBuffer {
+    pub name: String,
+    /// the whole buffer in bytes, a multiple of the register size
+    pub size: u32,
+    pub variables: Vec<Member>,
+}
+
+impl ConstantBuffer {
+    pub fn layout(&self) -> Layout {
+        Layout {
+            members: self.variables.clone(),
+            size: self
+                .variables
+                .iter()
+                .map(|variable| variable.offset + variable.size)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+pub struct Chunk<'a> {
+    pub fourcc: [u8; 4],
+    pub data: &'a [u8],
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| format!("invalid dxbc: unexpected end of data at {}", offset))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("invalid dxbc: unexpected end of data at {}", offset))
+}
+
+pub fn chunks(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+    if bytes.len() < HEADER_SIZE || &bytes[0..4] != b"DXBC" {
+        return Err("invalid dxbc: missing container header".to_string());
+    }
+    let total_size = read_u32(bytes, 24)? as usize;
+    if total_size > bytes.len() {
+        return Err(format!(
+            "invalid dxbc: container claims {} bytes, got {}",
+            total_size,
+            bytes.len()
+        ));
+    }
+    let bytes = &bytes[..total_size];
+    let chunk_count = read_u32(bytes, 28)? as usize;
+    // the offsets table must fit before trusting the count with memory
+    if chunk_count > (total_size.saturating_sub(HEADER_SIZE)) / 4 {
+        return Err(format!(
+            "invalid dxbc: {} chunks do not fit in {} bytes",
+            chunk_count, total_size
+        ));
+    }
+    let mut chunks = Vec::with_capacity(chunk_count);
+    for i in 0..chunk_count {
+        let offset = read_u32(bytes, HEADER_SIZE + i * 4)? as usize;
+        let size = read_u32(bytes, offset + 4)? as usize;
+        let begin = offset + CHUNK_HEADER_SIZE;
+        let data = bytes
+            .get(begin..begin + size)
+            .ok_or_else(|| format!("invalid dxbc: chunk {} is out of range", i))?;
+        let mut fourcc = [0u8; 4];
+        fourcc.copy_from_slice(&bytes[offset..offset + 4]);
+        chunks.push(Chunk {
+            fourcc: fourcc,
+            data: data,
+        });
+    }
+    Ok(chunks)
+}
+
+pub fn find_chunk<'a>(bytes: &'a [u8], fourcc: &[u8; 4]) -> Result<Option<&'a [u8]>, String> {
+    Ok(chunks(bytes)?
+        .into_iter()
+        .find(|chunk| &chunk.fourcc == fourcc)
+        .map(|chunk| chunk.data))
+}
+
+/// reads the `[numthreads(x, y, z)]` declaration of a compiled compute shader.
+/// returns `None` if the shader does not declare a thread group.
+pub fn num_threads(bytes: &[u8]) -> Result<Option<[u32; 3]>, String> {
+    let program = match find_chunk(bytes, b"SHEX")? {
+        Some(program) => program,
+        None => find_chunk(bytes, b"SHDR")?
+            .ok_or_else(|| "invalid dxbc: no shader program chunk".to_string())?,
+    };
+    // version token and program length in dwords, followed by instructions
+    let length = read_u32(program, 4)? as usize;
+    let mut pos = 2;
+    while pos < length {
+        let token = read_u32(program, pos * 4)?;
+        let opcode = token & 0x7ff;
+        let instruction_length = if opcode == OPCODE_CUSTOMDATA {
+            read_u32(program, (pos + 1) * 4)? as usize
+        } else {
+            ((token >> 24) & 0x7f) as usize
+        };
+        if instruction_length == 0 {
+            return Err(format!("invalid dxbc: zero-length instruction at {}", pos));
+        }
+        if opcode == OPCODE_DCL_THREAD_GROUP {
+            return Ok(Some([
+                read_u32(program, (pos + 1) * 4)?,
+                read_u32(program, (pos + 2) * 4)?,
+                read_u32(program, (pos + 3) * 4)?,
+            ]));
+        }
+        pos += instruction_length;
+    }
+    Ok(None)
+}
+
+/// the nul terminated string at `offset`
+fn read_string(bytes: &[u8], offset: usize) -> Result<String, String> {
+    let tail = bytes
+        .get(offset..)
+        .ok_or_else(|| format!("invalid dxbc: string out of range at {}", offset))?;
+    let len = tail
+        .iter()
+        .position(|byte| *byte == 0)
+        .ok_or_else(|| format!("invalid dxbc: unterminated string at {}", offset))?;
+    Ok(String::from_utf8_lossy(&tail[..len]).into_owned())
+}
+
+/// checks that a table of `count` records of `size` bytes at `offset` lies
+/// inside `bytes`, before anything is allocated for it
+fn check_table(
+    bytes: &[u8],
+    offset: usize,
+    count: usize,
+    size: usize,
+    what: &str,
+) -> Result<(), String> {
+    match count
+        .checked_mul(size)
+        .and_then(|len| len.checked_add(offset))
+    {
+        Some(end) if end <= bytes.len() => Ok(()),
+        _ => Err(format!(
+            "invalid dxbc: {} {} at {} are out of range",
+            count, what, offset
+        )),
+    }
+}
+
+/// the `D3D12_SHADER_TYPE_DESC` at `offset`, relative to the RDEF data
+fn read_type(rdef: &[u8], offset: usize, depth: u32) -> Result<Type, String> {
+    if depth > 16 {
+        return Err("invalid dxbc: types nested too deeply".to_string());
+    }
+    let class = read_u16(rdef, offset)?;
+    let scalar = match read_u16(rdef, offset + 2)? {
+        SVT_BOOL => Some(Scalar::Bool),
+        SVT_INT => Some(Scalar::Int),
+        SVT_FLOAT => Some(Scalar::Float),
+        SVT_UINT => Some(Scalar::Uint),
+        _ => None,
+    };
+    let rows = read_u16(rdef, offset + 4)? as u32;
+    let columns = read_u16(rdef, offset + 6)? as u32;
+    let elements = read_u16(rdef, offset + 8)? as u32;
+    let member_count = read_u16(rdef, offset + 10)? as usize;
+    let member_offset = read_u32(rdef, offset + 12)? as usize;
+    let unsupported = || {
+        format!(
+            "unsupported constant of class {} and type {}",
+            class,
+            read_u16(rdef, offset + 2).unwrap_or(0)
+        )
+    };
+    let ty = match (class, scalar) {
+        (SVC_SCALAR, Some(scalar)) => Type::Scalar(scalar),
+        (SVC_VECTOR, Some(scalar)) => Type::Vector(scalar, columns),
+        (SVC_MATRIX_ROWS, Some(scalar)) | (SVC_MATRIX_COLUMNS, Some(scalar)) => Type::Matrix {
+            scalar: scalar,
+            rows: rows,
+            columns: columns,
+            major: if class == SVC_MATRIX_ROWS {
+                Major::Row
+            } else {
+                Major::Column
+            },
+        },
+        (SVC_STRUCT, _) => {
+            check_table(
+                rdef,
+                member_offset,
+                member_count,
+                RDEF_MEMBER_SIZE,
+                "members",
+            )?;
+            let mut fields = Vec::with_capacity(member_count);
+            for i in 0..member_count {
+                let at = member_offset + i * RDEF_MEMBER_SIZE;
+                let name = read_string(rdef, read_u32(rdef, at)? as usize)?;
+                let ty = read_type(rdef, read_u32(rdef, at + 4)? as usize, depth + 1)?;
+                fields.push(Field::new(&name, ty));
+            }
+            Type::Struct(fields)
+        }
+        _ => return Err(unsupported()),
+    };
+    Ok(if elements > 0 {
+        Type::Array(Box::new(ty), elements)
+    } else {
+        ty
+    })
+}
+
+/// the constant buffers in the reflection data of a compiled shader, with
+/// every variable where the compiler put it
+pub fn constant_buffers(bytes: &[u8]) -> Result<Vec<ConstantBuffer>, String> {
+    let rdef = find_chunk(bytes, b"RDEF")?
+        .ok_or_else(|| "invalid dxbc: no reflection chunk".to_string())?;
+    let buffer_count = read_u32(rdef, 0)? as usize;
+    let buffer_offset = read_u32(rdef, 4)? as usize;
+    let variable_size = if rdef.get(RDEF_HEADER_SIZE..RDEF_HEADER_SIZE + 4) == Some(b"RD11") {
+        // header, buffer, binding and then variable record sizes follow
+        read_u32(rdef, RDEF_HEADER_SIZE + 16)? as usize
+    } else {
+        RDEF_VARIABLE_SIZE
+    };
+    if variable_size < RDEF_VARIABLE_SIZE {
+        return Err(format!(
+            "invalid dxbc: variables of {} bytes are too small",
+            variable_size
+        ));
+    }
+    check_table(
+        rdef,
+        buffer_offset,
+        buffer_count,
+        RDEF_BUFFER_SIZE,
+        "buffers",
+    )?;
+    let mut buffers = Vec::with_capacity(buffer_count);
+    for i in 0..buffer_count {
+        let at = buffer_offset + i * RDEF_BUFFER_SIZE;
+        let name = read_string(rdef, read_u32(rdef, at)? as usize)?;
+        let variable_count = read_u32(rdef, at + 4)? as usize;
+        let variable_offset = read_u32(rdef, at + 8)? as usize;
+        let size = read_u32(rdef, at + 12)?;
+        if read_u32(rdef, at + 20)? != CT_CBUFFER {
+            continue;
+        }
+        check_table(
+            rdef,
+            variable_offset,
+            variable_count,
+            variable_size,
+            "variables",
+        )?;
+        let mut variables = Vec::with_capacity(variable_count);
+        for j in 0..variable_count {
+            let at = variable_offset + j * variable_size;
+            let variable_name = read_string(rdef, read_u32(rdef, at)? as usize)?;
+            let ty = read_type(rdef, read_u32(rdef, at + 16)? as usize, 0)
+                .map_err(|err| format!("{}.{}: {}", name, variable_name, err))?;
+            variables.push(Member {
+                name: variable_name,
+                ty: ty,
+                offset: read_u32(rdef, at + 4)?,
+                size: read_u32(rdef, at + 8)?,
+            });
+        }
+        buffers.push(ConstantBuffer {
+            name: name,
+            size: size,
+            variables: variables,
+        });
+    }
+    Ok(buffers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NUMTHREADS: &[u8] = include_bytes!("../tests/fixtures/numthreads.dxbc");
+
+    #[test]
+    fn reads_the_chunks() {
+        let fourccs: Vec<[u8; 4]> = chunks(NUMTHREADS)
+            .unwrap()
+            .iter()
+            .map(|chunk| chunk.fourcc)
+            .collect();
+        assert_eq!(fourccs, [*b"RDEF", *b"ISGN", *b"OSGN", *b"SHEX"]);
+        assert_eq!(find_chunk(NUMTHREADS, b"STAT"), Ok(None));
+    }
+
+    #[test]
+    fn num_threads_of_a_compute_shader() {
+        assert_eq!(num_threads(NUMTHREADS), Ok(Some([8, 4, 2])));
+        assert_eq!(constant_buffers(NUMTHREADS), Ok(vec![]));
+    }
+
+    #[test]
+    fn num_threads_without_a_declaration() {
+        // the same container with the program cut after dcl_temps
+        let mut bytes = NUMTHREADS.to_vec();
+        let shex = bytes.windows(4).position(|w| w == b"SHEX").unwrap() + 8;
+        bytes[shex + 4..shex + 8].copy_from_slice(&9u32.to_le_bytes());
+        assert_eq!(num_threads(&bytes), Ok(None));
+    }
+
+    #[test]
+    fn broken_containers() {
+        assert_eq!(
+            num_threads(b"DXBC").unwrap_err(),
+            "invalid dxbc: missing container header"
+        );
+        let mut bytes = NUMTHREADS.to_vec();
+        bytes.truncate(200);
+        assert_eq!(
+            chunks(&bytes).err().unwrap(),
+            "invalid dxbc: container claims 288 bytes, got 200"
+        );
+        let mut bytes = NUMTHREADS.to_vec();
+        let shex = bytes.windows(4).position(|w| w == b"SHEX").unwrap() + 8;
+        bytes[shex + 8..shex + 12].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            num_threads(&bytes).unwrap_err(),
+            "invalid dxbc: zero-length instruction at 2"
+        );
+    }
+
+    #[test]
+    fn chunk_counts_beyond_the_container() {
+        let mut bytes = b"DXBC".to_vec();
+        bytes.extend_from_slice(&[0; 20]);
+        bytes.extend_from_slice(&32u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            chunks(&bytes).err().unwrap(),
+            "invalid dxbc: 4294967295 chunks do not fit in 32 bytes"
+        );
+        // one chunk offset fits, but points past the end
+        bytes[24..28].copy_from_slice(&36u32.to_le_bytes());
+        bytes[28..32].copy_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&1000u32.to_le_bytes());
+        assert_eq!(
+            chunks(&bytes).err().unwrap(),
+            "invalid dxbc: unexpected end of data at 1004"
+        );
+        let mut bytes = NUMTHREADS.to_vec();
+        bytes[28..32].copy_from_slice(&200u32.to_le_bytes());
+        assert_eq!(
+            chunks(&bytes).err().unwrap(),
+            "invalid dxbc: 200 chunks do not fit in 288 bytes"
+        );
+    }
+}
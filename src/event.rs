@@ -0,0 +1,398 @@
+//! typed window events, translated from win32 messages
+
+use crate::window::id::WindowId;
+use std::path::PathBuf;
+
+// win32 messages and constants, mirrored here so that translation does not need winapi
+pub const WM_DESTROY: u32 = 0x0002;
+pub const WM_MOVE: u32 = 0x0003;
+pub const WM_SIZE: u32 = 0x0005;
+pub const WM_SETFOCUS: u32 = 0x0007;
+pub const WM_KILLFOCUS: u32 = 0x0008;
+pub const WM_CLOSE: u32 = 0x0010;
+pub const WM_KEYDOWN: u32 = 0x0100;
+pub const WM_KEYUP: u32 = 0x0101;
+pub const WM_CHAR: u32 = 0x0102;
+pub const WM_SYSKEYDOWN: u32 = 0x0104;
+pub const WM_SYSKEYUP: u32 = 0x0105;
+pub const WM_MOUSEMOVE: u32 = 0x0200;
+pub const WM_LBUTTONDOWN: u32 = 0x0201;
+pub const WM_LBUTTONUP: u32 = 0x0202;
+pub const WM_RBUTTONDOWN: u32 = 0x0204;
+pub const WM_RBUTTONUP: u32 = 0x0205;
+pub const WM_MBUTTONDOWN: u32 = 0x0207;
+pub const WM_MBUTTONUP: u32 = 0x0208;
+pub const WM_MOUSEWHEEL: u32 = 0x020a;
+pub const WM_XBUTTONDOWN: u32 = 0x020b;
+pub const WM_XBUTTONUP: u32 = 0x020c;
+pub const WM_MOUSEHWHEEL: u32 = 0x020e;
+pub const WM_DPICHANGED: u32 = 0x02e0;
+
+pub const VK_RETURN: u32 = 0x0d;
+pub const WHEEL_DELTA: f32 = 120.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// new client size; 0x0 when minimized
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    /// new position of the client area's top left corner
+    Moved {
+        x: i32,
+        y: i32,
+    },
+    Focus(bool),
+    KeyDown {
+        /// windows virtual key code
+        key: u32,
+        /// hardware scancode, with 0xe000 added for extended keys
+        scancode: u32,
+        /// sent again because the key is held
+        repeat: bool,
+        alt: bool,
+    },
+    KeyUp {
+        key: u32,
+        scancode: u32,
+    },
+    Char(char),
+    MouseMove {
+        x: i32,
+        y: i32,
+    },
+    /// unaccelerated motion from raw input, in device units
+    RawMouseMotion {
+        dx: i32,
+        dy: i32,
+    },
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+        x: i32,
+        y: i32,
+    },
+    /// scrolled by `delta` notches, positive away from the user or to the right
+    Wheel {
+        delta: f32,
+        horizontal: bool,
+    },
+    DpiChanged {
+        dpi: u32,
+    },
+    /// one event per file when several are dropped at once
+    FileDropped(PathBuf),
+    CloseRequested,
+    /// the window is gone; whatever was drawn into it has to go too
+    Destroyed,
+}
+
+/// an event and the window it happened to
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowEvent {
+    pub window: WindowId,
+    pub event: Event,
+}
+
+impl WindowEvent {
+    pub fn new(window: WindowId, event: Event) -> Self {
+        WindowEvent {
+            window: window,
+            event: event,
+        }
+    }
+}
+
+impl Event {
+    /// alt+enter, which toggles fullscreen
+    pub fn is_alt_enter(&self) -> bool {
+        match *self {
+            Event::KeyDown {
+                key, repeat, alt, ..
+            } => key == VK_RETURN && alt && !repeat,
+            _ => false,
+        }
+    }
+}
+
+fn low_word(value: usize) -> u16 {
+    (value & 0xffff) as u16
+}
+
+fn high_word(value: usize) -> u16 {
+    ((value >> 16) & 0xffff) as u16
+}
+
+/// client coordinates packed into an lparam, which may be negative
+fn point(lparam: isize) -> (i32, i32) {
+    let lparam = lparam as usize;
+    (
+        low_word(lparam) as i16 as i32,
+        high_word(lparam) as i16 as i32,
+    )
+}
+
+fn scancode(lparam: isize) -> u32 {
+    let scancode = (lparam as usize >> 16) as u32 & 0xff;
+    if lparam & (1 << 24) != 0 {
+        scancode | 0xe000
+    } else {
+        scancode
+    }
+}
+
+/// turns win32 messages into events. the only state it keeps is the first
+/// half of a utf-16 surrogate pair, which `WM_CHAR` delivers in two messages.
+#[derive(Debug, Default)]
+pub struct Translator {
+    high_surrogate: Option<u16>,
+}
+
+impl Translator {
+    pub const fn new() -> Self {
+        Translator {
+            high_surrogate: None,
+        }
+    }
+
+    pub fn translate(&mut self, msg: u32, wparam: usize, lparam: isize) -> Option<Event> {
+        let mouse_button = |button, pressed| {
+            let (x, y) = point(lparam);
+            Some(Event::MouseButton {
+                button: button,
+                pressed: pressed,
+                x: x,
+                y: y,
+            })
+        };
+        let x_button = if high_word(wparam) == 2 {
+            MouseButton::X2
+        } else {
+            MouseButton::X1
+        };
+        match msg {
+            WM_SIZE => Some(Event::Resized {
+                width: low_word(lparam as usize) as u32,
+                height: high_word(lparam as usize) as u32,
+            }),
+            WM_MOVE => {
+                let (x, y) = point(lparam);
+                Some(Event::Moved { x: x, y: y })
+            }
+            WM_SETFOCUS => Some(Event::Focus(true)),
+            WM_KILLFOCUS => Some(Event::Focus(false)),
+            WM_CLOSE => Some(Event::CloseRequested),
+            WM_DESTROY => Some(Event::Destroyed),
+            WM_KEYDOWN | WM_SYSKEYDOWN => Some(Event::KeyDown {
+                key: wparam as u32,
+                scancode: scancode(lparam),
+                repeat: lparam & (1 << 30) != 0,
+                alt: lparam & (1 << 29) != 0,
+            }),
+            WM_KEYUP | WM_SYSKEYUP => Some(Event::KeyUp {
+                key: wparam as u32,
+                scancode: scancode(lparam),
+            }),
+            WM_CHAR => self.char(wparam as u16),
+            WM_MOUSEMOVE => {
+                let (x, y) = point(lparam);
+                Some(Event::MouseMove { x: x, y: y })
+            }
+            WM_LBUTTONDOWN => mouse_button(MouseButton::Left, true),
+            WM_LBUTTONUP => mouse_button(MouseButton::Left, false),
+            WM_RBUTTONDOWN => mouse_button(MouseButton::Right, true),
+            WM_RBUTTONUP => mouse_button(MouseButton::Right, false),
+            WM_MBUTTONDOWN => mouse_button(MouseButton::Middle, true),
+            WM_MBUTTONUP => mouse_button(MouseButton::Middle, false),
+            WM_XBUTTONDOWN => mouse_button(x_button, true),
+            WM_XBUTTONUP => mouse_button(x_button, false),
+            WM_MOUSEWHEEL | WM_MOUSEHWHEEL => Some(Event::Wheel {
+                delta: high_word(wparam) as i16 as f32 / WHEEL_DELTA,
+                horizontal: msg == WM_MOUSEHWHEEL,
+            }),
+            WM_DPICHANGED => Some(Event::DpiChanged {
+                dpi: high_word(wparam) as u32,
+            }),
+            _ => None,
+        }
+    }
+
+    fn char(&mut self, unit: u16) -> Option<Event> {
+        match unit {
+            0xd800..=0xdbff => {
+                self.high_surrogate = Some(unit);
+                None
+            }
+            0xdc00..=0xdfff => {
+                let high = self.high_surrogate.take()?;
+                std::char::decode_utf16([high, unit].iter().cloned())
+                    .next()
+                    .and_then(|c| c.ok())
+                    .map(Event::Char)
+            }
+            _ => {
+                self.high_surrogate = None;
+                std::char::from_u32(unit as u32).map(Event::Char)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an lparam of WM_KEYDOWN: repeat count 1 and the scancode, extended
+    // and previous state bits
+    fn key_lparam(scancode: u32, extended: bool, repeat: bool, alt: bool) -> isize {
+        (1 | scancode << 16 | (extended as u32) << 24 | (alt as u32) << 29 | (repeat as u32) << 30)
+            as isize
+    }
+
+    fn chars(units: &[u16]) -> Vec<Option<Event>> {
+        let mut translator = Translator::new();
+        units
+            .iter()
+            .map(|unit| translator.translate(WM_CHAR, *unit as usize, 0))
+            .collect()
+    }
+
+    #[test]
+    fn surrogate_pair() {
+        // U+1F600 in two messages
+        assert_eq!(
+            chars(&[0xd83d, 0xde00]),
+            vec![None, Some(Event::Char('\u{1f600}'))]
+        );
+        assert_eq!(chars(&[0x41]), vec![Some(Event::Char('A'))]);
+    }
+
+    #[test]
+    fn lone_surrogates_are_dropped() {
+        // a high surrogate followed by a plain character loses the surrogate
+        assert_eq!(
+            chars(&[0xd83d, 0x41, 0xde00]),
+            vec![None, Some(Event::Char('A')), None]
+        );
+        // a second high surrogate replaces the first
+        assert_eq!(
+            chars(&[0xd83d, 0xd83c, 0xdf89]),
+            vec![None, None, Some(Event::Char('\u{1f389}'))]
+        );
+        assert_eq!(chars(&[0xdc00]), vec![None]);
+    }
+
+    #[test]
+    fn key_repeat_and_alt() {
+        let mut translator = Translator::new();
+        let down = |lparam| Translator::new().translate(WM_KEYDOWN, 0x41, lparam);
+        assert_eq!(
+            down(key_lparam(0x1e, false, false, false)),
+            Some(Event::KeyDown {
+                key: 0x41,
+                scancode: 0x1e,
+                repeat: false,
+                alt: false
+            })
+        );
+        assert_eq!(
+            down(key_lparam(0x1e, false, true, false)),
+            Some(Event::KeyDown {
+                key: 0x41,
+                scancode: 0x1e,
+                repeat: true,
+                alt: false
+            })
+        );
+        // right ctrl is the extended left ctrl
+        assert_eq!(
+            translator.translate(WM_KEYUP, 0x11, key_lparam(0x1d, true, true, false)),
+            Some(Event::KeyUp {
+                key: 0x11,
+                scancode: 0xe01d
+            })
+        );
+
+        let mut alt_enter = |repeat| {
+            translator
+                .translate(
+                    WM_SYSKEYDOWN,
+                    VK_RETURN as usize,
+                    key_lparam(0x1c, false, repeat, true),
+                )
+                .unwrap()
+        };
+        assert!(alt_enter(false).is_alt_enter());
+        assert!(!alt_enter(true).is_alt_enter());
+    }
+
+    #[test]
+    fn mouse_buttons() {
+        let mut translator = Translator::new();
+        // x = -5, y = 300
+        let lparam = (300 << 16 | 0xfffb) as isize;
+        let cases = [
+            (WM_LBUTTONDOWN, 0, MouseButton::Left, true),
+            (WM_LBUTTONUP, 0, MouseButton::Left, false),
+            (WM_RBUTTONDOWN, 0, MouseButton::Right, true),
+            (WM_RBUTTONUP, 0, MouseButton::Right, false),
+            (WM_MBUTTONDOWN, 0, MouseButton::Middle, true),
+            (WM_MBUTTONUP, 0, MouseButton::Middle, false),
+            (WM_XBUTTONDOWN, 1 << 16, MouseButton::X1, true),
+            (WM_XBUTTONUP, 1 << 16, MouseButton::X1, false),
+            (WM_XBUTTONDOWN, 2 << 16, MouseButton::X2, true),
+            (WM_XBUTTONUP, 2 << 16, MouseButton::X2, false),
+        ];
+        for &(msg, wparam, button, pressed) in cases.iter() {
+            assert_eq!(
+                translator.translate(msg, wparam, lparam),
+                Some(Event::MouseButton {
+                    button: button,
+                    pressed: pressed,
+                    x: -5,
+                    y: 300
+                }),
+                "{:#x}",
+                msg
+            );
+        }
+    }
+
+    #[test]
+    fn wheel_sizes_and_unknown_messages() {
+        let mut translator = Translator::new();
+        // one notch towards the user
+        assert_eq!(
+            translator.translate(WM_MOUSEWHEEL, (-120i16 as u16 as usize) << 16, 0),
+            Some(Event::Wheel {
+                delta: -1.0,
+                horizontal: false
+            })
+        );
+        assert_eq!(
+            translator.translate(WM_MOUSEHWHEEL, 60 << 16, 0),
+            Some(Event::Wheel {
+                delta: 0.5,
+                horizontal: true
+            })
+        );
+        assert_eq!(
+            translator.translate(WM_SIZE, 0, (480 << 16 | 640) as isize),
+            Some(Event::Resized {
+                width: 640,
+                height: 480
+            })
+        );
+        assert_eq!(translator.translate(0x0400, 0, 0), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! keyboard and mouse state, fed from window events once per frame
+
+pub mod actions;
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::event::{Event, MouseButton};
+
+/// a key, by windows virtual key code, or a mouse button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Key(u32),
+    Mouse(MouseButton),
+}
+
+const KEY_NAMES: [(&str, u32); 18] = [
+    ("Backspace", 0x08),
+    ("Tab", 0x09),
+    ("Enter", 0x0d),
+    ("Shift", 0x10),
+    ("Ctrl", 0x11),
+    ("Alt", 0x12),
+    ("Escape", 0x1b),
+    ("Space", 0x20),
+    ("PageUp", 0x21),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("Insert", 0x2d),
+    ("Delete", 0x2e),
+];
+
+impl Button {
+    /// parses names such as `W`, `7`, `F5`, `Space` or `MouseLeft`, and
+    /// `Key0xba` for keys without a name
+    pub fn from_name(name: &str) -> Option<Button> {
+        let mouse = match name {
+            "MouseLeft" => Some(MouseButton::Left),
+            "MouseRight" => Some(MouseButton::Right),
+            "MouseMiddle" => Some(MouseButton::Middle),
+            "Mouse4" => Some(MouseButton::X1),
+            "Mouse5" => Some(MouseButton::X2),
+            _ => None,
+        };
+        if let Some(button) = mouse {
+            return Some(Button::Mouse(button));
+        }
+        let bytes = name.as_bytes();
+        if bytes.len() == 1 && (bytes[0].is_ascii_uppercase() || bytes[0].is_ascii_digit()) {
+            // letters and digits are their own virtual key codes
+            return Some(Button::Key(bytes[0] as u32));
+        }
+        if let Some(number) = name.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+            if (1..=24).contains(&number) {
+                return Some(Button::Key(0x70 + number - 1));
+            }
+        }
+        if let Some(hex) = name.strip_prefix("Key0x") {
+            if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return u32::from_str_radix(hex, 16).ok().map(Button::Key);
+            }
+        }
+        KEY_NAMES
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, code)| Button::Key(*code))
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Button::Mouse(MouseButton::Left) => write!(f, "MouseLeft"),
+            Button::Mouse(MouseButton::Right) => write!(f, "MouseRight"),
+            Button::Mouse(MouseButton::Middle) => write!(f, "MouseMiddle"),
+            Button::Mouse(MouseButton::X1) => write!(f, "Mouse4"),
+            Button::Mouse(MouseButton::X2) => write!(f, "Mouse5"),
+            Button::Key(code @ 0x30..=0x39) | Button::Key(code @ 0x41..=0x5a) => {
+                write!(f, "{}", code as u8 as char)
+            }
+            Button::Key(code @ 0x70..=0x87) => write!(f, "F{}", code - 0x70 + 1),
+            Button::Key(code) => match KEY_NAMES.iter().find(|(_, c)| *c == code) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "Key{:#04x}", code),
+            },
+        }
+    }
+}
+
+/// what the keyboard and mouse did, as of the current frame
+#[derive(Debug, Default)]
+pub struct InputState {
+    held: HashSet<Button>,
+    pressed: HashSet<Button>,
+    released: HashSet<Button>,
+    mouse_position: Option<(i32, i32)>,
+    mouse_delta: (i32, i32),
+    raw_mouse_delta: (i32, i32),
+    wheel: f32,
+    horizontal_wheel: f32,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// forgets the edges, deltas and wheel that the updates have seen. the
+    /// runner calls it after the updates of a frame, and only if there was
+    /// at least one, so that input of a frame without updates carries over.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.mouse_delta = (0, 0);
+        self.raw_mouse_delta = (0, 0);
+        self.wheel = 0.0;
+        self.horizontal_wheel = 0.0;
+    }
+
+    pub fn handle(&mut self, event: &Event) {
+        match *event {
+            // repeats of a held key are not presses
+            Event::KeyDown { key, .. } => self.press(Button::Key(key)),
+            Event::KeyUp { key, .. } => self.release(Button::Key(key)),
+            Event::MouseButton {
+                button, pressed, ..
+            } => {
+                if pressed {
+                    self.press(Button::Mouse(button));
+                } else {
+                    self.release(Button::Mouse(button));
+                }
+            }
+            Event::MouseMove { x, y } => {
+                if let Some((last_x, last_y)) = self.mouse_position {
+                    self.mouse_delta.0 += x - last_x;
+                    self.mouse_delta.1 += y - last_y;
+                }
+                self.mouse_position = Some((x, y));
+            }
+            Event::RawMouseMotion { dx, dy } => {
+                self.raw_mouse_delta.0 += dx;
+                self.raw_mouse_delta.1 += dy;
+            }
+            Event::Wheel { delta, horizontal } => {
+                if horizontal {
+                    self.horizontal_wheel += delta;
+                } else {
+                    self.wheel += delta;
+                }
+            }
+            // key up messages go to the window that has the focus then
+            Event::Focus(false) => {
+                let held: Vec<Button> = self.held.iter().cloned().collect();
+                for button in held {
+                    self.release(button);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn press(&mut self, button: Button) {
+        if self.held.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: Button) {
+        if self.held.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    pub fn is_held(&self, button: Button) -> bool {
+        self.held.contains(&button)
+    }
+
+    /// went down during this frame
+    pub fn was_pressed(&self, button: Button) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// went up during this frame
+    pub fn was_released(&self, button: Button) -> bool {
+        self.released.contains(&button)
+    }
+
+    /// last known cursor position in client coordinates
+    pub fn mouse_position(&self) -> Option<(i32, i32)> {
+        self.mouse_position
+    }
+
+    /// how far the cursor moved during this frame
+    pub fn mouse_delta(&self) -> (i32, i32) {
+        self.mouse_delta
+    }
+
+    /// unaccelerated motion during this frame, which keeps coming while the
+    /// cursor is held in place by relative capture
+    pub fn raw_mouse_delta(&self) -> (i32, i32) {
+        self.raw_mouse_delta
+    }
+
+    /// notches scrolled during this frame
+    pub fn wheel(&self) -> f32 {
+        self.wheel
+    }
+
+    pub fn horizontal_wheel(&self) -> f32 {
+        self.horizontal_wheel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_down(key: u32, repeat: bool) -> Event {
+        Event::KeyDown {
+            key: key,
+            scancode: 0,
+            repeat: repeat,
+            alt: false,
+        }
+    }
+
+    fn key_up(key: u32) -> Event {
+        Event::KeyUp {
+            key: key,
+            scancode: 0,
+        }
+    }
+
+    const W: Button = Button::Key(0x57);
+
+    #[test]
+    fn edges_last_until_begin_frame() {
+        let mut input = InputState::new();
+        input.handle(&key_down(0x57, false));
+        assert!(input.was_pressed(W) && input.is_held(W));
+        input.handle(&key_down(0x57, true));
+        input.begin_frame();
+        assert!(!input.was_pressed(W) && input.is_held(W));
+
+        // a repeat is no new press
+        input.handle(&key_down(0x57, true));
+        assert!(!input.was_pressed(W));
+
+        // pressed and released within one frame shows both edges
+        input.handle(&key_up(0x57));
+        input.handle(&key_down(0x57, false));
+        input.handle(&key_up(0x57));
+        assert!(input.was_pressed(W) && input.was_released(W) && !input.is_held(W));
+        input.begin_frame();
+        assert!(!input.was_released(W));
+    }
+
+    #[test]
+    fn mouse_motion_and_wheel_accumulate() {
+        let mut input = InputState::new();
+        input.handle(&Event::MouseMove { x: 10, y: 10 });
+        assert_eq!(input.mouse_delta(), (0, 0));
+        input.handle(&Event::MouseMove { x: 15, y: 7 });
+        input.handle(&Event::MouseMove { x: 16, y: 7 });
+        input.handle(&Event::RawMouseMotion { dx: 3, dy: -2 });
+        input.handle(&Event::RawMouseMotion { dx: 1, dy: 0 });
+        for _ in 0..2 {
+            input.handle(&Event::Wheel {
+                delta: 1.0,
+                horizontal: false,
+            });
+        }
+        assert_eq!(input.mouse_delta(), (6, -3));
+        assert_eq!(input.raw_mouse_delta(), (4, -2));
+        assert_eq!(input.wheel(), 2.0);
+        input.begin_frame();
+        assert_eq!(input.mouse_delta(), (0, 0));
+        assert_eq!(input.raw_mouse_delta(), (0, 0));
+        assert_eq!(input.wheel(), 0.0);
+        assert_eq!(input.mouse_position(), Some((16, 7)));
+    }
+
+    #[test]
+    fn losing_focus_releases_everything() {
+        let mut input = InputState::new();
+        input.handle(&key_down(0x11, false));
+        input.handle(&Event::MouseButton {
+            button: MouseButton::Left,
+            pressed: true,
+            x: 0,
+            y: 0,
+        });
+        input.handle(&Event::Focus(false));
+        let ctrl = Button::Key(0x11);
+        let left = Button::Mouse(MouseButton::Left);
+        assert!(!input.is_held(ctrl) && input.was_released(ctrl));
+        assert!(!input.is_held(left) && input.was_released(left));
+    }
+
+    #[test]
+    fn button_names() {
+        let names = [
+            ("W", Button::Key(0x57)),
+            ("7", Button::Key(0x37)),
+            ("F5", Button::Key(0x74)),
+            ("F24", Button::Key(0x87)),
+            ("Escape", Button::Key(0x1b)),
+            ("MouseLeft", Button::Mouse(MouseButton::Left)),
+            ("Mouse5", Button::Mouse(MouseButton::X2)),
+            ("Key0xba", Button::Key(0xba)),
+            ("Key0x07", Button::Key(0x07)),
+        ];
+        for (name, button) in names.iter() {
+            assert_eq!(Button::from_name(name), Some(*button));
+            assert_eq!(button.to_string(), *name);
+        }
+        for name in ["w", "F0", "F25", "Mouse6", "", "Key0x", "Key0x+1", "Keyba"].iter() {
+            assert_eq!(Button::from_name(name), None, "{:?}", name);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! named actions and axes bound to buttons, loaded from a config file like
+//!
+//! ```text
+//! # comments start with '#'
+//! action move_forward = W, Up
+//! action save = Ctrl+S
+//! axis move_x = D/A, Right/Left
+//! axis look_x = MouseX
+//! ```
+//!
+//! an action is bound to one or more chords, each of which is a set of
+//! buttons joined with `+`. an axis is bound to `positive/negative` button
+//! pairs or to `MouseX`, `MouseY`, `RawMouseX`, `RawMouseY` or `Wheel`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::{Button, InputState};
+
+/// buttons that must be held together
+pub type Chord = Vec<Button>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisBinding {
+    /// 1 while `positive` is held, -1 while `negative` is
+    Buttons {
+        positive: Button,
+        negative: Button,
+    },
+    MouseX,
+    MouseY,
+    RawMouseX,
+    RawMouseY,
+    Wheel,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionMap {
+    actions: BTreeMap<String, Vec<Chord>>,
+    axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+fn parse_button(name: &str) -> Result<Button, String> {
+    Button::from_name(name).ok_or_else(|| format!("unknown button '{}'", name))
+}
+
+fn parse_chord(text: &str) -> Result<Chord, String> {
+    text.split('+')
+        .map(|name| parse_button(name.trim()))
+        .collect()
+}
+
+fn parse_axis(text: &str) -> Result<AxisBinding, String> {
+    match text {
+        "MouseX" => Ok(AxisBinding::MouseX),
+        "MouseY" => Ok(AxisBinding::MouseY),
+        "RawMouseX" => Ok(AxisBinding::RawMouseX),
+        "RawMouseY" => Ok(AxisBinding::RawMouseY),
+        "Wheel" => Ok(AxisBinding::Wheel),
+        _ => {
+            let mut names = text.split('/');
+            match (names.next(), names.next(), names.next()) {
+                (Some(positive), Some(negative), None) => Ok(AxisBinding::Buttons {
+                    positive: parse_button(positive.trim())?,
+                    negative: parse_button(negative.trim())?,
+                }),
+                _ => Err(format!("invalid axis binding '{}'", text)),
+            }
+        }
+    }
+}
+
+fn parse_line(map: &mut ActionMap, line: &str) -> Result<(), String> {
+    let (head, bindings) = match line.find('=') {
+        Some(at) => (&line[..at], &line[at + 1..]),
+        None => return Err("expected '='".to_string()),
+    };
+    let mut head = head.split_whitespace();
+    let (kind, name) = match (head.next(), head.next(), head.next()) {
+        (Some(kind), Some(name), None) => (kind, name),
+        _ => return Err("expected 'action <name>' or 'axis <name>'".to_string()),
+    };
+    let bindings = bindings.split(',').map(str::trim);
+    match kind {
+        "action" => {
+            let chords = bindings.map(parse_chord).collect::<Result<_, _>>()?;
+            map.actions.insert(name.to_string(), chords);
+        }
+        "axis" => {
+            let axes = bindings.map(parse_axis).collect::<Result<_, _>>()?;
+            map.axes.insert(name.to_string(), axes);
+        }
+        _ => return Err(format!("unknown binding kind '{}'", kind)),
+    }
+    Ok(())
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(text: &str) -> Result<ActionMap, String> {
+        let mut map = ActionMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(at) => &line[..at],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            parse_line(&mut map, line).map_err(|err| format!("line {}: {}", number + 1, err))?;
+        }
+        Ok(map)
+    }
+
+    pub fn load(path: &str) -> Result<ActionMap, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed: read {}: {}", path, err))?;
+        ActionMap::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    /// replaces the chords of `action`
+    pub fn bind(&mut self, action: &str, chords: Vec<Chord>) {
+        self.actions.insert(action.to_string(), chords);
+    }
+
+    pub fn bind_axis(&mut self, axis: &str, bindings: Vec<AxisBinding>) {
+        self.axes.insert(axis.to_string(), bindings);
+    }
+
+    pub fn chords(&self, action: &str) -> &[Chord] {
+        self.actions.get(action).map_or(&[], |chords| &chords[..])
+    }
+
+    /// true while every button of one of the chords is held
+    pub fn is_active(&self, action: &str, input: &InputState) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| !chord.is_empty() && chord.iter().all(|b| input.is_held(*b)))
+    }
+
+    /// true in the frame a chord of `action` became complete
+    pub fn was_triggered(&self, action: &str, input: &InputState) -> bool {
+        self.chords(action).iter().any(|chord| {
+            !chord.is_empty()
+                && chord.iter().all(|b| input.is_held(*b))
+                && chord.iter().any(|b| input.was_pressed(*b))
+        })
+    }
+
+    /// sum of the axis bindings: -1 to 1 for buttons, pixels for the mouse
+    /// and notches for the wheel
+    pub fn axis(&self, axis: &str, input: &InputState) -> f32 {
+        let bindings = match self.axes.get(axis) {
+            Some(bindings) => bindings,
+            None => return 0.0,
+        };
+        bindings
+            .iter()
+            .map(|binding| match *binding {
+                AxisBinding::Buttons { positive, negative } => {
+                    let value = |button| if input.is_held(button) { 1.0 } else { 0.0 };
+                    value(positive) - value(negative)
+                }
+                AxisBinding::MouseX => input.mouse_delta().0 as f32,
+                AxisBinding::MouseY => input.mouse_delta().1 as f32,
+                AxisBinding::RawMouseX => input.raw_mouse_delta().0 as f32,
+                AxisBinding::RawMouseY => input.raw_mouse_delta().1 as f32,
+                AxisBinding::Wheel => input.wheel(),
+            })
+            .sum()
+    }
+}
+
+/// writes the map in the format `parse` reads, e.g. to save rebindings
+impl fmt::Display for ActionMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, chords) in self.actions.iter() {
+            let chords: Vec<String> = chords
+                .iter()
+                .map(|chord| {
+                    let names: Vec<String> = chord.iter().map(|b| b.to_string()).collect();
+                    names.join("+")
+                })
+                .collect();
+            writeln!(f, "action {} = {}", name, chords.join(", "))?;
+        }
+        for (name, bindings) in self.axes.iter() {
+            let bindings: Vec<String> = bindings
+                .iter()
+                .map(|binding| match *binding {
+                    AxisBinding::Buttons { positive, negative } => {
+                        format!("{}/{}", positive, negative)
+                    }
+                    AxisBinding::MouseX => "MouseX".to_string(),
+                    AxisBinding::MouseY => "MouseY".to_string(),
+                    AxisBinding::RawMouseX => "RawMouseX".to_string(),
+                    AxisBinding::RawMouseY => "RawMouseY".to_string(),
+                    AxisBinding::Wheel => "Wheel".to_string(),
+                })
+                .collect();
+            writeln!(f, "axis {} = {}", name, bindings.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, MouseButton};
+
+    fn key_down(key: u32) -> Event {
+        Event::KeyDown {
+            key: key,
+            scancode: 0,
+            repeat: false,
+            alt: false,
+        }
+    }
+
+    #[test]
+    fn parses_the_viewer_bindings() {
+        let map = ActionMap::parse(include_str!("../../resource/actions.cfg")).unwrap();
+        assert_eq!(map.chords("quit"), &[vec![Button::Key(0x1b)]]);
+        assert_eq!(map.chords("missing"), &[] as &[Chord]);
+    }
+
+    #[test]
+    fn parses_every_kind_of_binding() {
+        let text = "
+            # comment
+            action forward = W, Up   # trailing comment
+            action save = Ctrl+S
+            action fire = MouseLeft
+            action crouch = Key0xba
+            axis move_x = D/A, Right/Left
+            axis look = MouseX, RawMouseY, Wheel
+        ";
+        let map = ActionMap::parse(text).unwrap();
+        assert_eq!(
+            map.chords("save"),
+            &[vec![Button::Key(0x11), Button::Key(0x53)]]
+        );
+        assert_eq!(map.chords("forward").len(), 2);
+        assert_eq!(map.chords("crouch"), &[vec![Button::Key(0xba)]]);
+        assert_eq!(map.axes["look"].len(), 3);
+        // written out and read back, it is the same map
+        assert_eq!(ActionMap::parse(&map.to_string()).unwrap(), map);
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let errors = [
+            ("action quit Escape", "line 1: expected '='"),
+            (
+                "\naction = W",
+                "line 2: expected 'action <name>' or 'axis <name>'",
+            ),
+            (
+                "action a b = W",
+                "line 1: expected 'action <name>' or 'axis <name>'",
+            ),
+            ("bind quit = W", "line 1: unknown binding kind 'bind'"),
+            ("# ok\n\naction quit = Esc", "line 3: unknown button 'Esc'"),
+            ("action save = Ctrl+", "line 1: unknown button ''"),
+            ("axis x = D/A/S", "line 1: invalid axis binding 'D/A/S'"),
+            ("axis x = MouseZ", "line 1: invalid axis binding 'MouseZ'"),
+            ("axis x = D/Nope", "line 1: unknown button 'Nope'"),
+        ];
+        for (text, error) in errors.iter() {
+            assert_eq!(ActionMap::parse(text), Err(error.to_string()), "{:?}", text);
+        }
+        assert!(ActionMap::load("resource/missing.cfg")
+            .unwrap_err()
+            .starts_with("failed: read resource/missing.cfg"));
+    }
+
+    #[test]
+    fn actions_and_axes_follow_the_input() {
+        let map = ActionMap::parse(
+            "action save = Ctrl+S\naction fire = MouseLeft\naxis move_x = Right/Left",
+        )
+        .unwrap();
+        let mut input = InputState::new();
+        input.handle(&key_down(0x11));
+        input.begin_frame();
+        assert!(!map.was_triggered("save", &input));
+        // the chord completes in this frame
+        input.handle(&key_down(0x53));
+        assert!(map.was_triggered("save", &input) && map.is_active("save", &input));
+        input.begin_frame();
+        assert!(!map.was_triggered("save", &input) && map.is_active("save", &input));
+
+        input.handle(&Event::MouseButton {
+            button: MouseButton::Left,
+            pressed: true,
+            x: 0,
+            y: 0,
+        });
+        assert!(map.was_triggered("fire", &input));
+
+        input.handle(&key_down(0x27));
+        assert_eq!(map.axis("move_x", &input), 1.0);
+        input.handle(&key_down(0x25));
+        assert_eq!(map.axis("move_x", &input), 0.0);
+        assert_eq!(map.axis("missing", &input), 0.0);
+    }
+}
@@ -0,0 +1,37 @@
+// com objects are passed around as raw pointers, as the d3d12 api does
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+#![allow(clippy::redundant_field_names)]
+
+#[cfg(windows)]
+extern crate widestring;
+extern crate winapi;
+
+pub mod adapter;
+pub mod app;
+pub mod caps;
+pub mod cbuffer;
+pub mod clock;
+pub mod color;
+pub mod compute;
+#[cfg(windows)]
+pub mod direct3d;
+pub mod display;
+pub mod dxbc;
+pub mod event;
+pub mod input;
+pub mod list_pool;
+pub mod math;
+#[cfg(windows)]
+pub mod msg;
+#[cfg(windows)]
+pub mod options;
+pub mod pipeline;
+pub mod present;
+pub mod replay;
+pub mod resize;
+pub mod shaders;
+pub mod surface;
+pub mod sync;
+pub mod timestep;
+pub mod util;
+pub mod window;
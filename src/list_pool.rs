@@ -0,0 +1,310 @@
+//! command lists that worker threads check out, record in parallel, and hand
+//! back for a single submission. what a list is comes from a `ListFactory`;
+//! `direct3d::list_pool` has the d3d12 one.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use crate::sync::pool::FencedPool;
+
+/// creates and resets command lists for a `CommandListPool`
+pub trait ListFactory {
+    type List: Send;
+    fn create(&mut self) -> Result<Self::List, String>;
+    /// prepares a list whose previous submission has finished for recording
+    fn reset(&mut self, list: &mut Self::List) -> Result<(), String>;
+    /// ends recording on a list that goes back to the pool unsubmitted
+    fn close(&mut self, list: &mut Self::List);
+}
+
+/// a list checked out of the pool, tagged with its place in the submission
+pub struct Recording<L> {
+    pub order: u32,
+    pub list: L,
+}
+
+struct PoolState<F: ListFactory> {
+    factory: F,
+    lists: FencedPool<F::List>,
+    created: usize,
+}
+
+/// lists are reused once the fence value they were submitted with has
+/// completed
+pub struct CommandListPool<F: ListFactory> {
+    state: Mutex<PoolState<F>>,
+}
+
+impl<F: ListFactory> CommandListPool<F> {
+    pub fn new(factory: F) -> Self {
+        CommandListPool {
+            state: Mutex::new(PoolState {
+                factory: factory,
+                lists: FencedPool::new(),
+                created: 0,
+            }),
+        }
+    }
+
+    /// takes a list ready for recording; `completed` is the fence value the
+    /// gpu has finished
+    pub fn checkout(&self, order: u32, completed: u64) -> Result<Recording<F::List>, String> {
+        let mut state = self.state.lock().unwrap();
+        let list = match state.lists.acquire(completed) {
+            Some(mut list) => {
+                if let Err(e) = state.factory.reset(&mut list) {
+                    // the list stays with the pool, to be reset again next time
+                    state.lists.release(list, completed);
+                    state.lists.reclaim(completed);
+                    return Err(e);
+                }
+                list
+            }
+            None => {
+                let list = state.factory.create()?;
+                state.created += 1;
+                list
+            }
+        };
+        Ok(Recording {
+            order: order,
+            list: list,
+        })
+    }
+
+    /// hands lists back after they were submitted with `fence_value`
+    pub fn recycle(&self, lists: Vec<F::List>, fence_value: u64) {
+        let mut state = self.state.lock().unwrap();
+        for list in lists {
+            state.lists.release(list, fence_value);
+        }
+    }
+
+    /// hands back lists that were checked out but never submitted; they can
+    /// be reused right away
+    pub fn abandon(&self, lists: Vec<F::List>, completed: u64) {
+        let mut state = self.state.lock().unwrap();
+        for mut list in lists {
+            state.factory.close(&mut list);
+            state.lists.release(list, completed);
+        }
+        state.lists.reclaim(completed);
+    }
+
+    /// number of lists ever created by this pool
+    pub fn created(&self) -> usize {
+        self.state.lock().unwrap().created
+    }
+
+    pub fn idle(&self) -> usize {
+        self.state.lock().unwrap().lists.free_count()
+    }
+}
+
+/// puts finished recordings into submission order, independent of the order
+/// in which the recording threads finished
+pub fn submission_order<L>(mut recordings: Vec<Recording<L>>) -> Vec<L> {
+    recordings.sort_by_key(|recording| recording.order);
+    recordings
+        .into_iter()
+        .map(|recording| recording.list)
+        .collect()
+}
+
+/// records every job on its own thread, each into its own list from `pool`,
+/// and returns the lists in job order. if a checkout fails or a job panics,
+/// every list goes back to the pool before the error is returned.
+pub fn record_parallel<F, J>(
+    pool: &CommandListPool<F>,
+    completed: u64,
+    jobs: Vec<J>,
+) -> Result<Vec<F::List>, String>
+where
+    F: ListFactory + Send,
+    J: FnOnce(&mut F::List) + Send,
+{
+    let mut recordings = vec![];
+    for order in 0..jobs.len() {
+        match pool.checkout(order as u32, completed) {
+            Ok(recording) => recordings.push(recording),
+            Err(e) => {
+                pool.abandon(submission_order(recordings), completed);
+                return Err(e);
+            }
+        }
+    }
+    // a panic is caught on its thread so that the list comes back with it
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = recordings
+            .into_iter()
+            .zip(jobs)
+            .map(|(mut recording, job)| {
+                scope.spawn(move || {
+                    let result = catch_unwind(AssertUnwindSafe(|| job(&mut recording.list)));
+                    (recording, result.is_ok())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    let panicked = results.iter().any(|(_, ok)| !ok);
+    let lists = submission_order(
+        results
+            .into_iter()
+            .map(|(recording, _)| recording)
+            .collect(),
+    );
+    if panicked {
+        pool.abandon(lists, completed);
+        return Err("failed: command recording thread panicked".to_string());
+    }
+    Ok(lists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct FakeList {
+        id: usize,
+        open: bool,
+        commands: Vec<u32>,
+    }
+
+    /// makes lists the way d3d12 does: created open, and only a closed list
+    /// can be reset
+    #[derive(Default)]
+    struct FakeFactory {
+        next_id: usize,
+        fail_create_at: Option<usize>,
+        resets: usize,
+    }
+
+    impl ListFactory for FakeFactory {
+        type List = FakeList;
+
+        fn create(&mut self) -> Result<FakeList, String> {
+            if self.fail_create_at == Some(self.next_id) {
+                return Err("failed: create list".to_string());
+            }
+            self.next_id += 1;
+            Ok(FakeList {
+                id: self.next_id - 1,
+                open: true,
+                commands: vec![],
+            })
+        }
+
+        fn reset(&mut self, list: &mut FakeList) -> Result<(), String> {
+            if list.open {
+                return Err("failed: reset an open list".to_string());
+            }
+            self.resets += 1;
+            list.open = true;
+            list.commands.clear();
+            Ok(())
+        }
+
+        fn close(&mut self, list: &mut FakeList) {
+            list.open = false;
+        }
+    }
+
+    // what execute_recorded does before the lists go back to the pool
+    fn submit(pool: &CommandListPool<FakeFactory>, mut lists: Vec<FakeList>, fence_value: u64) {
+        for list in lists.iter_mut() {
+            list.open = false;
+        }
+        pool.recycle(lists, fence_value);
+    }
+
+    fn ids(lists: &[FakeList]) -> Vec<usize> {
+        let mut ids: Vec<usize> = lists.iter().map(|list| list.id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    type Job = Box<dyn FnOnce(&mut FakeList) + Send>;
+
+    fn jobs(count: u32) -> Vec<impl FnOnce(&mut FakeList) + Send> {
+        (0..count)
+            .map(|i| move |list: &mut FakeList| list.commands.push(i))
+            .collect()
+    }
+
+    #[test]
+    fn lists_come_back_in_job_order() {
+        let pool = CommandListPool::new(FakeFactory::default());
+        let lists = record_parallel(&pool, 0, jobs(8)).unwrap();
+        let commands: Vec<Vec<u32>> = lists.iter().map(|list| list.commands.clone()).collect();
+        assert_eq!(commands, (0..8).map(|i| vec![i]).collect::<Vec<_>>());
+        assert_eq!(pool.created(), 8);
+    }
+
+    #[test]
+    fn reuses_lists_once_their_fence_completes() {
+        let pool = CommandListPool::new(FakeFactory::default());
+        let first = record_parallel(&pool, 0, jobs(3)).unwrap();
+        submit(&pool, first, 1);
+        assert_eq!(pool.idle(), 0);
+
+        // still in flight, so new lists are made
+        let second = record_parallel(&pool, 0, jobs(2)).unwrap();
+        assert_eq!(ids(&second), vec![3, 4]);
+        submit(&pool, second, 2);
+
+        let third = record_parallel(&pool, 1, jobs(3)).unwrap();
+        assert_eq!(ids(&third), vec![0, 1, 2]);
+        assert!(third.iter().all(|list| list.open));
+        assert_eq!(pool.created(), 5);
+        assert_eq!(pool.state.lock().unwrap().factory.resets, 3);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn failed_checkout_returns_the_lists() {
+        let pool = CommandListPool::new(FakeFactory {
+            fail_create_at: Some(2),
+            ..FakeFactory::default()
+        });
+        assert!(record_parallel(&pool, 0, jobs(4)).is_err());
+        assert_eq!(pool.created(), 2);
+        assert_eq!(pool.idle(), 2);
+
+        // the returned lists were closed, so they reset without error
+        let lists = record_parallel(&pool, 0, jobs(2)).unwrap();
+        assert_eq!(ids(&lists), vec![0, 1]);
+        assert_eq!(pool.created(), 2);
+    }
+
+    #[test]
+    fn panicking_job_returns_the_lists() {
+        let pool = CommandListPool::new(FakeFactory::default());
+        let jobs: Vec<Job> = vec![
+            Box::new(|list| list.commands.push(0)),
+            Box::new(|_| panic!("recording failed")),
+            Box::new(|list| list.commands.push(2)),
+        ];
+        assert!(record_parallel(&pool, 0, jobs).is_err());
+        assert_eq!(pool.idle(), 3);
+
+        let lists = record_parallel(&pool, 0, self::jobs(3)).unwrap();
+        assert_eq!(ids(&lists), vec![0, 1, 2]);
+        assert!(lists.iter().all(|list| list.commands.len() == 1));
+        assert_eq!(pool.created(), 3);
+    }
+
+    #[test]
+    fn failed_reset_keeps_the_list() {
+        let pool = CommandListPool::new(FakeFactory::default());
+        let lists = record_parallel(&pool, 0, jobs(1)).unwrap();
+        // handed back still open, which the reset refuses
+        pool.recycle(lists, 1);
+        assert!(pool.checkout(0, 1).is_err());
+        assert_eq!(pool.idle(), 1);
+    }
+}
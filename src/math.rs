@@ -1,15 +1,18 @@
-use std::fmt::Debug;
+//! vectors, matrices and quaternions for d3d's conventions: left-handed
+//! coordinates and row vectors that are multiplied on the left, `v * m`,
+//! as in DirectXMath. every type is `#[repr(C)]` so it can be copied into
+//! an upload buffer as it is.
 
-#[repr(C)]
-#[derive(Debug, Clone)]
-pub struct Vec3<T: Debug + Clone> {
-    pub x: T,
-    pub y: T,
-    pub z: T,
-}
+pub mod approx;
+pub mod half;
+pub mod matrix;
+pub mod projection;
+pub mod quantize;
+pub mod quat;
+pub mod vector;
 
-impl<T: Debug + Clone> Vec3<T> {
-    pub fn new(x: T, y: T, z: T) -> Self {
-        Vec3 { x: x, y: y, z: z }
-    }
-}
+pub use self::approx::ApproxEq;
+pub use self::half::Half;
+pub use self::matrix::{Mat3, Mat4};
+pub use self::quat::Quat;
+pub use self::vector::{Vec2, Vec3, Vec4};
@@ -0,0 +1,66 @@
+/// the tolerance of `ApproxEq::approx_eq`, relative to the larger value
+/// once that is above 1
+pub const EPSILON: f32 = 1e-5;
+
+/// equality up to rounding, for the results of float math
+pub trait ApproxEq {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool;
+
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.approx_eq_eps(other, EPSILON)
+    }
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+        let scale = self.abs().max(other.abs()).max(1.0);
+        (self - other).abs() <= epsilon * scale
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for [T] {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| a.approx_eq_eps(b, epsilon))
+    }
+}
+
+/// asserts that two values are equal up to `epsilon`, 1e-4 if not given,
+/// which leaves room for a few operations' rounding
+#[cfg(test)]
+macro_rules! assert_approx_eq {
+    ($a:expr, $b:expr) => {
+        assert_approx_eq!($a, $b, 1e-4)
+    };
+    ($a:expr, $b:expr, $epsilon:expr) => {{
+        let (a, b) = (&$a, &$b);
+        assert!(
+            $crate::math::ApproxEq::approx_eq_eps(a, b, $epsilon),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }};
+}
+
+#[cfg(test)]
+pub(crate) use assert_approx_eq;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerance_is_relative_above_one() {
+        assert!(1.0f32.approx_eq(&1.000_009));
+        assert!(!1.0f32.approx_eq(&1.000_02));
+        assert!(1000.0f32.approx_eq(&1000.009));
+        assert!(!0.001f32.approx_eq(&0.001_02));
+        assert!([1.0f32, 2.0][..].approx_eq(&[1.0, 2.000_001][..]));
+        assert!(![1.0f32][..].approx_eq(&[1.0, 2.0][..]));
+        assert!(!f32::NAN.approx_eq(&f32::NAN));
+    }
+}
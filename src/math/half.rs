@@ -0,0 +1,145 @@
+//! ieee 754 half precision floats, as `DXGI_FORMAT_R16_FLOAT` stores them.
+//! conversions round to nearest even, keep denormals and nan payloads, and
+//! overflow to infinity, as the gpu does.
+
+/// the bits of a half float
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Half(pub u16);
+
+impl Half {
+    pub const ZERO: Half = Half(0);
+    pub const ONE: Half = Half(0x3c00);
+    /// the largest finite half, 65504
+    pub const MAX: Half = Half(0x7bff);
+    /// the smallest positive normal half, 2^-14
+    pub const MIN_POSITIVE: Half = Half(0x0400);
+    pub const INFINITY: Half = Half(0x7c00);
+    pub const NEG_INFINITY: Half = Half(0xfc00);
+    pub const NAN: Half = Half(0x7e00);
+
+    pub fn from_f32(value: f32) -> Half {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = bits & 0x7f_ffff;
+        if exponent == 0xff {
+            // a nan stays quiet and keeps the top of its payload
+            let nan = if mantissa != 0 {
+                0x200 | (mantissa >> 13) as u16
+            } else {
+                0
+            };
+            return Half(sign | 0x7c00 | nan);
+        }
+        let exponent = exponent - 127 + 15;
+        if exponent >= 0x1f {
+            return Half(sign | 0x7c00);
+        }
+        // the mantissa with its implicit bit, and how far it moves right
+        let (mantissa, shift, base) = if exponent > 0 {
+            (mantissa, 13, (exponent as u32) << 10)
+        } else if exponent >= -10 {
+            (mantissa | 0x80_0000, (14 - exponent) as u32, 0)
+        } else {
+            return Half(sign);
+        };
+        let half = base | (mantissa >> shift);
+        let rest = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        // a carry out of the mantissa correctly bumps the exponent, up to
+        // infinity
+        let round = rest > halfway || (rest == halfway && half & 1 == 1);
+        Half(sign | (half + round as u32) as u16)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exponent = ((self.0 >> 10) & 0x1f) as u32;
+        let mantissa = (self.0 & 0x3ff) as u32;
+        let bits = match exponent {
+            0 if mantissa == 0 => sign,
+            0 => {
+                // a denormal is normal in f32
+                let shift = mantissa.leading_zeros() - 21;
+                sign | ((113 - shift) << 23) | (((mantissa << shift) & 0x3ff) << 13)
+            }
+            0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+            _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+        };
+        f32::from_bits(bits)
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.0 & 0x7c00 == 0x7c00 && self.0 & 0x3ff != 0
+    }
+}
+
+impl From<f32> for Half {
+    fn from(value: f32) -> Half {
+        Half::from_f32(value)
+    }
+}
+
+impl From<Half> for f32 {
+    fn from(value: Half) -> f32 {
+        value.to_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_half_round_trips() {
+        for bits in 0..=u16::MAX {
+            let half = Half(bits);
+            let value = half.to_f32();
+            if half.is_nan() {
+                // quiet, with the sign and the payload below the quiet bit
+                let back = Half::from_f32(value);
+                assert!(value.is_nan());
+                assert!(back.is_nan());
+                assert_eq!(back.0 & 0x81ff, bits & 0x81ff, "{:#06x}", bits);
+                assert_eq!(back.0 & 0x200, 0x200);
+            } else {
+                assert_eq!(Half::from_f32(value), half, "{:#06x} {}", bits, value);
+            }
+        }
+    }
+
+    #[test]
+    fn rounds_to_nearest_even() {
+        // 2048 and 2050 are neighbours, so 2049 is halfway
+        assert_eq!(Half::from_f32(2049.0).to_f32(), 2048.0);
+        assert_eq!(Half::from_f32(2051.0).to_f32(), 2052.0);
+        assert_eq!(Half::from_f32(2049.001).to_f32(), 2050.0);
+        // halfway between the two smallest denormals
+        assert_eq!(Half::from_f32(1.5 * 2f32.powi(-24)), Half(2));
+        assert_eq!(Half::from_f32(2.5 * 2f32.powi(-24)), Half(2));
+        // rounding up out of the denormals into the normals
+        assert_eq!(Half::from_f32(2f32.powi(-14) * 0.99995), Half::MIN_POSITIVE);
+    }
+
+    #[test]
+    fn edges() {
+        assert_eq!(Half::from_f32(65504.0), Half::MAX);
+        assert_eq!(Half::from_f32(65519.99), Half::MAX);
+        assert_eq!(Half::from_f32(65520.0), Half::INFINITY);
+        assert_eq!(Half::from_f32(1e10), Half::INFINITY);
+        assert_eq!(Half::from_f32(f32::NEG_INFINITY), Half::NEG_INFINITY);
+        assert_eq!(Half::from_f32(-0.0), Half(0x8000));
+        assert_eq!(Half::from_f32(2f32.powi(-14)), Half::MIN_POSITIVE);
+        assert_eq!(Half::from_f32(2f32.powi(-24)), Half(1));
+        assert_eq!(Half::from_f32(2f32.powi(-25)), Half::ZERO);
+        assert_eq!(Half::from_f32(2f32.powi(-25) * 1.0001), Half(1));
+        assert_eq!(Half::from_f32(f32::MIN_POSITIVE), Half::ZERO);
+        assert_eq!(Half::from_f32(-f32::MIN_POSITIVE), Half(0x8000));
+        assert!(Half::from_f32(f32::NAN).is_nan());
+        assert!(!Half::INFINITY.is_nan());
+        assert!(Half::NAN.to_f32().is_nan());
+        assert_eq!(Half::ONE.to_f32(), 1.0);
+        assert_eq!(f32::from(Half::from(-0.5f32)), -0.5);
+    }
+}
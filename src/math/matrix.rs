@@ -0,0 +1,478 @@
+//! matrices are stored row by row and transform row vectors, so `a * b`
+//! applies `a` first. hlsl packs constant buffer matrices by column unless
+//! they are declared `row_major`; upload the `transpose` for those.
+
+use std::ops::Mul;
+
+use super::approx::ApproxEq;
+use super::quat::Quat;
+use super::vector::{Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    /// `m[row][column]`
+    pub m: [[f32; 3]; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    /// `m[row][column]`
+    pub m: [[f32; 4]; 4],
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3 {
+        m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    pub fn from_rows(x: Vec3<f32>, y: Vec3<f32>, z: Vec3<f32>) -> Mat3 {
+        Mat3 {
+            m: [[x.x, x.y, x.z], [y.x, y.y, y.z], [z.x, z.y, z.z]],
+        }
+    }
+
+    pub fn row(&self, i: usize) -> Vec3<f32> {
+        let row = self.m[i];
+        Vec3::new(row[0], row[1], row[2])
+    }
+
+    pub fn column(&self, i: usize) -> Vec3<f32> {
+        Vec3::new(self.m[0][i], self.m[1][i], self.m[2][i])
+    }
+
+    pub fn scaling(scale: Vec3<f32>) -> Mat3 {
+        Mat3 {
+            m: [
+                [scale.x, 0.0, 0.0],
+                [0.0, scale.y, 0.0],
+                [0.0, 0.0, scale.z],
+            ],
+        }
+    }
+
+    pub fn from_quat(q: Quat) -> Mat3 {
+        let (x, y, z, w) = (q.x, q.y, q.z, q.w);
+        Mat3 {
+            m: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y + w * z),
+                    2.0 * (x * z - w * y),
+                ],
+                [
+                    2.0 * (x * y - w * z),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z + w * x),
+                ],
+                [
+                    2.0 * (x * z + w * y),
+                    2.0 * (y * z - w * x),
+                    1.0 - 2.0 * (x * x + y * y),
+                ],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Mat3 {
+        Mat3::from_rows(self.column(0), self.column(1), self.column(2))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.row(0).dot(self.row(1).cross(self.row(2)))
+    }
+
+    /// none if the matrix is singular
+    pub fn inverse(&self) -> Option<Mat3> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (a, b, c) = (self.row(0), self.row(1), self.row(2));
+        // the cross products are the columns of the inverse
+        let inverse = Mat3::from_rows(b.cross(c), c.cross(a), a.cross(b)).transpose();
+        Some(inverse * (1.0 / det))
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+    fn mul(self, rhs: Mat3) -> Mat3 {
+        Mat3::from_rows(self.row(0) * rhs, self.row(1) * rhs, self.row(2) * rhs)
+    }
+}
+
+impl Mul<f32> for Mat3 {
+    type Output = Mat3;
+    fn mul(self, rhs: f32) -> Mat3 {
+        Mat3::from_rows(self.row(0) * rhs, self.row(1) * rhs, self.row(2) * rhs)
+    }
+}
+
+impl Mul<Mat3> for Vec3<f32> {
+    type Output = Vec3<f32>;
+    fn mul(self, rhs: Mat3) -> Vec3<f32> {
+        rhs.row(0) * self.x + rhs.row(1) * self.y + rhs.row(2) * self.z
+    }
+}
+
+impl ApproxEq for Mat3 {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+        self.m.concat().approx_eq_eps(&other.m.concat(), epsilon)
+    }
+}
+
+impl From<Mat4> for Mat3 {
+    /// the rotation and scale, without the translation
+    fn from(m: Mat4) -> Mat3 {
+        Mat3::from_rows(
+            m.row(0).truncate(),
+            m.row(1).truncate(),
+            m.row(2).truncate(),
+        )
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        m: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn from_rows(x: Vec4<f32>, y: Vec4<f32>, z: Vec4<f32>, w: Vec4<f32>) -> Mat4 {
+        Mat4 {
+            m: [
+                [x.x, x.y, x.z, x.w],
+                [y.x, y.y, y.z, y.w],
+                [z.x, z.y, z.z, z.w],
+                [w.x, w.y, w.z, w.w],
+            ],
+        }
+    }
+
+    pub fn row(&self, i: usize) -> Vec4<f32> {
+        let row = self.m[i];
+        Vec4::new(row[0], row[1], row[2], row[3])
+    }
+
+    pub fn column(&self, i: usize) -> Vec4<f32> {
+        Vec4::new(self.m[0][i], self.m[1][i], self.m[2][i], self.m[3][i])
+    }
+
+    pub fn translation(offset: Vec3<f32>) -> Mat4 {
+        let mut m = Mat4::IDENTITY;
+        m.m[3] = [offset.x, offset.y, offset.z, 1.0];
+        m
+    }
+
+    pub fn scaling(scale: Vec3<f32>) -> Mat4 {
+        Mat4::from(Mat3::scaling(scale))
+    }
+
+    /// rotations by `angle` radians, clockwise when looking along the axis
+    /// toward the origin
+    pub fn rotation_x(angle: f32) -> Mat4 {
+        let (s, c) = angle.sin_cos();
+        let mut m = Mat4::IDENTITY;
+        m.m[1] = [0.0, c, s, 0.0];
+        m.m[2] = [0.0, -s, c, 0.0];
+        m
+    }
+
+    pub fn rotation_y(angle: f32) -> Mat4 {
+        let (s, c) = angle.sin_cos();
+        let mut m = Mat4::IDENTITY;
+        m.m[0] = [c, 0.0, -s, 0.0];
+        m.m[2] = [s, 0.0, c, 0.0];
+        m
+    }
+
+    pub fn rotation_z(angle: f32) -> Mat4 {
+        let (s, c) = angle.sin_cos();
+        let mut m = Mat4::IDENTITY;
+        m.m[0] = [c, s, 0.0, 0.0];
+        m.m[1] = [-s, c, 0.0, 0.0];
+        m
+    }
+
+    pub fn from_quat(q: Quat) -> Mat4 {
+        Mat4::from(Mat3::from_quat(q))
+    }
+
+    /// scales, then rotates, then translates
+    pub fn from_scale_rotation_translation(
+        scale: Vec3<f32>,
+        rotation: Quat,
+        translation: Vec3<f32>,
+    ) -> Mat4 {
+        let mut m = Mat4::from(Mat3::scaling(scale) * Mat3::from_quat(rotation));
+        m.m[3] = [translation.x, translation.y, translation.z, 1.0];
+        m
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        Mat4::from_rows(
+            self.column(0),
+            self.column(1),
+            self.column(2),
+            self.column(3),
+        )
+    }
+
+    // the 2x2 minors of the top two rows and of the bottom two rows, which
+    // both the determinant and the inverse are made of
+    fn minors(&self) -> ([f32; 6], [f32; 6]) {
+        let a = &self.m;
+        let top = [
+            a[0][0] * a[1][1] - a[1][0] * a[0][1],
+            a[0][0] * a[1][2] - a[1][0] * a[0][2],
+            a[0][0] * a[1][3] - a[1][0] * a[0][3],
+            a[0][1] * a[1][2] - a[1][1] * a[0][2],
+            a[0][1] * a[1][3] - a[1][1] * a[0][3],
+            a[0][2] * a[1][3] - a[1][2] * a[0][3],
+        ];
+        let bottom = [
+            a[2][0] * a[3][1] - a[3][0] * a[2][1],
+            a[2][0] * a[3][2] - a[3][0] * a[2][2],
+            a[2][0] * a[3][3] - a[3][0] * a[2][3],
+            a[2][1] * a[3][2] - a[3][1] * a[2][2],
+            a[2][1] * a[3][3] - a[3][1] * a[2][3],
+            a[2][2] * a[3][3] - a[3][2] * a[2][3],
+        ];
+        (top, bottom)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.minors();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// none if the matrix is singular
+    pub fn inverse(&self) -> Option<Mat4> {
+        let (s, c) = self.minors();
+        let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let a = &self.m;
+        let inverse = Mat4 {
+            m: [
+                [
+                    a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                    -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                    a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                    -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+                ],
+                [
+                    -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                    a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                    -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                    a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+                ],
+                [
+                    a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                    -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                    a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                    -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+                ],
+                [
+                    -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                    a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                    -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                    a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+                ],
+            ],
+        };
+        Some(inverse * (1.0 / det))
+    }
+
+    /// `point` with w = 1, divided by the resulting w
+    pub fn transform_point(&self, point: Vec3<f32>) -> Vec3<f32> {
+        let v = point.extend(1.0) * *self;
+        v.truncate() / v.w
+    }
+
+    /// `vector` with w = 0, which leaves out the translation
+    pub fn transform_vector(&self, vector: Vec3<f32>) -> Vec3<f32> {
+        (vector.extend(0.0) * *self).truncate()
+    }
+}
+
+impl From<Mat3> for Mat4 {
+    fn from(m: Mat3) -> Mat4 {
+        Mat4::from_rows(
+            m.row(0).extend(0.0),
+            m.row(1).extend(0.0),
+            m.row(2).extend(0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        )
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        Mat4::from_rows(
+            self.row(0) * rhs,
+            self.row(1) * rhs,
+            self.row(2) * rhs,
+            self.row(3) * rhs,
+        )
+    }
+}
+
+impl Mul<f32> for Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: f32) -> Mat4 {
+        Mat4::from_rows(
+            self.row(0) * rhs,
+            self.row(1) * rhs,
+            self.row(2) * rhs,
+            self.row(3) * rhs,
+        )
+    }
+}
+
+impl Mul<Mat4> for Vec4<f32> {
+    type Output = Vec4<f32>;
+    fn mul(self, rhs: Mat4) -> Vec4<f32> {
+        rhs.row(0) * self.x + rhs.row(1) * self.y + rhs.row(2) * self.z + rhs.row(3) * self.w
+    }
+}
+
+impl ApproxEq for Mat4 {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+        self.m.concat().approx_eq_eps(&other.m.concat(), epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::approx::assert_approx_eq;
+    use super::*;
+
+    fn general() -> Mat4 {
+        Mat4 {
+            m: [
+                [1.0, 2.0, 0.0, 1.0],
+                [0.0, 1.0, 3.0, 2.0],
+                [4.0, 0.0, 1.0, 1.0],
+                [1.0, 1.0, 2.0, 0.0],
+            ],
+        }
+    }
+
+    fn transform() -> Mat4 {
+        Mat4::from_scale_rotation_translation(
+            Vec3::new(2.0, 0.5, 1.5),
+            Quat::from_euler(0.3, -1.1, 0.7),
+            Vec3::new(3.0, -2.0, 5.0),
+        )
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(std::mem::size_of::<Mat3>(), 36);
+        assert_eq!(std::mem::size_of::<Mat4>(), 64);
+        // the translation is in the last row
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(m.m[3], [1.0, 2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn mat3_inverse() {
+        let m = Mat3::from_rows(
+            Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(1.0, 3.0, 2.0),
+            Vec3::new(1.0, 1.0, 2.0),
+        );
+        assert_approx_eq!(m.determinant(), 6.0);
+        let inverse = m.inverse().unwrap();
+        assert_approx_eq!(m * inverse, Mat3::IDENTITY);
+        assert_approx_eq!(inverse * m, Mat3::IDENTITY);
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.transpose().row(0), m.column(0));
+    }
+
+    #[test]
+    fn mat4_inverse() {
+        for m in [general(), transform()].iter() {
+            let inverse = m.inverse().unwrap();
+            assert_approx_eq!(*m * inverse, Mat4::IDENTITY);
+            assert_approx_eq!(inverse * *m, Mat4::IDENTITY);
+            assert_approx_eq!(inverse.determinant(), 1.0 / m.determinant());
+            assert_approx_eq!(m.transpose().determinant(), m.determinant());
+        }
+        assert_approx_eq!(transform().determinant(), 1.5);
+        assert_approx_eq!(
+            (general() * transform()).determinant(),
+            general().determinant() * 1.5
+        );
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        let row = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(Mat3::from_rows(row, row * 2.0, Vec3::Z).inverse(), None);
+        assert_eq!(Mat3::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+        assert_eq!(Mat4::scaling(Vec3::new(1.0, 1.0, 0.0)).inverse(), None);
+        let mut m = general();
+        m.m[3] = m.m[0];
+        assert_eq!(m.inverse(), None);
+        m.m[3][0] = f32::NAN;
+        assert_eq!(m.inverse(), None);
+    }
+
+    #[test]
+    fn row_vectors_apply_the_left_matrix_first() {
+        let t = Mat4::translation(Vec3::X);
+        let s = Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        assert_approx_eq!(
+            (t * s).transform_point(Vec3::ZERO),
+            Vec3::new(2.0, 0.0, 0.0)
+        );
+        assert_approx_eq!((s * t).transform_point(Vec3::ZERO), Vec3::X);
+        assert_approx_eq!(t.transform_vector(Vec3::X), Vec3::X);
+        assert_approx_eq!(
+            transform().transform_point(Vec3::ZERO),
+            Vec3::new(3.0, -2.0, 5.0)
+        );
+        assert_approx_eq!(
+            Mat3::from(transform()),
+            Mat3::scaling(Vec3::new(2.0, 0.5, 1.5))
+                * Mat3::from_quat(Quat::from_euler(0.3, -1.1, 0.7))
+        );
+    }
+
+    #[test]
+    fn rotations_are_left_handed() {
+        use std::f32::consts::FRAC_PI_2;
+        // a quarter turn about y takes z to x, about x takes y to z
+        assert_approx_eq!(
+            Mat4::rotation_y(FRAC_PI_2).transform_vector(Vec3::Z),
+            Vec3::X
+        );
+        assert_approx_eq!(
+            Mat4::rotation_x(FRAC_PI_2).transform_vector(Vec3::Y),
+            Vec3::Z
+        );
+        assert_approx_eq!(
+            Mat4::rotation_z(FRAC_PI_2).transform_vector(Vec3::X),
+            Vec3::Y
+        );
+        let rotations = [
+            (Vec3::X, Mat4::rotation_x(0.7)),
+            (Vec3::Y, Mat4::rotation_y(0.7)),
+            (Vec3::Z, Mat4::rotation_z(0.7)),
+        ];
+        for (axis, m) in rotations.iter() {
+            assert_approx_eq!(Mat4::from_quat(Quat::from_axis_angle(*axis, 0.7)), *m);
+            assert_approx_eq!(m.inverse().unwrap(), m.transpose());
+        }
+    }
+}
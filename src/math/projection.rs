@@ -0,0 +1,196 @@
+//! view and projection matrices for left-handed coordinates, x right, y up
+//! and z into the screen, mapping depth to d3d's [0, 1]. the reversed
+//! variants map the near plane to 1 and the far plane to 0, which spreads
+//! float depth precision evenly; they want a `GREATER` depth test and a
+//! depth buffer cleared to 0.
+
+use super::matrix::Mat4;
+use super::vector::{Vec3, Vec4};
+
+/// the view from `eye` in `direction`, with `up` roughly up
+pub fn look_to_lh(eye: Vec3<f32>, direction: Vec3<f32>, up: Vec3<f32>) -> Mat4 {
+    let z = direction.normalize();
+    let x = up.cross(z).normalize();
+    let y = z.cross(x);
+    Mat4::from_rows(
+        Vec4::new(x.x, y.x, z.x, 0.0),
+        Vec4::new(x.y, y.y, z.y, 0.0),
+        Vec4::new(x.z, y.z, z.z, 0.0),
+        Vec4::new(-x.dot(eye), -y.dot(eye), -z.dot(eye), 1.0),
+    )
+}
+
+pub fn look_at_lh(eye: Vec3<f32>, target: Vec3<f32>, up: Vec3<f32>) -> Mat4 {
+    look_to_lh(eye, target - eye, up)
+}
+
+// depth comes out as (z * depth_scale + depth_offset) / z
+fn perspective(fov_y: f32, aspect: f32, depth_scale: f32, depth_offset: f32) -> Mat4 {
+    let height = 1.0 / (fov_y * 0.5).tan();
+    Mat4 {
+        m: [
+            [height / aspect, 0.0, 0.0, 0.0],
+            [0.0, height, 0.0, 0.0],
+            [0.0, 0.0, depth_scale, 1.0],
+            [0.0, 0.0, depth_offset, 0.0],
+        ],
+    }
+}
+
+/// `fov_y` is the vertical field of view in radians and `aspect` is width
+/// over height
+pub fn perspective_lh(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+    let scale = far / (far - near);
+    perspective(fov_y, aspect, scale, -near * scale)
+}
+
+pub fn perspective_reversed_lh(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+    perspective_lh(fov_y, aspect, far, near)
+}
+
+/// without a far plane; depth approaches 1 with distance
+pub fn perspective_infinite_lh(fov_y: f32, aspect: f32, near: f32) -> Mat4 {
+    perspective(fov_y, aspect, 1.0, -near)
+}
+
+/// without a far plane; depth approaches 0 with distance
+pub fn perspective_infinite_reversed_lh(fov_y: f32, aspect: f32, near: f32) -> Mat4 {
+    perspective(fov_y, aspect, 0.0, near)
+}
+
+/// the box from (`left`, `bottom`, `near`) to (`right`, `top`, `far`)
+pub fn orthographic_off_center_lh(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+) -> Mat4 {
+    let (width, height, depth) = (right - left, top - bottom, far - near);
+    Mat4 {
+        m: [
+            [2.0 / width, 0.0, 0.0, 0.0],
+            [0.0, 2.0 / height, 0.0, 0.0],
+            [0.0, 0.0, 1.0 / depth, 0.0],
+            [
+                -(left + right) / width,
+                -(top + bottom) / height,
+                -near / depth,
+                1.0,
+            ],
+        ],
+    }
+}
+
+/// a `width` x `height` box centered on the z axis
+pub fn orthographic_lh(width: f32, height: f32, near: f32, far: f32) -> Mat4 {
+    let (x, y) = (width * 0.5, height * 0.5);
+    orthographic_off_center_lh(-x, x, -y, y, near, far)
+}
+
+pub fn orthographic_reversed_lh(width: f32, height: f32, near: f32, far: f32) -> Mat4 {
+    orthographic_lh(width, height, far, near)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::approx::assert_approx_eq;
+    use super::super::vector::Vec2;
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn depth(m: &Mat4, z: f32) -> f32 {
+        m.transform_point(Vec3::new(0.0, 0.0, z)).z
+    }
+
+    #[test]
+    fn views() {
+        let eye = Vec3::new(1.0, 2.0, -5.0);
+        let view = look_at_lh(eye, Vec3::new(1.0, 2.0, 0.0), Vec3::Y);
+        assert_approx_eq!(view.transform_point(eye), Vec3::ZERO);
+        assert_approx_eq!(
+            view.transform_point(Vec3::new(1.0, 2.0, 3.0)),
+            Vec3::new(0.0, 0.0, 8.0)
+        );
+        assert_approx_eq!(
+            view.transform_point(Vec3::new(2.0, 3.0, -5.0)),
+            Vec3::new(1.0, 1.0, 0.0)
+        );
+        let view = look_to_lh(Vec3::ZERO, Vec3::X, Vec3::Y);
+        assert_approx_eq!(
+            view.transform_point(Vec3::new(5.0, 0.0, 0.0)),
+            Vec3::new(0.0, 0.0, 5.0)
+        );
+        assert_approx_eq!(view.transform_point(Vec3::Z), Vec3::new(-1.0, 0.0, 0.0));
+        assert_approx_eq!(view.determinant(), 1.0);
+    }
+
+    #[test]
+    fn perspective_maps_depth_to_0_1() {
+        let (near, far) = (0.5, 100.0);
+        let p = perspective_lh(FRAC_PI_2, 2.0, near, far);
+        assert_approx_eq!(depth(&p, near), 0.0);
+        assert_approx_eq!(depth(&p, far), 1.0);
+        // increasing in between, and outside of [0, 1] beyond the planes
+        let depths: Vec<f32> = [1.0, 2.0, 10.0, 50.0]
+            .iter()
+            .map(|z| depth(&p, *z))
+            .collect();
+        assert!(depths
+            .windows(2)
+            .all(|pair| 0.0 < pair[0] && pair[0] < pair[1] && pair[1] < 1.0));
+        assert!(depth(&p, 0.25) < 0.0 && depth(&p, 200.0) > 1.0);
+        // x by the aspect, and w is the view depth for the divide
+        let edge = p.transform_point(Vec3::new(2.0, 1.0, 1.0)).truncate();
+        assert_approx_eq!(edge, Vec2::new(1.0, 1.0));
+        assert_approx_eq!((Vec3::new(0.0, 0.0, 7.0).extend(1.0) * p).w, 7.0);
+    }
+
+    #[test]
+    fn reversed_and_infinite_perspectives() {
+        let (near, far) = (0.5, 100.0);
+        let r = perspective_reversed_lh(FRAC_PI_2, 2.0, near, far);
+        assert_approx_eq!(depth(&r, near), 1.0);
+        assert_approx_eq!(depth(&r, far), 0.0);
+
+        let i = perspective_infinite_lh(FRAC_PI_4, 1.0, near);
+        assert_approx_eq!(depth(&i, near), 0.0);
+        assert!(depth(&i, 1e3) < 1.0);
+        assert_approx_eq!(depth(&i, 1e7), 1.0);
+
+        let ir = perspective_infinite_reversed_lh(FRAC_PI_4, 1.0, near);
+        assert_approx_eq!(depth(&ir, near), 1.0);
+        assert!(depth(&ir, 1e7) > 0.0);
+        assert_approx_eq!(depth(&ir, 1e7), 0.0);
+        let tan = (FRAC_PI_4 / 2.0).tan();
+        let corner = ir.transform_point(Vec3::new(tan * 10.0, -tan * 10.0, 10.0));
+        assert_approx_eq!(corner.truncate(), Vec2::new(1.0, -1.0));
+    }
+
+    #[test]
+    fn orthographic() {
+        let o = orthographic_lh(4.0, 2.0, 1.0, 11.0);
+        assert_approx_eq!(
+            o.transform_point(Vec3::new(2.0, 1.0, 1.0)),
+            Vec3::new(1.0, 1.0, 0.0)
+        );
+        assert_approx_eq!(
+            o.transform_point(Vec3::new(-2.0, -1.0, 11.0)),
+            Vec3::new(-1.0, -1.0, 1.0)
+        );
+        assert_approx_eq!(depth(&o, 6.0), 0.5);
+        let reversed = orthographic_reversed_lh(4.0, 2.0, 1.0, 11.0);
+        assert_approx_eq!(depth(&reversed, 1.0), 1.0);
+        assert_approx_eq!(depth(&reversed, 11.0), 0.0);
+        let pixels = orthographic_off_center_lh(0.0, 640.0, 480.0, 0.0, 0.0, 1.0);
+        assert_approx_eq!(
+            pixels.transform_point(Vec3::ZERO),
+            Vec3::new(-1.0, 1.0, 0.0)
+        );
+        assert_approx_eq!(
+            pixels.transform_point(Vec3::new(640.0, 480.0, 1.0)),
+            Vec3::new(1.0, -1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! smaller vertex attributes. floats become normalized integers the way d3d
+//! converts them: unorm maps [0, 1] and snorm [-1, 1] onto the whole integer
+//! range, rounding to nearest and mapping nan to 0. snorm's most negative
+//! value reads as -1 like the one above it.
+//!
+//! normals pack into two snorm16s with the octahedral mapping, and a whole
+//! tangent frame into a snorm16 quaternion, the qtangent, whose sign carries
+//! the handedness of the bitangent.
+
+use super::half::Half;
+use super::matrix::Mat3;
+use super::quat::Quat;
+use super::vector::{Vec2, Vec3, Vec4};
+
+/// `value` in [0, 1] as an unsigned `bits` bit integer
+pub fn to_unorm(value: f32, bits: u32) -> u32 {
+    let max = ((1u64 << bits) - 1) as f32;
+    if value.is_nan() {
+        0
+    } else {
+        (value.clamp(0.0, 1.0) * max + 0.5) as u32
+    }
+}
+
+pub fn from_unorm(value: u32, bits: u32) -> f32 {
+    value as f32 / ((1u64 << bits) - 1) as f32
+}
+
+/// `value` in [-1, 1] as a signed `bits` bit integer
+pub fn to_snorm(value: f32, bits: u32) -> i32 {
+    let max = ((1u64 << (bits - 1)) - 1) as f32;
+    if value.is_nan() {
+        0
+    } else {
+        (value.clamp(-1.0, 1.0) * max).round() as i32
+    }
+}
+
+pub fn from_snorm(value: i32, bits: u32) -> f32 {
+    (value as f32 / ((1u64 << (bits - 1)) - 1) as f32).max(-1.0)
+}
+
+pub fn to_unorm8(value: f32) -> u8 {
+    to_unorm(value, 8) as u8
+}
+
+pub fn from_unorm8(value: u8) -> f32 {
+    from_unorm(value as u32, 8)
+}
+
+pub fn to_unorm16(value: f32) -> u16 {
+    to_unorm(value, 16) as u16
+}
+
+pub fn from_unorm16(value: u16) -> f32 {
+    from_unorm(value as u32, 16)
+}
+
+pub fn to_snorm8(value: f32) -> i8 {
+    to_snorm(value, 8) as i8
+}
+
+pub fn from_snorm8(value: i8) -> f32 {
+    from_snorm(value as i32, 8)
+}
+
+pub fn to_snorm16(value: f32) -> i16 {
+    to_snorm(value, 16) as i16
+}
+
+pub fn from_snorm16(value: i16) -> f32 {
+    from_snorm(value as i32, 16)
+}
+
+// a vector of normalized integers or halves, and its conversions from and to
+// the float vector
+macro_rules! packed_vector {
+    ($(#[$doc:meta])* $name:ident([$element:ty; $len:literal]), $vector:ident, $to:path, $from:path) => {
+        $(#[$doc])*
+        #[repr(C)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+        pub struct $name(pub [$element; $len]);
+
+        impl From<$vector<f32>> for $name {
+            fn from(v: $vector<f32>) -> $name {
+                let array: [f32; $len] = v.into();
+                $name(array.map($to))
+            }
+        }
+
+        impl From<$name> for $vector<f32> {
+            fn from(packed: $name) -> $vector<f32> {
+                $vector::from(packed.0.map($from))
+            }
+        }
+    };
+}
+
+packed_vector!(Half2([Half; 2]), Vec2, Half::from_f32, Half::to_f32);
+packed_vector!(Half4([Half; 4]), Vec4, Half::from_f32, Half::to_f32);
+packed_vector!(Unorm8x2([u8; 2]), Vec2, to_unorm8, from_unorm8);
+packed_vector!(Unorm8x4([u8; 4]), Vec4, to_unorm8, from_unorm8);
+packed_vector!(Snorm8x2([i8; 2]), Vec2, to_snorm8, from_snorm8);
+packed_vector!(Snorm8x4([i8; 4]), Vec4, to_snorm8, from_snorm8);
+packed_vector!(Unorm16x2([u16; 2]), Vec2, to_unorm16, from_unorm16);
+packed_vector!(Unorm16x4([u16; 4]), Vec4, to_unorm16, from_unorm16);
+packed_vector!(Snorm16x2([i16; 2]), Vec2, to_snorm16, from_snorm16);
+packed_vector!(Snorm16x4([i16; 4]), Vec4, to_snorm16, from_snorm16);
+
+/// x, y and z as 10 bit unorms from the low bits up, and w as 2 bits
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Unorm1010102(pub u32);
+
+impl From<Vec4<f32>> for Unorm1010102 {
+    fn from(v: Vec4<f32>) -> Unorm1010102 {
+        Unorm1010102(
+            to_unorm(v.x, 10)
+                | to_unorm(v.y, 10) << 10
+                | to_unorm(v.z, 10) << 20
+                | to_unorm(v.w, 2) << 30,
+        )
+    }
+}
+
+impl From<Unorm1010102> for Vec4<f32> {
+    fn from(packed: Unorm1010102) -> Vec4<f32> {
+        let bits = packed.0;
+        Vec4::new(
+            from_unorm(bits & 0x3ff, 10),
+            from_unorm((bits >> 10) & 0x3ff, 10),
+            from_unorm((bits >> 20) & 0x3ff, 10),
+            from_unorm(bits >> 30, 2),
+        )
+    }
+}
+
+fn sign_not_zero(value: f32) -> f32 {
+    if value < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+/// a direction on the octahedron unfolded into [-1, 1]². the zero vector
+/// has no direction and maps to the centre, which is +z.
+pub fn octahedral(direction: Vec3<f32>) -> Vec2<f32> {
+    let sum = direction.x.abs() + direction.y.abs() + direction.z.abs();
+    if sum == 0.0 {
+        return Vec2::ZERO;
+    }
+    let n = direction / sum;
+    if n.z >= 0.0 {
+        Vec2::new(n.x, n.y)
+    } else {
+        Vec2::new(
+            (1.0 - n.y.abs()) * sign_not_zero(n.x),
+            (1.0 - n.x.abs()) * sign_not_zero(n.y),
+        )
+    }
+}
+
+/// the unit direction of a point from `octahedral`
+pub fn from_octahedral(p: Vec2<f32>) -> Vec3<f32> {
+    let z = 1.0 - p.x.abs() - p.y.abs();
+    let (x, y) = if z < 0.0 {
+        (
+            (1.0 - p.y.abs()) * sign_not_zero(p.x),
+            (1.0 - p.x.abs()) * sign_not_zero(p.y),
+        )
+    } else {
+        (p.x, p.y)
+    };
+    Vec3::new(x, y, z).normalize()
+}
+
+/// a unit normal in two snorm16s, as `DXGI_FORMAT_R16G16_SNORM`. of the four
+/// neighbouring values the one that decodes closest is kept, which halves
+/// the error of plain rounding.
+pub fn encode_normal(normal: Vec3<f32>) -> Snorm16x2 {
+    let normal = normal.normalize();
+    let p = octahedral(normal) * 32767.0;
+    // by distance, as the dot products are all too close to 1 for f32
+    let mut best = (Snorm16x2::default(), f32::MAX);
+    for &x in [p.x.floor(), p.x.ceil()].iter() {
+        for &y in [p.y.floor(), p.y.ceil()].iter() {
+            let packed = Snorm16x2([x as i16, y as i16]);
+            let error = (decode_normal(packed) - normal).length_squared();
+            if error < best.1 {
+                best = (packed, error);
+            }
+        }
+    }
+    best.0
+}
+
+pub fn decode_normal(packed: Snorm16x2) -> Vec3<f32> {
+    from_octahedral(packed.into())
+}
+
+/// a tangent frame as a quaternion in four snorm16s, as
+/// `DXGI_FORMAT_R16G16B16A16_SNORM`. the tangent is made perpendicular to
+/// the normal, and the bitangent is `normal.cross(tangent) * handedness`,
+/// where `handedness` is 1 or -1.
+pub fn encode_qtangent(normal: Vec3<f32>, tangent: Vec3<f32>, handedness: f32) -> Snorm16x4 {
+    let normal = normal.normalize();
+    let tangent = (tangent - normal * normal.dot(tangent)).normalize();
+    let frame = Mat3::from_rows(tangent, normal.cross(tangent), normal);
+    let mut q = Quat::from_mat3(&frame).normalize();
+    if q.w < 0.0 {
+        q = -q;
+    }
+    // w must not round to 0, or its sign is lost
+    let bias = 1.0 / 32767.0;
+    if q.w < bias {
+        let scale = (1.0 - bias * bias).sqrt();
+        q = Quat::new(q.x * scale, q.y * scale, q.z * scale, bias);
+    }
+    if handedness < 0.0 {
+        q = -q;
+    }
+    Snorm16x4::from(Vec4::new(q.x, q.y, q.z, q.w))
+}
+
+/// `(normal, tangent, handedness)` from `encode_qtangent`
+pub fn decode_qtangent(packed: Snorm16x4) -> (Vec3<f32>, Vec3<f32>, f32) {
+    let v = Vec4::from(packed);
+    let handedness = sign_not_zero(v.w);
+    let frame = Mat3::from_quat(Quat::new(v.x, v.y, v.z, v.w).normalize());
+    (frame.row(2), frame.row(0), handedness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::approx::assert_approx_eq;
+    use super::*;
+
+    #[test]
+    fn norms_round_trip_every_value() {
+        for v in 0..=u8::MAX {
+            assert_eq!(to_unorm8(from_unorm8(v)), v);
+        }
+        for v in 0..=u16::MAX {
+            assert_eq!(to_unorm16(from_unorm16(v)), v);
+        }
+        // the most negative value reads as -1, which writes the one above it
+        for v in i8::MIN..=i8::MAX {
+            assert_eq!(to_snorm8(from_snorm8(v)), v.max(-127));
+        }
+        for v in i16::MIN..=i16::MAX {
+            assert_eq!(to_snorm16(from_snorm16(v)), v.max(-32767));
+        }
+        for v in 0..1024 {
+            assert_eq!(to_unorm(from_unorm(v, 10), 10), v);
+        }
+    }
+
+    #[test]
+    fn unorm_edges() {
+        let cases = [
+            (-1.0, 0),
+            (0.0, 0),
+            (0.5, 128),
+            (1.0, 255),
+            (1.5, 255),
+            (f32::INFINITY, 255),
+            (f32::NEG_INFINITY, 0),
+            (f32::NAN, 0),
+        ];
+        for &(value, expected) in cases.iter() {
+            assert_eq!(to_unorm8(value), expected, "{}", value);
+        }
+        assert_eq!(to_unorm16(1.0), u16::MAX);
+        assert_eq!(to_unorm(1.0, 2), 3);
+        assert_eq!(from_unorm8(0), 0.0);
+        assert_eq!(from_unorm16(u16::MAX), 1.0);
+    }
+
+    #[test]
+    fn snorm_edges() {
+        let cases = [
+            (-2.0, -127),
+            (-1.0, -127),
+            (0.0, 0),
+            (-0.0, 0),
+            (0.5, 64),
+            (1.0, 127),
+            (2.0, 127),
+            (f32::NAN, 0),
+        ];
+        for &(value, expected) in cases.iter() {
+            assert_eq!(to_snorm8(value), expected, "{}", value);
+        }
+        assert_eq!(to_snorm16(-1.0), -32767);
+        assert_eq!(to_snorm16(1.0), 32767);
+        assert_eq!(from_snorm8(-128), -1.0);
+        assert_eq!(from_snorm8(-127), -1.0);
+        assert_eq!(from_snorm16(0), 0.0);
+        assert_eq!(from_snorm16(32767), 1.0);
+    }
+
+    #[test]
+    fn norms_are_within_half_a_step() {
+        for i in 0..=10000 {
+            let x = i as f32 / 10000.0;
+            assert!((from_unorm8(to_unorm8(x)) - x).abs() <= 0.5 / 255.0 + 1e-7);
+            let y = 2.0 * x - 1.0;
+            assert!((from_snorm16(to_snorm16(y)) - y).abs() <= 0.5 / 32767.0 + 1e-7);
+        }
+    }
+
+    #[test]
+    fn packed_vectors() {
+        let v = Vec4::new(0.25, 0.5, 0.75, 1.0);
+        assert_eq!(Vec4::from(Half4::from(v)), v);
+        assert_eq!(Unorm8x4::from(v), Unorm8x4([64, 128, 191, 255]));
+        assert_approx_eq!(Vec4::from(Unorm16x4::from(v)), v);
+        assert_eq!(Snorm8x2::from(Vec2::new(-1.0, 1.0)), Snorm8x2([-127, 127]));
+        assert_eq!(std::mem::size_of::<Half2>(), 4);
+        assert_eq!(std::mem::size_of::<Snorm16x4>(), 8);
+        assert_eq!(std::mem::size_of::<Unorm8x2>(), 2);
+    }
+
+    #[test]
+    fn unorm1010102() {
+        let v = Vec4::new(1.0, 0.0, 0.5, 1.0 / 3.0);
+        let packed = Unorm1010102::from(v);
+        assert_eq!(packed.0, 0x3ff | 512 << 20 | 1 << 30);
+        assert_approx_eq!(
+            Vec4::from(packed),
+            Vec4::new(1.0, 0.0, 512.0 / 1023.0, 1.0 / 3.0)
+        );
+        // out of range components clamp instead of spilling into the next
+        let clamped = Unorm1010102::from(Vec4::new(2.0, -1.0, f32::NAN, 5.0));
+        assert_eq!(clamped.0, 0x3ff | 3 << 30);
+        for &bits in [0u32, 0xffff_ffff, 0x1234_5678, 0xdead_beef].iter() {
+            assert_eq!(Unorm1010102::from(Vec4::from(Unorm1010102(bits))).0, bits);
+        }
+    }
+
+    fn directions() -> Vec<Vec3<f32>> {
+        let mut out = vec![Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z];
+        for i in 0..64 {
+            for j in 0..=32 {
+                let theta = i as f32 / 64.0 * std::f32::consts::TAU;
+                let phi = j as f32 / 32.0 * std::f32::consts::PI;
+                out.push(Vec3::new(
+                    phi.sin() * theta.cos(),
+                    phi.sin() * theta.sin(),
+                    phi.cos(),
+                ));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn octahedral_axes() {
+        let axes = [
+            (Vec3::X, Vec2::new(1.0, 0.0)),
+            (-Vec3::X, Vec2::new(-1.0, 0.0)),
+            (Vec3::Y, Vec2::new(0.0, 1.0)),
+            (-Vec3::Y, Vec2::new(0.0, -1.0)),
+            (Vec3::Z, Vec2::new(0.0, 0.0)),
+            // the lower pole is every corner; the positive one is picked
+            (-Vec3::Z, Vec2::new(1.0, 1.0)),
+        ];
+        for &(axis, point) in axes.iter() {
+            assert_eq!(octahedral(axis), point, "{:?}", axis);
+            assert_eq!(from_octahedral(point), axis);
+            assert_eq!(decode_normal(encode_normal(axis)), axis);
+        }
+        assert_eq!(from_octahedral(Vec2::new(-1.0, -1.0)), -Vec3::Z);
+    }
+
+    #[test]
+    fn octahedral_zero_is_up() {
+        assert_eq!(octahedral(Vec3::ZERO), Vec2::ZERO);
+        assert_eq!(encode_normal(Vec3::ZERO), Snorm16x2([0, 0]));
+        assert_eq!(decode_normal(encode_normal(Vec3::ZERO)), Vec3::Z);
+    }
+
+    #[test]
+    fn octahedral_normals() {
+        for n in directions() {
+            let p = octahedral(n);
+            assert!(p.x.abs() <= 1.0 && p.y.abs() <= 1.0);
+            assert_approx_eq!(from_octahedral(p), n, 1e-5);
+            let decoded = decode_normal(encode_normal(n));
+            assert!(decoded.cross(n).length() < 1e-4 && decoded.dot(n) > 0.0);
+            assert!((decoded.length() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn qtangents_keep_the_handedness() {
+        let directions = directions();
+        for (i, &n) in directions.iter().enumerate() {
+            let other = directions[(i * 7 + 3) % directions.len()];
+            let t = n.cross(other);
+            if t.length() < 0.1 {
+                continue;
+            }
+            let t = t.normalize();
+            for &handedness in [1.0, -1.0].iter() {
+                let packed = encode_qtangent(n, t, handedness);
+                assert_eq!(packed.0[3] < 0, handedness < 0.0);
+                let (n2, t2, h2) = decode_qtangent(packed);
+                assert_eq!(h2, handedness);
+                assert_approx_eq!(n2, n, 1e-3);
+                assert_approx_eq!(t2, t, 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn qtangent_with_w_near_zero() {
+        // a half turn about x, where w would round to zero and lose its sign
+        let (n, t) = (-Vec3::Z, Vec3::X);
+        for &handedness in [1.0, -1.0].iter() {
+            let packed = encode_qtangent(n, t, handedness);
+            assert_eq!(packed.0[3], handedness as i16);
+            let (n2, t2, h2) = decode_qtangent(packed);
+            assert_eq!(h2, handedness);
+            assert_approx_eq!(n2, n, 1e-3);
+            assert_approx_eq!(t2, t, 1e-3);
+        }
+    }
+}
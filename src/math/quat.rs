@@ -0,0 +1,314 @@
+use std::ops::{Mul, Neg};
+
+use super::approx::ApproxEq;
+use super::matrix::Mat3;
+use super::vector::{Vec3, Vec4};
+
+/// a rotation as a unit quaternion. `a * b` rotates by `b` first, so it is
+/// the rotation of `Mat4::from_quat(b) * Mat4::from_quat(a)`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 1.0,
+    };
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quat {
+        Quat {
+            x: x,
+            y: y,
+            z: z,
+            w: w,
+        }
+    }
+
+    /// `angle` radians about `axis`, in the direction of `Mat4::rotation_x`
+    /// and its siblings
+    pub fn from_axis_angle(axis: Vec3<f32>, angle: f32) -> Quat {
+        let (s, c) = (angle * 0.5).sin_cos();
+        let axis = axis.normalize() * s;
+        Quat::new(axis.x, axis.y, axis.z, c)
+    }
+
+    /// the axis and an angle in [0, 2pi]; the identity gives the x axis
+    pub fn to_axis_angle(self) -> (Vec3<f32>, f32) {
+        let q = self.normalize();
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        let s = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if s < 1e-6 {
+            (Vec3::X, angle)
+        } else {
+            (Vec3::new(q.x, q.y, q.z) / s, angle)
+        }
+    }
+
+    /// roll about z, then pitch about x, then yaw about y, as DirectXMath's
+    /// `XMQuaternionRotationRollPitchYaw`
+    pub fn from_euler(pitch: f32, yaw: f32, roll: f32) -> Quat {
+        Quat::from_axis_angle(Vec3::Y, yaw)
+            * Quat::from_axis_angle(Vec3::X, pitch)
+            * Quat::from_axis_angle(Vec3::Z, roll)
+    }
+
+    /// `(pitch, yaw, roll)` for `from_euler`. pitch is in [-pi/2, pi/2]; at
+    /// either end roll is 0 and yaw takes the whole rotation about y.
+    pub fn to_euler(self) -> (f32, f32, f32) {
+        let m = Mat3::from_quat(self.normalize()).transpose().m;
+        let pitch = (-m[1][2]).clamp(-1.0, 1.0).asin();
+        if m[1][0].abs() < 1e-6 && m[1][1].abs() < 1e-6 {
+            (pitch, (-m[2][0]).atan2(m[0][0]), 0.0)
+        } else {
+            (pitch, m[0][2].atan2(m[2][2]), m[1][0].atan2(m[1][1]))
+        }
+    }
+
+    /// the rotation of a matrix with orthonormal rows and a determinant of 1,
+    /// the inverse of `Mat3::from_quat`
+    pub fn from_mat3(m: &Mat3) -> Quat {
+        let m = m.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // divide by the largest of 4w, 4x, 4y and 4z
+        if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat::new(
+                (m[1][2] - m[2][1]) / s,
+                (m[2][0] - m[0][2]) / s,
+                (m[0][1] - m[1][0]) / s,
+                s / 4.0,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quat::new(
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] - m[2][1]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quat::new(
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+                (m[2][0] - m[0][2]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quat::new(
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+                (m[0][1] - m[1][0]) / s,
+            )
+        }
+    }
+
+    pub fn dot(self, rhs: Quat) -> f32 {
+        self.to_vec4().dot(rhs.to_vec4())
+    }
+
+    pub fn length(self) -> f32 {
+        self.to_vec4().length()
+    }
+
+    pub fn normalize(self) -> Quat {
+        Quat::from_vec4(self.to_vec4().normalize())
+    }
+
+    pub fn conjugate(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// the opposite rotation; the conjugate when the length is 1
+    pub fn inverse(self) -> Quat {
+        Quat::from_vec4(self.conjugate().to_vec4() / self.to_vec4().length_squared())
+    }
+
+    pub fn rotate(self, v: Vec3<f32>) -> Vec3<f32> {
+        let axis = Vec3::new(self.x, self.y, self.z);
+        let t = axis.cross(v) * 2.0;
+        v + t * self.w + axis.cross(t)
+    }
+
+    /// the shortest arc from `self` at `t` = 0 to `to` at `t` = 1, at a
+    /// constant angular speed
+    pub fn slerp(self, to: Quat, t: f32) -> Quat {
+        let mut to = to;
+        let mut cos = self.dot(to);
+        // q and -q are the same rotation; take the one that is closer
+        if cos < 0.0 {
+            to = -to;
+            cos = -cos;
+        }
+        if cos > 0.9995 {
+            // too close for sin to be accurate, where a lerp is the same
+            return Quat::from_vec4(self.to_vec4().lerp(to.to_vec4(), t)).normalize();
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        Quat::from_vec4(self.to_vec4() * a + to.to_vec4() * b)
+    }
+
+    /// the same rotation, whichever sign either has
+    pub fn same_rotation(self, other: Quat, epsilon: f32) -> bool {
+        self.approx_eq_eps(&other, epsilon) || self.approx_eq_eps(&-other, epsilon)
+    }
+
+    fn to_vec4(self) -> Vec4<f32> {
+        Vec4::new(self.x, self.y, self.z, self.w)
+    }
+
+    fn from_vec4(v: Vec4<f32>) -> Quat {
+        Quat::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Quat {
+        Quat::IDENTITY
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+    fn mul(self, rhs: Quat) -> Quat {
+        Quat::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl Neg for Quat {
+    type Output = Quat;
+    fn neg(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl ApproxEq for Quat {
+    fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+        self.to_vec4().approx_eq_eps(&other.to_vec4(), epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::approx::assert_approx_eq;
+    use super::super::matrix::Mat4;
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn about_y(angle: f32) -> Quat {
+        Quat::from_axis_angle(Vec3::Y, angle)
+    }
+
+    #[test]
+    fn rotates_like_its_matrix() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, -0.5), 1.3);
+        let v = Vec3::new(0.3, -4.0, 2.0);
+        assert_approx_eq!(q.length(), 1.0);
+        assert_approx_eq!(q.rotate(v), v * Mat3::from_quat(q));
+        assert_approx_eq!(q * q.inverse(), Quat::IDENTITY);
+        assert_approx_eq!(q.conjugate(), q.inverse());
+        // a * b rotates by b first
+        let r = Quat::from_euler(0.2, 0.9, -0.4);
+        assert_approx_eq!((q * r).rotate(v), q.rotate(r.rotate(v)));
+        assert_approx_eq!(
+            Mat4::from_quat(q * r),
+            Mat4::from_quat(r) * Mat4::from_quat(q)
+        );
+        assert_approx_eq!(
+            Mat4::from_quat(r),
+            Mat4::rotation_z(-0.4) * Mat4::rotation_x(0.2) * Mat4::rotation_y(0.9)
+        );
+    }
+
+    #[test]
+    fn matrix_round_trips() {
+        // one rotation for each way from_mat3 divides: by w, then by x, y
+        // and z for half turns about each axis
+        let rotations = [
+            Quat::IDENTITY,
+            Quat::from_axis_angle(Vec3::new(1.0, 2.0, -0.5), 1.3),
+            Quat::from_axis_angle(Vec3::new(1.0, 0.1, 0.2), 3.0),
+            Quat::from_axis_angle(Vec3::new(0.1, 1.0, -0.2), 3.0),
+            Quat::from_axis_angle(Vec3::new(-0.2, 0.1, 1.0), 3.0),
+            Quat::from_axis_angle(Vec3::X, PI),
+            Quat::from_axis_angle(Vec3::Y, PI),
+            Quat::from_axis_angle(Vec3::Z, PI),
+            Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), PI),
+        ];
+        for q in rotations.iter() {
+            let m = Mat3::from_quat(*q);
+            assert!(Quat::from_mat3(&m).same_rotation(*q, 1e-5), "{:?}", q);
+            assert_approx_eq!(Mat3::from_quat(Quat::from_mat3(&m)), m);
+        }
+        // w comes out positive when it is not 0
+        assert!(Quat::from_mat3(&Mat3::from_quat(-rotations[1])).w > 0.0);
+    }
+
+    #[test]
+    fn axis_angle_round_trips() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, -0.5), 1.3);
+        let (axis, angle) = q.to_axis_angle();
+        assert_approx_eq!(axis, Vec3::new(1.0, 2.0, -0.5).normalize());
+        assert_approx_eq!(angle, 1.3);
+        assert_eq!(Quat::IDENTITY.to_axis_angle(), (Vec3::X, 0.0));
+        assert_eq!(Quat::default(), Quat::IDENTITY);
+    }
+
+    #[test]
+    fn euler_round_trips() {
+        let angles = [
+            (0.2, 0.9, -0.4),
+            (-1.2, 3.0, 2.5),
+            (0.0, -2.0, 0.0),
+            (1.5, 0.1, -3.0),
+        ];
+        for &(pitch, yaw, roll) in angles.iter() {
+            let (p, y, r) = Quat::from_euler(pitch, yaw, roll).to_euler();
+            assert_approx_eq!([p, y, r][..], [pitch, yaw, roll][..]);
+        }
+        // at a pitch of pi/2 only yaw - roll matters, and roll becomes 0
+        let q = Quat::from_euler(FRAC_PI_2, 0.8, 0.3);
+        let (pitch, yaw, roll) = q.to_euler();
+        assert_approx_eq!(pitch, FRAC_PI_2);
+        assert_eq!(roll, 0.0);
+        assert!(Quat::from_euler(pitch, yaw, roll).same_rotation(q, 1e-4));
+    }
+
+    #[test]
+    fn slerp() {
+        let (a, b) = (about_y(0.0), about_y(2.0));
+        assert_approx_eq!(a.slerp(b, 0.0), a);
+        assert_approx_eq!(a.slerp(b, 1.0), b);
+        assert_approx_eq!(a.slerp(b, 0.25), about_y(0.5));
+        assert_approx_eq!(a.slerp(about_y(3.0), 0.5), about_y(1.5));
+        // the ends hold when the shorter way means negating the target
+        let c = about_y(350f32.to_radians());
+        assert!(a.slerp(c, 1.0).same_rotation(c, 1e-5));
+        assert!(a
+            .slerp(c, 0.5)
+            .same_rotation(about_y(-5f32.to_radians()), 1e-5));
+        // nearly equal rotations are lerped, and still unit length
+        let d = about_y(1e-4);
+        assert_approx_eq!(a.slerp(d, 0.5).length(), 1.0);
+        assert_approx_eq!(a.slerp(d, 1.0), d);
+        assert_approx_eq!(a.slerp(about_y(PI), 0.5).length(), 1.0);
+    }
+}
@@ -0,0 +1,247 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::approx::ApproxEq;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec4<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+impl<T> Vec2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Vec2 { x: x, y: y }
+    }
+
+    pub fn extend(self, z: T) -> Vec3<T> {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl<T> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Vec3 { x: x, y: y, z: z }
+    }
+
+    pub fn extend(self, w: T) -> Vec4<T> {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub fn truncate(self) -> Vec2<T> {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl<T> Vec4<T> {
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Vec4 {
+            x: x,
+            y: y,
+            z: z,
+            w: w,
+        }
+    }
+
+    pub fn truncate(self) -> Vec3<T> {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+// to and from arrays, for any element type
+macro_rules! array_vector {
+    ($name:ident, $len:literal { $($field:ident),+ }) => {
+        impl<T> From<[T; $len]> for $name<T> {
+            fn from(array: [T; $len]) -> Self {
+                let [$($field),+] = array;
+                $name { $($field: $field),+ }
+            }
+        }
+
+        impl<T> From<$name<T>> for [T; $len] {
+            fn from(v: $name<T>) -> Self {
+                [$(v.$field),+]
+            }
+        }
+    };
+}
+
+array_vector!(Vec2, 2 { x, y });
+array_vector!(Vec3, 3 { x, y, z });
+array_vector!(Vec4, 4 { x, y, z, w });
+
+// the float operations, the same for every size
+macro_rules! float_vector {
+    ($name:ident { $($field:ident),+ }) => {
+        impl $name<f32> {
+            pub const ZERO: Self = $name { $($field: 0.0),+ };
+
+            pub fn splat(value: f32) -> Self {
+                $name { $($field: value),+ }
+            }
+
+            pub fn dot(self, rhs: Self) -> f32 {
+                0.0 $(+ self.$field * rhs.$field)+
+            }
+
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            /// the vector scaled to length 1; a zero vector stays zero
+            pub fn normalize(self) -> Self {
+                let length = self.length();
+                if length == 0.0 {
+                    self
+                } else {
+                    self / length
+                }
+            }
+
+            /// `self` at `t` = 0 and `to` at `t` = 1
+            pub fn lerp(self, to: Self, t: f32) -> Self {
+                self + (to - self) * t
+            }
+
+            pub fn min(self, rhs: Self) -> Self {
+                $name { $($field: self.$field.min(rhs.$field)),+ }
+            }
+
+            pub fn max(self, rhs: Self) -> Self {
+                $name { $($field: self.$field.max(rhs.$field)),+ }
+            }
+        }
+
+        impl Add for $name<f32> {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                $name { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl Sub for $name<f32> {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                $name { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        /// component-wise
+        impl Mul for $name<f32> {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                $name { $($field: self.$field * rhs.$field),+ }
+            }
+        }
+
+        impl Mul<f32> for $name<f32> {
+            type Output = Self;
+            fn mul(self, rhs: f32) -> Self {
+                $name { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl Mul<$name<f32>> for f32 {
+            type Output = $name<f32>;
+            fn mul(self, rhs: $name<f32>) -> $name<f32> {
+                rhs * self
+            }
+        }
+
+        impl Div<f32> for $name<f32> {
+            type Output = Self;
+            fn div(self, rhs: f32) -> Self {
+                $name { $($field: self.$field / rhs),+ }
+            }
+        }
+
+        impl Neg for $name<f32> {
+            type Output = Self;
+            fn neg(self) -> Self {
+                $name { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $name<f32> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $name<f32> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign<f32> for $name<f32> {
+            fn mul_assign(&mut self, rhs: f32) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl DivAssign<f32> for $name<f32> {
+            fn div_assign(&mut self, rhs: f32) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl ApproxEq for $name<f32> {
+            fn approx_eq_eps(&self, other: &Self, epsilon: f32) -> bool {
+                true $(&& self.$field.approx_eq_eps(&other.$field, epsilon))+
+            }
+        }
+    };
+}
+
+float_vector!(Vec2 { x, y });
+float_vector!(Vec3 { x, y, z });
+float_vector!(Vec4 { x, y, z, w });
+
+impl Vec3<f32> {
+    pub const X: Self = Vec3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    pub const Y: Self = Vec3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    pub const Z: Self = Vec3 {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+
+    /// perpendicular to both; in left-handed coordinates `X.cross(Y)` is `Z`
+    pub fn cross(self, rhs: Self) -> Self {
+        Vec3::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+}
@@ -1,15 +1,36 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::ptr::null_mut;
-use winapi::um::winuser::{DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE};
+use winapi::um::winuser::{
+    DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE, WM_QUIT,
+};
 
-pub struct Msg(pub MSG);
+use crate::event::WindowEvent;
 
-pub fn peek() -> Option<Msg> {
+thread_local! {
+    // filled by the window procedure, which also receives messages that are
+    // sent directly instead of going through the queue
+    static EVENTS: RefCell<VecDeque<WindowEvent>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// called by the window procedure for every event, after its handlers ran
+pub fn push(event: WindowEvent) {
+    EVENTS.with(|events| events.borrow_mut().push_back(event));
+}
+
+/// dispatches every pending message and appends the resulting events to
+/// `events`. returns false once the application should quit.
+pub fn pump(events: &mut Vec<WindowEvent>) -> bool {
     let mut msg: MSG = unsafe { std::mem::zeroed() };
-    if unsafe { PeekMessageW(&mut msg, null_mut(), 0, 0, PM_REMOVE) } != 0 {
-        unsafe { TranslateMessage(&mut msg) };
-        unsafe { DispatchMessageW(&mut msg) };
-        Some(Msg(msg))
-    } else {
-        None
+    let mut running = true;
+    while unsafe { PeekMessageW(&mut msg, null_mut(), 0, 0, PM_REMOVE) } != 0 {
+        if msg.message == WM_QUIT {
+            running = false;
+            break;
+        }
+        unsafe { TranslateMessage(&msg) };
+        unsafe { DispatchMessageW(&msg) };
     }
+    EVENTS.with(|queue| events.extend(queue.borrow_mut().drain(..)));
+    running
 }
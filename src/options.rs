@@ -0,0 +1,60 @@
+use crate::{adapter::AdapterPolicy, present::PresentConfig};
+
+/// command line options of the viewer
+#[derive(Debug, Default)]
+pub struct Options {
+    pub adapter: AdapterPolicy,
+    pub present: PresentConfig,
+    /// print the outputs of the adapter and exit
+    pub list_displays: bool,
+    /// write the input of the run to this file
+    pub record: Option<String>,
+    /// run with the input recorded in this file instead of live input
+    pub playback: Option<String>,
+}
+
+fn path<I: Iterator<Item = String>>(args: &mut I, name: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("missing value for {}", name))
+}
+
+fn number<I: Iterator<Item = String>>(args: &mut I, name: &str) -> Result<u32, String> {
+    let value = args
+        .next()
+        .ok_or_else(|| format!("missing value for {}", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, name))
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--adapter" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| "missing value for --adapter".to_string())?;
+                    options.adapter = value.parse()?;
+                }
+                "--sync-interval" => {
+                    options.present.sync_interval = number(&mut args, "--sync-interval")?
+                }
+                "--list-displays" => options.list_displays = true,
+                "--tearing" => options.present.tearing = true,
+                "--buffers" => options.present.buffer_count = number(&mut args, "--buffers")?,
+                "--max-latency" => {
+                    options.present.max_latency = number(&mut args, "--max-latency")?
+                }
+                "--record" => options.record = Some(path(&mut args, "--record")?),
+                "--playback" => options.playback = Some(path(&mut args, "--playback")?),
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+        if options.record.is_some() && options.playback.is_some() {
+            return Err("--record and --playback cannot be used together".to_string());
+        }
+        Ok(options)
+    }
+}
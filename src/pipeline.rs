@@ -0,0 +1,204 @@
+//! the rules for combining shader stages and primitive topologies, which
+//! pipeline creation and draw calls check before d3d12 sees them.
+
+pub const MAX_PATCH_CONTROL_POINTS: u32 = 32;
+
+/// primitive topology used by draw calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    PointList,
+    LineList,
+    LineStrip,
+    TriangleList,
+    TriangleStrip,
+    /// patch list with the given number of control points (1..=32)
+    PatchList(u32),
+}
+
+/// topology class that a pipeline state is built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopologyType {
+    Point,
+    Line,
+    Triangle,
+    Patch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Hull,
+    Domain,
+    Geometry,
+    Pixel,
+}
+
+impl Topology {
+    pub fn topology_type(self) -> TopologyType {
+        match self {
+            Topology::PointList => TopologyType::Point,
+            Topology::LineList | Topology::LineStrip => TopologyType::Line,
+            Topology::TriangleList | Topology::TriangleStrip => TopologyType::Triangle,
+            Topology::PatchList(_) => TopologyType::Patch,
+        }
+    }
+
+    pub fn validate(self) -> Result<(), String> {
+        match self {
+            Topology::PatchList(n) if n == 0 || n > MAX_PATCH_CONTROL_POINTS => Err(format!(
+                "invalid topology: patch list needs 1..={} control points, got {}",
+                MAX_PATCH_CONTROL_POINTS, n
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// checks that the set of shader stages can be combined with `topology`.
+/// hull and domain shaders come as a pair, and are used if and only if
+/// the topology is a patch list.
+pub fn validate_stages(stages: &[ShaderStage], topology: Topology) -> Result<(), String> {
+    topology.validate()?;
+    let has = |stage| stages.contains(&stage);
+    if !has(ShaderStage::Vertex) {
+        return Err("invalid pipeline: vertex shader is required".to_string());
+    }
+    match (has(ShaderStage::Hull), has(ShaderStage::Domain)) {
+        (true, false) => {
+            return Err("invalid pipeline: hull shader without domain shader".to_string())
+        }
+        (false, true) => {
+            return Err("invalid pipeline: domain shader without hull shader".to_string())
+        }
+        (true, true) if topology.topology_type() != TopologyType::Patch => {
+            return Err(format!(
+                "invalid pipeline: tessellation requires a patch list topology, got {:?}",
+                topology
+            ))
+        }
+        (false, false) if topology.topology_type() == TopologyType::Patch => {
+            return Err(format!(
+                "invalid pipeline: {:?} requires hull and domain shaders",
+                topology
+            ))
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// checks that `topology` can be drawn with a pipeline built for `topology_type`
+pub fn check_draw_topology(topology_type: TopologyType, topology: Topology) -> Result<(), String> {
+    topology.validate()?;
+    if topology.topology_type() == topology_type {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid draw: {:?} does not match pipeline topology type {:?}",
+            topology, topology_type
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShaderStage::*;
+    use super::*;
+
+    const NON_PATCH: [Topology; 5] = [
+        Topology::PointList,
+        Topology::LineList,
+        Topology::LineStrip,
+        Topology::TriangleList,
+        Topology::TriangleStrip,
+    ];
+
+    #[test]
+    fn valid_stage_combinations() {
+        for &topology in NON_PATCH.iter() {
+            for stages in [
+                &[Vertex][..],
+                &[Vertex, Pixel],
+                &[Vertex, Geometry],
+                &[Vertex, Geometry, Pixel],
+            ] {
+                assert_eq!(validate_stages(stages, topology), Ok(()));
+            }
+        }
+        for n in 1..=MAX_PATCH_CONTROL_POINTS {
+            let topology = Topology::PatchList(n);
+            for stages in [
+                &[Vertex, Hull, Domain][..],
+                &[Vertex, Hull, Domain, Pixel],
+                &[Vertex, Hull, Domain, Geometry, Pixel],
+            ] {
+                assert_eq!(validate_stages(stages, topology), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_stage_combinations() {
+        let error =
+            |stages: &[ShaderStage], topology| validate_stages(stages, topology).unwrap_err();
+        for &topology in NON_PATCH.iter().chain([Topology::PatchList(3)].iter()) {
+            assert_eq!(
+                error(&[Vertex, Hull, Pixel], topology),
+                "invalid pipeline: hull shader without domain shader"
+            );
+            assert_eq!(
+                error(&[Vertex, Domain, Pixel], topology),
+                "invalid pipeline: domain shader without hull shader"
+            );
+            assert_eq!(
+                error(&[Pixel], topology),
+                "invalid pipeline: vertex shader is required"
+            );
+        }
+        for &topology in NON_PATCH.iter() {
+            assert!(error(&[Vertex, Hull, Domain], topology)
+                .starts_with("invalid pipeline: tessellation requires a patch list topology"));
+        }
+        assert_eq!(
+            error(&[Vertex, Geometry, Pixel], Topology::PatchList(3)),
+            "invalid pipeline: PatchList(3) requires hull and domain shaders"
+        );
+        for n in [0, MAX_PATCH_CONTROL_POINTS + 1] {
+            assert_eq!(
+                error(&[Vertex, Hull, Domain], Topology::PatchList(n)),
+                format!(
+                    "invalid topology: patch list needs 1..=32 control points, got {}",
+                    n
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn draw_topology() {
+        assert_eq!(
+            check_draw_topology(TopologyType::Triangle, Topology::TriangleStrip),
+            Ok(())
+        );
+        assert_eq!(
+            check_draw_topology(TopologyType::Line, Topology::LineStrip),
+            Ok(())
+        );
+        assert_eq!(
+            check_draw_topology(TopologyType::Patch, Topology::PatchList(32)),
+            Ok(())
+        );
+        assert_eq!(
+            check_draw_topology(TopologyType::Triangle, Topology::PatchList(3)),
+            Err(
+                "invalid draw: PatchList(3) does not match pipeline topology type Triangle"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            check_draw_topology(TopologyType::Point, Topology::LineList),
+            Err("invalid draw: LineList does not match pipeline topology type Point".to_string())
+        );
+        assert!(check_draw_topology(TopologyType::Patch, Topology::PatchList(0)).is_err());
+    }
+}
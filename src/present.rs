@@ -0,0 +1,200 @@
+//! how frames are presented: vsync, tearing, back buffers and latency.
+//! `direct3d::surface` applies a config to its swapchain.
+
+pub const MIN_BUFFER_COUNT: u32 = 2;
+pub const MAX_BUFFER_COUNT: u32 = 3;
+pub const MAX_SYNC_INTERVAL: u32 = 4;
+/// the most frames dxgi lets the cpu queue ahead
+pub const MAX_FRAME_LATENCY: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresentConfig {
+    /// number of vblanks to wait for, 0 for no vsync
+    pub sync_interval: u32,
+    /// present without waiting for vblank even on variable refresh displays
+    pub tearing: bool,
+    pub buffer_count: u32,
+    /// frames the cpu may run ahead of the display
+    pub max_latency: u32,
+}
+
+impl Default for PresentConfig {
+    fn default() -> Self {
+        PresentConfig {
+            sync_interval: 1,
+            tearing: false,
+            buffer_count: 2,
+            max_latency: 1,
+        }
+    }
+}
+
+/// what has to be redone to go from one config to another.
+/// each variant includes the work of the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reconfigure {
+    /// only the arguments to `Present` change
+    Nothing,
+    SetLatency,
+    /// the number of back buffers changes
+    ResizeBuffers,
+    /// the swapchain flags change, which `ResizeBuffers` cannot do
+    Recreate,
+}
+
+impl PresentConfig {
+    /// uncapped frame rate, tearing if the display allows it
+    pub fn unlocked() -> Self {
+        PresentConfig {
+            sync_interval: 0,
+            tearing: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self, tearing_supported: bool) -> Result<(), String> {
+        if self.sync_interval > MAX_SYNC_INTERVAL {
+            return Err(format!(
+                "invalid sync interval {}: must be at most {}",
+                self.sync_interval, MAX_SYNC_INTERVAL
+            ));
+        }
+        if self.buffer_count < MIN_BUFFER_COUNT || self.buffer_count > MAX_BUFFER_COUNT {
+            return Err(format!(
+                "invalid buffer count {}: must be {} to {}",
+                self.buffer_count, MIN_BUFFER_COUNT, MAX_BUFFER_COUNT
+            ));
+        }
+        if self.max_latency == 0 || self.max_latency > MAX_FRAME_LATENCY {
+            return Err(format!(
+                "invalid max latency {}: must be 1 to {}",
+                self.max_latency, MAX_FRAME_LATENCY
+            ));
+        }
+        if self.tearing && !tearing_supported {
+            return Err("tearing is not supported by this system".to_string());
+        }
+        if self.tearing && self.sync_interval != 0 {
+            return Err("tearing requires a sync interval of 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// whether `Present` may pass `DXGI_PRESENT_ALLOW_TEARING`, which
+    /// exclusive fullscreen does not accept
+    pub fn present_with_tearing(&self, exclusive: bool) -> bool {
+        self.tearing && self.sync_interval == 0 && !exclusive
+    }
+
+    pub fn reconfigure(&self, to: &PresentConfig) -> Reconfigure {
+        if self.tearing != to.tearing {
+            Reconfigure::Recreate
+        } else if self.buffer_count != to.buffer_count {
+            Reconfigure::ResizeBuffers
+        } else if self.max_latency != to.max_latency {
+            Reconfigure::SetLatency
+        } else {
+            Reconfigure::Nothing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(PresentConfig::default().validate(false), Ok(()));
+        assert_eq!(PresentConfig::unlocked().validate(true), Ok(()));
+    }
+
+    #[test]
+    fn tearing_needs_support_and_no_vsync() {
+        assert_eq!(
+            PresentConfig::unlocked().validate(false),
+            Err("tearing is not supported by this system".to_string())
+        );
+        let vsync = PresentConfig {
+            tearing: true,
+            ..PresentConfig::default()
+        };
+        assert_eq!(
+            vsync.validate(true),
+            Err("tearing requires a sync interval of 0".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        let base = PresentConfig::default();
+        let invalid = [
+            PresentConfig {
+                sync_interval: MAX_SYNC_INTERVAL + 1,
+                ..base
+            },
+            PresentConfig {
+                buffer_count: MIN_BUFFER_COUNT - 1,
+                ..base
+            },
+            PresentConfig {
+                buffer_count: MAX_BUFFER_COUNT + 1,
+                ..base
+            },
+            PresentConfig {
+                max_latency: 0,
+                ..base
+            },
+            PresentConfig {
+                max_latency: MAX_FRAME_LATENCY + 1,
+                ..base
+            },
+        ];
+        for config in invalid.iter() {
+            assert!(config.validate(true).is_err(), "{:?}", config);
+        }
+        let limits = PresentConfig {
+            sync_interval: MAX_SYNC_INTERVAL,
+            buffer_count: MAX_BUFFER_COUNT,
+            max_latency: MAX_FRAME_LATENCY,
+            ..base
+        };
+        assert_eq!(limits.validate(false), Ok(()));
+    }
+
+    #[test]
+    fn reconfigures_as_little_as_needed() {
+        let from = PresentConfig::default();
+        let same = from;
+        let interval = PresentConfig {
+            sync_interval: 2,
+            ..from
+        };
+        let latency = PresentConfig {
+            max_latency: 3,
+            ..from
+        };
+        let buffers = PresentConfig {
+            buffer_count: 3,
+            ..latency
+        };
+        let tearing = PresentConfig {
+            buffer_count: 3,
+            ..PresentConfig::unlocked()
+        };
+        assert_eq!(from.reconfigure(&same), Reconfigure::Nothing);
+        assert_eq!(from.reconfigure(&interval), Reconfigure::Nothing);
+        assert_eq!(from.reconfigure(&latency), Reconfigure::SetLatency);
+        assert_eq!(from.reconfigure(&buffers), Reconfigure::ResizeBuffers);
+        assert_eq!(from.reconfigure(&tearing), Reconfigure::Recreate);
+        assert_eq!(tearing.reconfigure(&from), Reconfigure::Recreate);
+    }
+
+    #[test]
+    fn exclusive_fullscreen_never_tears() {
+        let unlocked = PresentConfig::unlocked();
+        assert!(unlocked.present_with_tearing(false));
+        assert!(!unlocked.present_with_tearing(true));
+        assert!(!PresentConfig::default().present_with_tearing(false));
+    }
+}
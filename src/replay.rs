@@ -0,0 +1,369 @@
+//! recording of the input a run received and of the fixed steps it took, so
+//! that the run can be repeated exactly, e.g. to reproduce a bug or to
+//! benchmark the same frames twice. `format` describes the file.
+
+pub mod format;
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::event::{Event, WindowEvent};
+use crate::timestep::Frame;
+
+/// what one iteration of the main loop consumed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    /// when the frame started, on the clock of the recording run
+    pub time: Duration,
+    pub steps: u32,
+    pub alpha: f32,
+    pub events: Vec<WindowEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// the fixed update step the recording was made with
+    pub step: Duration,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    pub fn new(step: Duration) -> Self {
+        Recording {
+            step: step,
+            frames: vec![],
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Recording, String> {
+        format::parse(text)
+    }
+
+    pub fn load(path: &str) -> Result<Recording, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed: read {}: {}", path, err))?;
+        Recording::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_string())
+            .map_err(|err| format!("failed: write {}: {}", path, err))
+    }
+
+    pub fn push(&mut self, time: Duration, events: &[WindowEvent], frame: &Frame) {
+        self.frames.push(RecordedFrame {
+            time: time,
+            steps: frame.steps,
+            alpha: frame.alpha,
+            events: events.to_vec(),
+        });
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format::write(f, self)
+    }
+}
+
+/// hands out the frames of a recording in order
+#[derive(Debug, Clone)]
+pub struct Playback {
+    recording: Recording,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Playback {
+            recording: recording,
+            next: 0,
+        }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.recording.step
+    }
+
+    pub fn next_frame(&mut self) -> Option<&RecordedFrame> {
+        let frame = self.recording.frames.get(self.next)?;
+        self.next += 1;
+        Some(frame)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.recording.frames.len() - self.next
+    }
+}
+
+/// where the main loop takes its input and steps from
+#[derive(Debug, Clone)]
+pub enum Replay {
+    /// live input, paced by the clock
+    Live,
+    /// live input, appended to the recording as it is handled
+    Record(Recording),
+    /// recorded input and steps. live input is ignored, except for close
+    /// requests and destroyed windows so that the run can still be ended;
+    /// a quit from the platform ends it too.
+    Playback(Playback),
+}
+
+/// how the main loop runs one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    /// as the clock says
+    Live,
+    /// as recorded
+    Recorded(Frame),
+    /// the recording is over
+    Finished,
+}
+
+impl Replay {
+    /// a recording or playback made with another step would not repeat
+    pub fn check_step(&self, step: Duration) -> Result<(), String> {
+        let recorded = match *self {
+            Replay::Live => return Ok(()),
+            Replay::Record(ref recording) => recording.step,
+            Replay::Playback(ref playback) => playback.step(),
+        };
+        if recorded != step {
+            return Err(format!(
+                "recording step {:?} does not match the update step {:?}",
+                recorded, step
+            ));
+        }
+        Ok(())
+    }
+
+    /// in playback, replaces the live `events` with those of the next
+    /// recorded frame
+    pub fn schedule(&mut self, events: &mut Vec<WindowEvent>) -> Schedule {
+        let playback = match *self {
+            Replay::Playback(ref mut playback) => playback,
+            _ => return Schedule::Live,
+        };
+        events.retain(|event| matches!(event.event, Event::CloseRequested | Event::Destroyed));
+        match playback.next_frame() {
+            Some(frame) => {
+                events.extend(frame.events.iter().cloned());
+                Schedule::Recorded(Frame {
+                    steps: frame.steps,
+                    alpha: frame.alpha,
+                    dropped: Duration::from_secs(0),
+                })
+            }
+            None => Schedule::Finished,
+        }
+    }
+
+    /// while recording, appends the frame the loop is about to run
+    pub fn record(&mut self, time: Duration, events: &[WindowEvent], frame: &Frame) {
+        if let Replay::Record(ref mut recording) = *self {
+            recording.push(time, events, frame);
+        }
+    }
+
+    pub fn recording(&self) -> Option<&Recording> {
+        match *self {
+            Replay::Record(ref recording) => Some(recording),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::headless::{HeadlessPlatform, HeadlessRenderer};
+    use crate::app::{self, App, Context};
+    use crate::clock::FakeClock;
+    use crate::timestep::FixedTimestep;
+    use crate::window::id::WindowId;
+
+    type HeadlessContext = Context<HeadlessPlatform, HeadlessRenderer>;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    /// logs what it sees and takes the next of `frame_times` per frame
+    #[derive(Default)]
+    struct Logger {
+        log: Vec<String>,
+        clock: FakeClock,
+        frame_times: Vec<u64>,
+        frames: usize,
+    }
+
+    impl App<HeadlessPlatform, HeadlessRenderer> for Logger {
+        fn event(
+            &mut self,
+            context: &mut HeadlessContext,
+            _window: WindowId,
+            event: &Event,
+        ) -> Result<(), String> {
+            self.log.push(format!("event {:?}", event));
+            if *event == Event::CloseRequested {
+                context.quit();
+            }
+            Ok(())
+        }
+
+        fn update(&mut self, context: &mut HeadlessContext, _dt: Duration) -> Result<(), String> {
+            self.log
+                .push(format!("update {:?}", context.input.mouse_position()));
+            Ok(())
+        }
+
+        fn render(&mut self, _context: &mut HeadlessContext, alpha: f32) -> Result<(), String> {
+            self.log.push(format!("render {}", alpha));
+            let millis = self.frame_times[self.frames % self.frame_times.len()];
+            self.frames += 1;
+            self.clock.advance(Duration::from_millis(millis));
+            Ok(())
+        }
+    }
+
+    fn run(
+        platform: HeadlessPlatform,
+        app: &mut Logger,
+        replay: &mut Replay,
+    ) -> Result<(), String> {
+        let renderer = HeadlessRenderer::new(&platform);
+        let mut clock = app.clock.clone();
+        app::run_with_replay(
+            &mut Context::new(platform, renderer),
+            app,
+            &mut clock,
+            FixedTimestep::new(STEP).unwrap(),
+            replay,
+        )
+    }
+
+    /// records a run of 20 mouse moves and a close, and what the app saw
+    fn record() -> (Recording, Vec<String>) {
+        let mut platform = HeadlessPlatform::new(64, 64);
+        for i in 0..20 {
+            platform.push_frame(vec![Event::MouseMove { x: i, y: i * 2 }]);
+        }
+        platform.push_frame(vec![Event::CloseRequested]);
+        let mut app = Logger {
+            frame_times: vec![7, 13, 25, 3],
+            ..Logger::default()
+        };
+        let mut replay = Replay::Record(Recording::new(STEP));
+        run(platform, &mut app, &mut replay).unwrap();
+        (replay.recording().unwrap().clone(), app.log)
+    }
+
+    #[test]
+    fn playback_repeats_the_recorded_run() {
+        let (recording, log) = record();
+        let path = std::env::temp_dir().join(format!("replay-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        recording.save(path).unwrap();
+        let loaded = Recording::load(path);
+        std::fs::remove_file(path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.frames.len(), 21);
+
+        // at another frame pace, with live input that is not used
+        let mut platform = HeadlessPlatform::new(64, 64);
+        for _ in 0..100 {
+            platform.push_frame(vec![Event::MouseMove { x: 999, y: 999 }]);
+        }
+        let mut player = Logger {
+            frame_times: vec![1],
+            ..Logger::default()
+        };
+        let mut replay = Replay::Playback(Playback::new(loaded));
+        run(platform, &mut player, &mut replay).unwrap();
+        assert_eq!(player.log, log);
+    }
+
+    #[test]
+    fn playback_needs_the_recorded_step() {
+        let mut replay = Replay::Playback(Playback::new(Recording::new(STEP * 2)));
+        let mut app = Logger {
+            frame_times: vec![1],
+            ..Logger::default()
+        };
+        assert_eq!(
+            run(HeadlessPlatform::new(1, 1), &mut app, &mut replay),
+            Err("recording step 20ms does not match the update step 10ms".to_string())
+        );
+        assert!(app.log.is_empty());
+    }
+
+    #[test]
+    fn playback_keeps_live_close_and_destroy() {
+        let live = WindowId(7);
+        let mut events = vec![
+            WindowEvent::new(live, Event::MouseMove { x: 1, y: 1 }),
+            WindowEvent::new(live, Event::CloseRequested),
+            WindowEvent::new(live, Event::Focus(false)),
+            WindowEvent::new(live, Event::Destroyed),
+        ];
+        let mut replay = Replay::Playback(Playback::new(record().0));
+        let recorded = match replay {
+            Replay::Playback(ref playback) => playback.recording.frames[0].events.clone(),
+            _ => unreachable!(),
+        };
+        assert!(matches!(
+            replay.schedule(&mut events),
+            Schedule::Recorded(_)
+        ));
+        let mut expected = vec![
+            WindowEvent::new(live, Event::CloseRequested),
+            WindowEvent::new(live, Event::Destroyed),
+        ];
+        expected.extend(recorded);
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn live_quit_ends_a_playback() {
+        let (recording, _) = record();
+        // the live window is destroyed, then the platform quits, which ends
+        // the run in its third frame
+        let mut platform = HeadlessPlatform::new(64, 64);
+        let primary = platform.primary();
+        platform.push_frame(vec![]);
+        platform.push_frame(vec![Event::Destroyed]);
+        let renderer = HeadlessRenderer::new(&platform);
+        let mut context = Context::new(platform, renderer);
+        let mut app = Logger {
+            frame_times: vec![1],
+            ..Logger::default()
+        };
+        let mut clock = app.clock.clone();
+        let mut replay = Replay::Playback(Playback::new(recording));
+        app::run_with_replay(
+            &mut context,
+            &mut app,
+            &mut clock,
+            FixedTimestep::new(STEP).unwrap(),
+            &mut replay,
+        )
+        .unwrap();
+        assert!(app.log.contains(&"event Destroyed".to_string()));
+        assert!(context.renderer.surfaces.get(primary).is_none());
+        match replay {
+            Replay::Playback(ref playback) => assert_eq!(playback.remaining(), 18),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn live_and_record_schedule_live() {
+        let mut events = vec![WindowEvent::new(WindowId(1), Event::Focus(true))];
+        let mut live = Replay::Live;
+        assert_eq!(live.schedule(&mut events), Schedule::Live);
+        assert_eq!(live.check_step(STEP * 3), Ok(()));
+        let mut record = Replay::Record(Recording::new(STEP));
+        assert_eq!(record.schedule(&mut events), Schedule::Live);
+        assert_eq!(events.len(), 1);
+        assert!(live.recording().is_none());
+    }
+}
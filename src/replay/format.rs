@@ -0,0 +1,472 @@
+//! recordings are text, one record per line:
+//!
+//! ```text
+//! replay 1
+//! step 16666666
+//! frame 0 0 0
+//! frame 16702100 1 0.0021
+//! window 1
+//! key-down 65 30 0 0
+//! mouse-move 120 87
+//! ```
+//!
+//! times are in nanoseconds and flags are 0 or 1. the events of a frame
+//! follow its `frame` line in the order the loop handled them, each for the
+//! window of the last `window` line. floats are written in their shortest
+//! form that reads back to the same value. paths of dropped files are
+//! utf-8 with `%`, whitespace and control characters written as `%xx`; an
+//! empty path is a `file-dropped` line without a field.
+//!
+//! version 1 had no `window` lines; its events are for the first window.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::{FromStr, SplitWhitespace};
+use std::time::Duration;
+
+use super::{RecordedFrame, Recording};
+use crate::event::{Event, MouseButton, WindowEvent};
+use crate::window::id::WindowId;
+
+pub const VERSION: u32 = 2;
+
+fn button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+    }
+}
+
+fn escape_path(path: &Path) -> String {
+    let mut escaped = String::new();
+    for c in path.to_string_lossy().chars() {
+        if c == '%' || c.is_whitespace() || c.is_control() {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                escaped.push_str(&format!("%{:02x}", byte));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+fn unescape_path(word: &str) -> Result<PathBuf, String> {
+    let invalid = || format!("invalid path '{}'", word);
+    let mut bytes = Vec::with_capacity(word.len());
+    let mut rest = word.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|_| invalid())
+}
+
+fn write_event(f: &mut fmt::Formatter, event: &Event) -> fmt::Result {
+    match *event {
+        Event::Resized { width, height } => writeln!(f, "resized {} {}", width, height),
+        Event::Moved { x, y } => writeln!(f, "moved {} {}", x, y),
+        Event::Focus(focused) => writeln!(f, "focus {}", focused as u8),
+        Event::KeyDown {
+            key,
+            scancode,
+            repeat,
+            alt,
+        } => writeln!(
+            f,
+            "key-down {} {} {} {}",
+            key, scancode, repeat as u8, alt as u8
+        ),
+        Event::KeyUp { key, scancode } => writeln!(f, "key-up {} {}", key, scancode),
+        Event::Char(c) => writeln!(f, "char {}", c as u32),
+        Event::MouseMove { x, y } => writeln!(f, "mouse-move {} {}", x, y),
+        Event::RawMouseMotion { dx, dy } => writeln!(f, "raw-mouse-motion {} {}", dx, dy),
+        Event::MouseButton {
+            button,
+            pressed,
+            x,
+            y,
+        } => writeln!(
+            f,
+            "mouse-button {} {} {} {}",
+            button_name(button),
+            pressed as u8,
+            x,
+            y
+        ),
+        Event::Wheel { delta, horizontal } => {
+            writeln!(f, "wheel {} {}", delta, horizontal as u8)
+        }
+        Event::DpiChanged { dpi } => writeln!(f, "dpi-changed {}", dpi),
+        Event::FileDropped(ref path) if path.as_os_str().is_empty() => {
+            writeln!(f, "file-dropped")
+        }
+        Event::FileDropped(ref path) => writeln!(f, "file-dropped {}", escape_path(path)),
+        Event::CloseRequested => writeln!(f, "close-requested"),
+        Event::Destroyed => writeln!(f, "destroyed"),
+    }
+}
+
+pub fn write(f: &mut fmt::Formatter, recording: &Recording) -> fmt::Result {
+    writeln!(f, "replay {}", VERSION)?;
+    writeln!(f, "step {}", recording.step.as_nanos())?;
+    let mut window = None;
+    for frame in recording.frames.iter() {
+        writeln!(
+            f,
+            "frame {} {} {}",
+            frame.time.as_nanos(),
+            frame.steps,
+            frame.alpha
+        )?;
+        for event in frame.events.iter() {
+            if window != Some(event.window) {
+                writeln!(f, "window {}", event.window.0)?;
+                window = Some(event.window);
+            }
+            write_event(f, &event.event)?;
+        }
+    }
+    Ok(())
+}
+
+/// the fields of a line after its first word
+struct Fields<'a> {
+    words: SplitWhitespace<'a>,
+}
+
+impl<'a> Fields<'a> {
+    fn next<T: FromStr>(&mut self) -> Result<T, String> {
+        let word = self
+            .words
+            .next()
+            .ok_or_else(|| "missing field".to_string())?;
+        word.parse()
+            .map_err(|_| format!("invalid field '{}'", word))
+    }
+
+    fn flag(&mut self) -> Result<bool, String> {
+        match self.next::<u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(format!("invalid flag '{}'", value)),
+        }
+    }
+
+    fn button(&mut self) -> Result<MouseButton, String> {
+        let name: String = self.next()?;
+        match name.as_str() {
+            "left" => Ok(MouseButton::Left),
+            "right" => Ok(MouseButton::Right),
+            "middle" => Ok(MouseButton::Middle),
+            "x1" => Ok(MouseButton::X1),
+            "x2" => Ok(MouseButton::X2),
+            _ => Err(format!("unknown mouse button '{}'", name)),
+        }
+    }
+
+    fn end(mut self) -> Result<(), String> {
+        match self.words.next() {
+            Some(word) => Err(format!("unexpected '{}'", word)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_event(kind: &str, fields: &mut Fields) -> Result<Event, String> {
+    Ok(match kind {
+        "resized" => Event::Resized {
+            width: fields.next()?,
+            height: fields.next()?,
+        },
+        "moved" => Event::Moved {
+            x: fields.next()?,
+            y: fields.next()?,
+        },
+        "focus" => Event::Focus(fields.flag()?),
+        "key-down" => Event::KeyDown {
+            key: fields.next()?,
+            scancode: fields.next()?,
+            repeat: fields.flag()?,
+            alt: fields.flag()?,
+        },
+        "key-up" => Event::KeyUp {
+            key: fields.next()?,
+            scancode: fields.next()?,
+        },
+        "char" => {
+            let code: u32 = fields.next()?;
+            Event::Char(std::char::from_u32(code).ok_or_else(|| format!("invalid char {}", code))?)
+        }
+        "mouse-move" => Event::MouseMove {
+            x: fields.next()?,
+            y: fields.next()?,
+        },
+        "raw-mouse-motion" => Event::RawMouseMotion {
+            dx: fields.next()?,
+            dy: fields.next()?,
+        },
+        "mouse-button" => Event::MouseButton {
+            button: fields.button()?,
+            pressed: fields.flag()?,
+            x: fields.next()?,
+            y: fields.next()?,
+        },
+        "wheel" => Event::Wheel {
+            delta: fields.next()?,
+            horizontal: fields.flag()?,
+        },
+        "dpi-changed" => Event::DpiChanged {
+            dpi: fields.next()?,
+        },
+        "file-dropped" => match fields.words.next() {
+            Some(word) => Event::FileDropped(unescape_path(word)?),
+            None => Event::FileDropped(PathBuf::new()),
+        },
+        "close-requested" => Event::CloseRequested,
+        "destroyed" => Event::Destroyed,
+        _ => return Err(format!("unknown record '{}'", kind)),
+    })
+}
+
+#[derive(Default)]
+struct Parser {
+    version: Option<u32>,
+    step: Option<Duration>,
+    window: Option<WindowId>,
+    frames: Vec<RecordedFrame>,
+}
+
+fn parse_line(parser: &mut Parser, line: &str) -> Result<(), String> {
+    let mut words = line.split_whitespace();
+    let kind = words.next().unwrap_or("");
+    let mut fields = Fields { words: words };
+    if parser.version.is_none() {
+        if kind != "replay" {
+            return Err("expected 'replay <version>'".to_string());
+        }
+        let version = fields.next()?;
+        if version == 0 || version > VERSION {
+            return Err(format!("unsupported version {}", version));
+        }
+        if version == 1 {
+            parser.window = Some(WindowId(1));
+        }
+        parser.version = Some(version);
+        return fields.end();
+    }
+    match kind {
+        "step" => {
+            if parser.step.is_some() {
+                return Err("duplicate step".to_string());
+            }
+            let nanos = fields.next()?;
+            if nanos == 0 {
+                return Err("step must not be zero".to_string());
+            }
+            parser.step = Some(Duration::from_nanos(nanos));
+        }
+        "frame" => {
+            if parser.step.is_none() {
+                return Err("expected 'step' before the first frame".to_string());
+            }
+            parser.frames.push(RecordedFrame {
+                time: Duration::from_nanos(fields.next()?),
+                steps: fields.next()?,
+                alpha: fields.next()?,
+                events: vec![],
+            });
+        }
+        "window" => parser.window = Some(WindowId(fields.next()?)),
+        _ => {
+            let event = parse_event(kind, &mut fields)?;
+            let window = parser
+                .window
+                .ok_or_else(|| "expected 'window' before the first event".to_string())?;
+            match parser.frames.last_mut() {
+                Some(frame) => frame.events.push(WindowEvent::new(window, event)),
+                None => return Err("event before the first frame".to_string()),
+            }
+        }
+    }
+    fields.end()
+}
+
+pub fn parse(text: &str) -> Result<Recording, String> {
+    let mut parser = Parser::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        parse_line(&mut parser, line).map_err(|err| format!("line {}: {}", number + 1, err))?;
+    }
+    if parser.version.is_none() {
+        return Err("empty recording".to_string());
+    }
+    match parser.step {
+        Some(step) => Ok(Recording {
+            step: step,
+            frames: parser.frames,
+        }),
+        None => Err("missing step".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestep::Frame;
+
+    fn every_event() -> Vec<Event> {
+        vec![
+            Event::Resized {
+                width: 3,
+                height: 4,
+            },
+            Event::Moved { x: -1, y: 2 },
+            Event::Focus(true),
+            Event::KeyDown {
+                key: 65,
+                scancode: 0xe01e,
+                repeat: true,
+                alt: false,
+            },
+            Event::KeyUp {
+                key: 65,
+                scancode: 30,
+            },
+            Event::Char('😀'),
+            Event::MouseMove { x: 5, y: -6 },
+            Event::RawMouseMotion { dx: -7, dy: 8 },
+            Event::MouseButton {
+                button: MouseButton::X2,
+                pressed: true,
+                x: 1,
+                y: 2,
+            },
+            Event::Wheel {
+                delta: -0.333_333,
+                horizontal: true,
+            },
+            Event::DpiChanged { dpi: 144 },
+            Event::FileDropped(PathBuf::from("C:\\my files\\100%\tü.txt")),
+            Event::FileDropped(PathBuf::new()),
+            Event::CloseRequested,
+            Event::Destroyed,
+        ]
+    }
+
+    fn frame(steps: u32, alpha: f32) -> Frame {
+        Frame {
+            steps: steps,
+            alpha: alpha,
+            dropped: Duration::from_secs(0),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        // the events alternate between windows every few records
+        let events: Vec<WindowEvent> = every_event()
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| WindowEvent::new(WindowId(1 + (i as u32 / 4) % 2), event))
+            .collect();
+        let mut recording = Recording::new(Duration::from_nanos(16_666_667));
+        recording.push(
+            Duration::from_nanos(123_456_789),
+            &events,
+            &frame(2, 0.123_456_7),
+        );
+        recording.push(Duration::from_nanos(5), &[], &frame(0, 0.0));
+        recording.push(
+            Duration::from_secs(3600),
+            &events[..1],
+            &frame(1, 1.0 / 3.0),
+        );
+        let text = recording.to_string();
+        assert_eq!(Recording::parse(&text), Ok(recording));
+        assert!(text.contains("file-dropped C:\\my%20files\\100%25%09ü.txt\n"));
+        assert!(text.contains("file-dropped\n"));
+    }
+
+    #[test]
+    fn reads_version_1() {
+        let recording = Recording::parse("replay 1\nstep 10\nframe 0 0 0\nfocus 1").unwrap();
+        assert_eq!(
+            recording.frames[0].events,
+            vec![WindowEvent::new(WindowId(1), Event::Focus(true))]
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let errors = [
+            ("", "empty recording"),
+            ("replay 2", "missing step"),
+            ("step 10", "line 1: expected 'replay <version>'"),
+            ("replay 3", "line 1: unsupported version 3"),
+            ("replay 0", "line 1: unsupported version 0"),
+            (
+                "replay 1\nframe 0 0 0",
+                "line 2: expected 'step' before the first frame",
+            ),
+            ("replay 2\nstep 0", "line 2: step must not be zero"),
+            ("replay 2\nstep 10\nstep 10", "line 3: duplicate step"),
+            ("replay 1\nstep 10\nframe 0 1 0 9", "line 3: unexpected '9'"),
+            (
+                "replay 2\nstep 10\nframe 0 x 0",
+                "line 3: invalid field 'x'",
+            ),
+            (
+                "replay 2\nstep 10\nwindow 1\nkey-up 1 2",
+                "line 4: event before the first frame",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nfocus 1",
+                "line 4: expected 'window' before the first event",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nwindow 1\nfocus 2",
+                "line 5: invalid flag '2'",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nwindow 1\nmouse-button up 1 0 0",
+                "line 5: unknown mouse button 'up'",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nwindow 1\nchar 55296",
+                "line 5: invalid char 55296",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nwindow 1\nfile-dropped a%2",
+                "line 5: invalid path 'a%2'",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nwindow 1\nfile-dropped %ff",
+                "line 5: invalid path '%ff'",
+            ),
+            (
+                "replay 2\nstep 10\nframe 0 0 0\nwindow 1\nteleport 1 2",
+                "line 5: unknown record 'teleport'",
+            ),
+        ];
+        for (text, error) in errors.iter() {
+            assert_eq!(Recording::parse(text), Err(error.to_string()), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! decides when the swapchain should follow the window's client size.
+//! sizes are fed in every frame; a new size is applied only after it has
+//! stayed the same for the debounce period, so that drag-resizing does not
+//! recreate the back buffers on every frame.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::window::id::WindowId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeAction {
+    None,
+    /// the window was minimized; stop rendering
+    Suspend,
+    /// the window was restored; rendering can continue
+    Resume,
+    Resize {
+        width: u32,
+        height: u32,
+    },
+}
+
+pub struct Resizer {
+    width: u32,
+    height: u32,
+    pending: Option<(u32, u32, Duration)>,
+    suspended: bool,
+    debounce: Duration,
+}
+
+impl Resizer {
+    pub fn new(width: u32, height: u32, debounce: Duration) -> Self {
+        Resizer {
+            width: width,
+            height: height,
+            pending: None,
+            suspended: false,
+            debounce: debounce,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// reports the client size seen at time `now`
+    pub fn observe(&mut self, width: u32, height: u32, now: Duration) -> ResizeAction {
+        if width == 0 || height == 0 {
+            self.pending = None;
+            if self.suspended {
+                return ResizeAction::None;
+            }
+            self.suspended = true;
+            return ResizeAction::Suspend;
+        }
+        let resumed = self.suspended;
+        self.suspended = false;
+
+        if (width, height) == (self.width, self.height) {
+            self.pending = None;
+        } else {
+            match self.pending {
+                Some((w, h, since)) if (w, h) == (width, height) => {
+                    if now >= since + self.debounce {
+                        self.pending = None;
+                        self.width = width;
+                        self.height = height;
+                        return ResizeAction::Resize { width, height };
+                    }
+                }
+                _ => self.pending = Some((width, height, now)),
+            }
+        }
+        if resumed {
+            ResizeAction::Resume
+        } else {
+            ResizeAction::None
+        }
+    }
+}
+
+/// a `Resizer` for each window
+pub struct Resizers {
+    resizers: BTreeMap<WindowId, Resizer>,
+    debounce: Duration,
+}
+
+impl Resizers {
+    pub fn new(debounce: Duration) -> Self {
+        Resizers {
+            resizers: BTreeMap::new(),
+            debounce: debounce,
+        }
+    }
+
+    /// `Resizer::observe` for `window`. a window seen for the first time
+    /// starts out at the size it has.
+    pub fn observe(
+        &mut self,
+        window: WindowId,
+        width: u32,
+        height: u32,
+        now: Duration,
+    ) -> ResizeAction {
+        let debounce = self.debounce;
+        self.resizers
+            .entry(window)
+            .or_insert_with(|| Resizer::new(width, height, debounce))
+            .observe(width, height, now)
+    }
+
+    pub fn remove(&mut self, window: WindowId) {
+        self.resizers.remove(&window);
+    }
+
+    pub fn is_suspended(&self, window: WindowId) -> bool {
+        self.resizers
+            .get(&window)
+            .is_some_and(|resizer| resizer.is_suspended())
+    }
+
+    /// true when there is nothing to draw into: every window is minimized.
+    /// false before any window has been observed.
+    pub fn all_suspended(&self) -> bool {
+        !self.resizers.is_empty() && self.resizers.values().all(|resizer| resizer.is_suspended())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEBOUNCE: Duration = Duration::from_millis(100);
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn resizes_once_the_size_settles() {
+        let mut resizer = Resizer::new(640, 480, DEBOUNCE);
+        assert_eq!(resizer.observe(640, 480, ms(0)), ResizeAction::None);
+        assert_eq!(resizer.observe(800, 600, ms(10)), ResizeAction::None);
+        assert_eq!(resizer.observe(800, 600, ms(109)), ResizeAction::None);
+        assert_eq!(
+            resizer.observe(800, 600, ms(110)),
+            ResizeAction::Resize {
+                width: 800,
+                height: 600
+            }
+        );
+        assert_eq!(resizer.size(), (800, 600));
+        assert_eq!(resizer.observe(800, 600, ms(300)), ResizeAction::None);
+    }
+
+    #[test]
+    fn a_changing_size_restarts_the_debounce() {
+        let mut resizer = Resizer::new(640, 480, DEBOUNCE);
+        assert_eq!(resizer.observe(700, 500, ms(0)), ResizeAction::None);
+        assert_eq!(resizer.observe(710, 510, ms(90)), ResizeAction::None);
+        assert_eq!(resizer.observe(710, 510, ms(180)), ResizeAction::None);
+        assert_eq!(
+            resizer.observe(710, 510, ms(190)),
+            ResizeAction::Resize {
+                width: 710,
+                height: 510
+            }
+        );
+    }
+
+    #[test]
+    fn going_back_to_the_old_size_cancels_the_resize() {
+        let mut resizer = Resizer::new(640, 480, DEBOUNCE);
+        assert_eq!(resizer.observe(800, 600, ms(0)), ResizeAction::None);
+        assert_eq!(resizer.observe(640, 480, ms(50)), ResizeAction::None);
+        assert_eq!(resizer.observe(800, 600, ms(120)), ResizeAction::None);
+        assert_eq!(resizer.size(), (640, 480));
+    }
+
+    #[test]
+    fn suspends_at_zero_size_and_resumes() {
+        let mut resizer = Resizer::new(640, 480, DEBOUNCE);
+        assert_eq!(resizer.observe(0, 0, ms(0)), ResizeAction::Suspend);
+        assert!(resizer.is_suspended());
+        assert_eq!(resizer.observe(0, 0, ms(10)), ResizeAction::None);
+        // one zero side is enough
+        assert_eq!(resizer.observe(640, 0, ms(20)), ResizeAction::None);
+        assert!(resizer.is_suspended());
+
+        assert_eq!(resizer.observe(640, 480, ms(30)), ResizeAction::Resume);
+        assert!(!resizer.is_suspended());
+        assert_eq!(resizer.observe(640, 480, ms(40)), ResizeAction::None);
+    }
+
+    #[test]
+    fn minimizing_drops_a_pending_resize() {
+        let mut resizer = Resizer::new(640, 480, DEBOUNCE);
+        assert_eq!(resizer.observe(800, 600, ms(0)), ResizeAction::None);
+        assert_eq!(resizer.observe(0, 0, ms(50)), ResizeAction::Suspend);
+        // restored at a new size: resumes now, resizes after the debounce
+        assert_eq!(resizer.observe(800, 600, ms(200)), ResizeAction::Resume);
+        assert_eq!(resizer.observe(800, 600, ms(250)), ResizeAction::None);
+        assert_eq!(
+            resizer.observe(800, 600, ms(300)),
+            ResizeAction::Resize {
+                width: 800,
+                height: 600
+            }
+        );
+    }
+
+    #[test]
+    fn all_suspended_needs_a_window() {
+        let mut resizers = Resizers::new(DEBOUNCE);
+        assert!(!resizers.all_suspended());
+
+        let (a, b) = (WindowId(1), WindowId(2));
+        // first seen at its size, so no resize
+        assert_eq!(resizers.observe(a, 640, 480, ms(0)), ResizeAction::None);
+        assert_eq!(resizers.observe(b, 0, 0, ms(0)), ResizeAction::Suspend);
+        assert!(resizers.is_suspended(b));
+        assert!(!resizers.all_suspended());
+
+        assert_eq!(resizers.observe(a, 0, 0, ms(10)), ResizeAction::Suspend);
+        assert!(resizers.all_suspended());
+
+        resizers.remove(a);
+        assert!(resizers.all_suspended());
+        resizers.remove(b);
+        assert!(!resizers.all_suspended());
+        assert!(!resizers.is_suspended(b));
+    }
+
+    #[test]
+    fn windows_resize_and_suspend_independently() {
+        let mut resizers = Resizers::new(DEBOUNCE);
+        let (a, b) = (WindowId(1), WindowId(2));
+        resizers.observe(a, 640, 480, ms(0));
+        resizers.observe(b, 320, 240, ms(0));
+
+        // a is minimized while b is being resized
+        assert_eq!(resizers.observe(a, 0, 0, ms(10)), ResizeAction::Suspend);
+        assert_eq!(resizers.observe(b, 400, 300, ms(10)), ResizeAction::None);
+        assert_eq!(resizers.observe(a, 0, 0, ms(110)), ResizeAction::None);
+        assert_eq!(
+            resizers.observe(b, 400, 300, ms(110)),
+            ResizeAction::Resize {
+                width: 400,
+                height: 300
+            }
+        );
+        assert!(resizers.is_suspended(a) && !resizers.is_suspended(b));
+        assert!(!resizers.all_suspended());
+
+        // closing b leaves only the minimized window
+        resizers.remove(b);
+        assert!(resizers.all_suspended());
+        assert_eq!(resizers.observe(a, 640, 480, ms(200)), ResizeAction::Resume);
+        assert!(!resizers.all_suspended());
+
+        // a window seen again after its removal starts over
+        assert_eq!(resizers.observe(b, 0, 0, ms(210)), ResizeAction::Suspend);
+    }
+}
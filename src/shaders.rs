@@ -0,0 +1,177 @@
+//! rust bindings for the shaders in `resource`, generated by the build script
+//! from their hlsl declarations, see `hlsl`. each `Foo.hlsl` is a module
+//! `foo` with
+//!
+//! - a `#[repr(C)]` struct for every struct and cbuffer, padded the way hlsl
+//!   packs it and checked at compile time,
+//! - `NAME_REGISTER` and `NAME_SPACE` for every cbuffer and resource bound
+//!   with `register`,
+//! - `NAME_INPUT_LAYOUT` for every struct with semantics, and for every
+//!   function taking vertex attributes.
+//!
+//! matrices are bound as they are declared: a `column_major` (the default)
+//! `float4x4` expects the transpose of a `Mat4`.
+//!
+//! what the parser skips, and a file it cannot bind at all, are build
+//! warnings; such a file gets no module.
+
+pub mod hlsl;
+
+use crate::cbuffer::packing;
+use crate::color::Color;
+use crate::math::quantize::{
+    Half2, Half4, Snorm16x2, Snorm16x4, Snorm8x2, Snorm8x4, Unorm1010102, Unorm16x2, Unorm16x4,
+    Unorm8x2, Unorm8x4,
+};
+use crate::math::{Half, Vec2, Vec3, Vec4};
+
+/// an element of an input layout, as `D3D12_INPUT_ELEMENT_DESC` describes
+/// it for the first input slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputElement {
+    pub semantic: &'static str,
+    pub index: u32,
+    /// a `DXGI_FORMAT`
+    pub format: u32,
+    /// bytes from the start of the vertex
+    pub offset: u32,
+}
+
+impl InputElement {
+    /// an element read as `T` reads
+    pub fn new<T: Attribute>(semantic: &'static str, index: u32, offset: u32) -> InputElement {
+        InputElement {
+            semantic: semantic,
+            index: index,
+            format: T::FORMAT,
+            offset: offset,
+        }
+    }
+}
+
+/// a type a vertex attribute can be stored as, and the format that reads it
+pub trait Attribute {
+    const FORMAT: u32;
+}
+
+macro_rules! attribute {
+    ($($ty:ty => $format:ident,)+) => {
+        $(impl Attribute for $ty {
+            const FORMAT: u32 = format::$format;
+        })+
+    };
+}
+
+attribute! {
+    f32 => R32_FLOAT,
+    Vec2<f32> => R32G32_FLOAT,
+    Vec3<f32> => R32G32B32_FLOAT,
+    Vec4<f32> => R32G32B32A32_FLOAT,
+    Color => R32G32B32A32_FLOAT,
+    u32 => R32_UINT,
+    Vec2<u32> => R32G32_UINT,
+    Vec3<u32> => R32G32B32_UINT,
+    Vec4<u32> => R32G32B32A32_UINT,
+    i32 => R32_SINT,
+    Vec2<i32> => R32G32_SINT,
+    Vec3<i32> => R32G32B32_SINT,
+    Vec4<i32> => R32G32B32A32_SINT,
+    Half => R16_FLOAT,
+    Half2 => R16G16_FLOAT,
+    Half4 => R16G16B16A16_FLOAT,
+    Unorm8x2 => R8G8_UNORM,
+    Unorm8x4 => R8G8B8A8_UNORM,
+    Snorm8x2 => R8G8_SNORM,
+    Snorm8x4 => R8G8B8A8_SNORM,
+    Unorm16x2 => R16G16_UNORM,
+    Unorm16x4 => R16G16B16A16_UNORM,
+    Snorm16x2 => R16G16_SNORM,
+    Snorm16x4 => R16G16B16A16_SNORM,
+    Unorm1010102 => R10G10B10A2_UNORM,
+}
+
+/// the `DXGI_FORMAT`s of vertex attributes, see `math::quantize` for the
+/// packed ones
+pub mod format {
+    pub const R32G32B32A32_FLOAT: u32 = 2;
+    pub const R32G32B32A32_UINT: u32 = 3;
+    pub const R32G32B32A32_SINT: u32 = 4;
+    pub const R16G16B16A16_FLOAT: u32 = 10;
+    pub const R16G16B16A16_UNORM: u32 = 11;
+    pub const R16G16B16A16_SNORM: u32 = 13;
+    pub const R32G32B32_FLOAT: u32 = 6;
+    pub const R32G32B32_UINT: u32 = 7;
+    pub const R32G32B32_SINT: u32 = 8;
+    pub const R32G32_FLOAT: u32 = 16;
+    pub const R32G32_UINT: u32 = 17;
+    pub const R32G32_SINT: u32 = 18;
+    pub const R10G10B10A2_UNORM: u32 = 24;
+    pub const R8G8B8A8_UNORM: u32 = 28;
+    pub const R8G8B8A8_SNORM: u32 = 31;
+    pub const R16G16_FLOAT: u32 = 34;
+    pub const R16G16_UNORM: u32 = 35;
+    pub const R16G16_SNORM: u32 = 37;
+    pub const R32_FLOAT: u32 = 41;
+    pub const R32_UINT: u32 = 42;
+    pub const R32_SINT: u32 = 43;
+    pub const R8G8_UNORM: u32 = 49;
+    pub const R8G8_SNORM: u32 = 51;
+    pub const R16_FLOAT: u32 = 54;
+}
+
+include!(concat!(env!("OUT_DIR"), "/shaders.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbuffer::{self, HlslType};
+
+    // the expected output of the generator for the fixtures, compiled here
+    // so that its layout checks run
+    #[allow(dead_code)]
+    mod scene {
+        include!("../tests/fixtures/Scene.rs");
+    }
+
+    #[allow(dead_code)]
+    mod skinning {
+        include!("../tests/fixtures/Skinning.rs");
+    }
+
+    #[test]
+    fn generated_layouts_match_the_packing() {
+        assert_eq!(scene::Scene::SIZE, 244);
+        assert_eq!(
+            cbuffer::layout::<scene::Scene>()
+                .member("weights")
+                .unwrap()
+                .offset,
+            208
+        );
+        let skinning = cbuffer::layout::<skinning::Skinning>();
+        assert_eq!(skinning.member("skeleton").unwrap().offset, 320);
+        assert_eq!(
+            skinning.buffer_size() as usize,
+            std::mem::size_of::<skinning::Skinning>()
+        );
+    }
+
+    #[test]
+    fn input_layouts() {
+        assert_eq!(
+            scene::MAIN_INPUT_LAYOUT[3],
+            InputElement::new::<Vec4<f32>>("COLOR", 1, 32)
+        );
+        // the system value is not part of the vertex
+        assert_eq!(scene::VERTEX_IN_INPUT_LAYOUT.len(), 3);
+        assert_eq!(std::mem::size_of::<scene::VertexIn>(), 32);
+        assert_eq!(
+            InputElement::new::<Unorm8x4>("COLOR", 0, 0).format,
+            format::R8G8B8A8_UNORM
+        );
+        assert_eq!(
+            InputElement::new::<Color>("COLOR", 0, 0).format,
+            format::R32G32B32A32_FLOAT
+        );
+    }
+}
@@ -0,0 +1,1340 @@
+//! a parser for the declarations of an hlsl file: structs, constant buffers,
+//! resources with registers and the parameters of functions. it needs no
+//! shader compiler, and nothing but std and the packing rules, so that the
+//! build script can include it.
+//!
+//! there is no preprocessor; `#pragma pack_matrix` is understood, other
+//! pragmas are ignored and any other directive is skipped. so are `static`
+//! and `groupshared` globals and output parameters, which have nothing to
+//! bind; `Shader::skipped` lists what was left out.
+
+use std::fmt::Write;
+
+use super::packing::{round_up, Field, Layout, Major, Scalar, Type, REGISTER_SIZE};
+
+/// a member of a struct or constant buffer, or a function parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub ty: Type,
+    /// the struct the member is, or is an array of
+    pub struct_name: Option<String>,
+    pub semantic: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    pub name: String,
+    pub members: Vec<Member>,
+}
+
+/// `register(b0, space1)` is `Register { class: 'b', index: 0, space: 1 }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Register {
+    pub class: char,
+    pub index: u32,
+    pub space: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantBuffer {
+    pub name: String,
+    pub members: Vec<Member>,
+    pub register: Option<Register>,
+}
+
+/// a global with a register: a texture, sampler, buffer or `ConstantBuffer<T>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    pub name: String,
+    pub register: Register,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub inputs: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Shader {
+    pub structs: Vec<Struct>,
+    pub constant_buffers: Vec<ConstantBuffer>,
+    pub resources: Vec<Resource>,
+    pub functions: Vec<Function>,
+    /// what the parser left out, as `line 3: skipped ...`
+    pub skipped: Vec<String>,
+}
+
+impl Shader {
+    pub fn find_struct(&self, name: &str) -> Option<&Struct> {
+        self.structs.iter().find(|s| s.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Punct(char),
+    /// a whole preprocessor line, without the `#`
+    Directive(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = source.chars().collect();
+    let (mut i, mut line) = (0, 1);
+    // only whitespace since the start of the line, where a directive may begin
+    let mut line_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            line_start = true;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(format!("line {}: unterminated comment", line));
+            }
+            i += 2;
+        } else if c == '#' && line_start {
+            let begin = i + 1;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            let text: String = chars[begin..i].iter().collect();
+            tokens.push((Token::Directive(text.trim().to_string()), line));
+        } else if c.is_alphabetic() || c == '_' {
+            let begin = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[begin..i].iter().collect()), line));
+        } else if c.is_ascii_digit() {
+            let begin = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push((Token::Number(chars[begin..i].iter().collect()), line));
+        } else if c == '"' {
+            // strings only appear in annotations and attributes, which are skipped
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Punct('"'), line));
+        } else {
+            tokens.push((Token::Punct(c), line));
+            i += 1;
+        }
+        if !c.is_whitespace() {
+            line_start = false;
+        }
+    }
+    Ok(tokens)
+}
+
+/// the scalar at the start of a type name like `float3x4`, and the rest
+fn split_scalar(name: &str) -> Option<(Scalar, &str)> {
+    const SCALARS: [(&str, Scalar); 11] = [
+        ("min16float", Scalar::Float),
+        ("min10float", Scalar::Float),
+        ("min16uint", Scalar::Uint),
+        ("min16int", Scalar::Int),
+        ("min12int", Scalar::Int),
+        ("float", Scalar::Float),
+        ("half", Scalar::Float),
+        ("dword", Scalar::Uint),
+        ("uint", Scalar::Uint),
+        ("bool", Scalar::Bool),
+        ("int", Scalar::Int),
+    ];
+    SCALARS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|&(prefix, scalar)| (scalar, &name[prefix.len()..]))
+}
+
+fn dimension(text: &str) -> Option<u32> {
+    match text.parse() {
+        Ok(n) if (1..=4).contains(&n) => Some(n),
+        _ => None,
+    }
+}
+
+fn numeric_type(scalar: Scalar, rows: u32, columns: u32, major: Major, matrix: bool) -> Type {
+    if matrix {
+        Type::Matrix {
+            scalar: scalar,
+            rows: rows,
+            columns: columns,
+            major: major,
+        }
+    } else if columns == 1 {
+        Type::Scalar(scalar)
+    } else {
+        Type::Vector(scalar, columns)
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    default_major: Major,
+    shader: Shader,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or_else(|| self.tokens.last()) {
+            Some(&(_, line)) => line,
+            None => 1,
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        Err(format!("line {}: {}", self.line(), message))
+    }
+
+    fn skipped(&mut self, line: usize, what: &str) {
+        self.shader
+            .skipped
+            .push(format!("line {}: skipped {}", line, what));
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        match self.tokens.get(self.pos) {
+            Some((token, _)) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => self.error("unexpected end of file"),
+        }
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        if self.is_punct(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), String> {
+        if self.eat_punct(c) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", c))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => self.error("expected a name"),
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, String> {
+        match self.peek() {
+            Some(Token::Number(text)) => match text.parse() {
+                Ok(n) => {
+                    self.pos += 1;
+                    Ok(n)
+                }
+                Err(_) => self.error(&format!("invalid count '{}'", text)),
+            },
+            _ => self.error("expected a number; macros are not expanded"),
+        }
+    }
+
+    /// skips past the bracket that closes the one just read
+    fn skip_group(&mut self, open: char, close: char) -> Result<(), String> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::Punct(c) if c == open => depth += 1,
+                Token::Punct(c) if c == close => depth -= 1,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// skips to the `;` that ends a declaration, past any brackets
+    fn skip_declaration(&mut self) -> Result<(), String> {
+        loop {
+            match self.next()? {
+                Token::Punct(';') => return Ok(()),
+                Token::Punct('{') => self.skip_group('{', '}')?,
+                Token::Punct('(') => self.skip_group('(', ')')?,
+                _ => (),
+            }
+        }
+    }
+
+    /// skips a declaration, or a function up to the end of its body
+    fn skip_global(&mut self) -> Result<(), String> {
+        let mut parameters = false;
+        loop {
+            match self.next()? {
+                Token::Punct(';') => return Ok(()),
+                Token::Punct('(') => {
+                    self.skip_group('(', ')')?;
+                    parameters = true;
+                }
+                Token::Punct('{') => {
+                    self.skip_group('{', '}')?;
+                    if parameters {
+                        return Ok(());
+                    }
+                }
+                // `=` starts an initializer, where parentheses are calls
+                Token::Punct('=') => parameters = false,
+                _ => (),
+            }
+        }
+    }
+
+    fn directive(&mut self, text: &str, line: usize) {
+        let words: String = text.split_whitespace().collect();
+        if words == "pragmapack_matrix(row_major)" {
+            self.default_major = Major::Row;
+        } else if words == "pragmapack_matrix(column_major)" {
+            self.default_major = Major::Column;
+        } else if !text.starts_with("pragma") {
+            self.skipped(line, &format!("directive '#{}'", text));
+        }
+    }
+
+    /// a type name with its modifiers, as a type and the struct it names
+    fn type_name(&mut self) -> Result<(Type, Option<String>), String> {
+        let mut major = self.default_major;
+        let name = loop {
+            let name = self.ident()?;
+            match name.as_str() {
+                "row_major" => major = Major::Row,
+                "column_major" => major = Major::Column,
+                "const" | "uniform" | "in" | "linear" | "centroid" | "nointerpolation"
+                | "noperspective" | "sample" | "precise" | "snorm" | "unorm" => (),
+                _ => break name,
+            }
+        };
+        if name == "vector" || name == "matrix" {
+            let matrix = name == "matrix";
+            if !self.eat_punct('<') {
+                return Ok((numeric_type(Scalar::Float, 4, 4, major, matrix), None));
+            }
+            let scalar = match split_scalar(&self.ident()?) {
+                Some((scalar, "")) => scalar,
+                _ => return self.error(&format!("invalid {} element type", name)),
+            };
+            self.expect_punct(',')?;
+            let first = self.number()?;
+            let (rows, columns) = if matrix {
+                self.expect_punct(',')?;
+                (first, self.number()?)
+            } else {
+                (1, first)
+            };
+            self.expect_punct('>')?;
+            if !(1..=4).contains(&rows) || !(1..=4).contains(&columns) {
+                return self.error(&format!("invalid {} size", name));
+            }
+            return Ok((numeric_type(scalar, rows, columns, major, matrix), None));
+        }
+        if let Some((scalar, rest)) = split_scalar(&name) {
+            let dims: Vec<&str> = rest.split('x').collect();
+            let ty = match dims.as_slice() {
+                [""] => Some(numeric_type(scalar, 1, 1, major, false)),
+                [n] => dimension(n).map(|n| numeric_type(scalar, 1, n, major, false)),
+                [r, c] => match (dimension(r), dimension(c)) {
+                    (Some(r), Some(c)) => Some(numeric_type(scalar, r, c, major, true)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(ty) = ty {
+                return Ok((ty, None));
+            }
+        }
+        if name == "double" || name.starts_with("double") || name.starts_with("uint64") {
+            return self.error(&format!("64 bit type '{}' is not supported", name));
+        }
+        match self.shader.find_struct(&name) {
+            Some(s) => {
+                let fields = s
+                    .members
+                    .iter()
+                    .map(|m| Field::new(&m.name, m.ty.clone()))
+                    .collect();
+                Ok((Type::Struct(fields), Some(name)))
+            }
+            None => self.error(&format!("unknown type '{}'", name)),
+        }
+    }
+
+    /// `[2][3]` after a name
+    fn array_suffix(&mut self, ty: Type) -> Result<Type, String> {
+        let mut counts = vec![];
+        while self.eat_punct('[') {
+            let count = self.number()?;
+            if count == 0 {
+                return self.error("array size must not be zero");
+            }
+            counts.push(count);
+            self.expect_punct(']')?;
+        }
+        // the first count is the outermost
+        Ok(counts
+            .into_iter()
+            .rev()
+            .fold(ty, |ty, count| Type::Array(Box::new(ty), count)))
+    }
+
+    fn register(&mut self) -> Result<Register, String> {
+        self.expect_punct('(')?;
+        let slot = self.ident()?;
+        let mut register = match (slot.chars().next(), slot.get(1..).map(str::parse)) {
+            (Some(class), Some(Ok(index))) => Register {
+                class: class,
+                index: index,
+                space: 0,
+            },
+            _ => return self.error(&format!("invalid register '{}'", slot)),
+        };
+        if self.eat_punct(',') {
+            let space = self.ident()?;
+            register.space = match space.strip_prefix("space").map(str::parse) {
+                Some(Ok(space)) => space,
+                _ => return self.error(&format!("invalid space '{}'", space)),
+            };
+        }
+        self.expect_punct(')')?;
+        Ok(register)
+    }
+
+    /// the members of a struct or constant buffer, after its `{`
+    fn members(&mut self) -> Result<Vec<Member>, String> {
+        let mut members = vec![];
+        while !self.eat_punct('}') {
+            if self.peek() == Some(&Token::Ident("struct".to_string())) {
+                return self.error(
+                    "nested struct definitions are not supported; declare the struct first",
+                );
+            }
+            let (ty, struct_name) = self.type_name()?;
+            loop {
+                let name = self.ident()?;
+                let ty = self.array_suffix(ty.clone())?;
+                let mut semantic = None;
+                if self.eat_punct(':') {
+                    let word = self.ident()?;
+                    if word == "packoffset" {
+                        return self.error("packoffset is not supported");
+                    }
+                    semantic = Some(word);
+                }
+                members.push(Member {
+                    name: name,
+                    ty: ty,
+                    struct_name: struct_name.clone(),
+                    semantic: semantic,
+                });
+                if !self.eat_punct(',') {
+                    break;
+                }
+            }
+            self.expect_punct(';')?;
+        }
+        Ok(members)
+    }
+
+    /// moves to the `,` or `)` after a parameter, and tells whether it is an
+    /// `out` or `inout` one
+    fn skip_parameter(&mut self) -> Result<bool, String> {
+        let (mut output, mut depth) = (false, 0);
+        loop {
+            match self.peek() {
+                Some(Token::Punct(',')) | Some(Token::Punct(')')) if depth == 0 => {
+                    return Ok(output)
+                }
+                Some(Token::Punct('(')) | Some(Token::Punct('<')) => depth += 1,
+                Some(Token::Punct(')')) | Some(Token::Punct('>')) => depth -= 1,
+                Some(Token::Ident(word)) if word == "out" || word == "inout" => output = true,
+                _ => (),
+            }
+            self.next()?;
+        }
+    }
+
+    /// the parameters after a function's `(`, without the outputs and
+    /// resources, which are not part of any input layout
+    fn parameters(&mut self) -> Result<Vec<Member>, String> {
+        let mut inputs = vec![];
+        if self.eat_punct(')') {
+            return Ok(inputs);
+        }
+        loop {
+            let start = self.pos;
+            let output = self.skip_parameter()?;
+            let end = self.pos;
+            self.pos = start;
+            if !output && self.type_name().is_ok() {
+                self.pos = start;
+                let (ty, struct_name) = self.type_name()?;
+                let name = self.ident()?;
+                let ty = self.array_suffix(ty)?;
+                let semantic = if self.eat_punct(':') {
+                    Some(self.ident()?)
+                } else {
+                    None
+                };
+                inputs.push(Member {
+                    name: name,
+                    ty: ty,
+                    struct_name: struct_name,
+                    semantic: semantic,
+                });
+            }
+            // past a default value, or all of a skipped parameter
+            self.pos = end;
+            if self.eat_punct(')') {
+                return Ok(inputs);
+            }
+            self.expect_punct(',')?;
+        }
+    }
+
+    fn item(&mut self) -> Result<(), String> {
+        match self.next()? {
+            Token::Directive(text) => {
+                let line = self.tokens[self.pos - 1].1;
+                self.directive(&text, line);
+                Ok(())
+            }
+            // attributes like [numthreads(8, 8, 1)]
+            Token::Punct('[') => self.skip_group('[', ']'),
+            Token::Punct(';') => Ok(()),
+            Token::Ident(word) => match word.as_str() {
+                "struct" => {
+                    let name = self.ident()?;
+                    self.expect_punct('{')?;
+                    let members = self.members()?;
+                    self.expect_punct(';')?;
+                    self.shader.structs.push(Struct {
+                        name: name,
+                        members: members,
+                    });
+                    Ok(())
+                }
+                "cbuffer" => {
+                    let name = self.ident()?;
+                    let register = if self.eat_punct(':') {
+                        if self.ident()? != "register" {
+                            return self.error("expected 'register'");
+                        }
+                        Some(self.register()?)
+                    } else {
+                        None
+                    };
+                    self.expect_punct('{')?;
+                    let members = self.members()?;
+                    self.eat_punct(';');
+                    self.shader.constant_buffers.push(ConstantBuffer {
+                        name: name,
+                        members: members,
+                        register: register,
+                    });
+                    Ok(())
+                }
+                // nothing to bind: constants compiled in, and memory of a group
+                "static" | "groupshared" => {
+                    let line = self.tokens[self.pos - 1].1;
+                    self.skipped(line, &format!("'{}' declaration", word));
+                    self.skip_global()
+                }
+                "typedef" | "namespace" | "tbuffer" | "interface" | "class" => {
+                    self.error(&format!("'{}' is not supported", word))
+                }
+                _ => {
+                    self.pos -= 1;
+                    self.global()
+                }
+            },
+            _ => self.error("expected a declaration"),
+        }
+    }
+
+    /// a function or a global variable
+    fn global(&mut self) -> Result<(), String> {
+        // resource types are not parsed, only skipped with their template arguments
+        let start = self.pos;
+        let mut is_resource = false;
+        if self.type_name().is_err() {
+            self.pos = start;
+            self.ident()?;
+            if self.eat_punct('<') {
+                self.skip_group('<', '>')?;
+            }
+            is_resource = true;
+        }
+        let name = self.ident()?;
+        if self.eat_punct('(') {
+            let inputs = self.parameters()?;
+            if self.eat_punct(':') {
+                self.ident()?;
+            }
+            if self.eat_punct('{') {
+                self.skip_group('{', '}')?;
+            } else {
+                self.expect_punct(';')?;
+            }
+            self.shader.functions.push(Function {
+                name: name,
+                inputs: inputs,
+            });
+            return Ok(());
+        }
+        while self.eat_punct('[') {
+            self.skip_group('[', ']')?;
+        }
+        if self.eat_punct(':') && self.ident()? == "register" {
+            let register = self.register()?;
+            self.shader.resources.push(Resource {
+                name: name,
+                register: register,
+            });
+        } else if !is_resource {
+            // a loose global would go into $Globals, which is not generated
+            return self.error(&format!(
+                "global '{}' outside of a cbuffer is not supported",
+                name
+            ));
+        }
+        self.skip_declaration()
+    }
+}
+
+pub fn parse(source: &str) -> Result<Shader, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        default_major: Major::Column,
+        shader: Shader::default(),
+    };
+    while parser.pos < parser.tokens.len() {
+        parser.item()?;
+    }
+    Ok(parser.shader)
+}
+
+/// `VertexShader` as `vertex_shader`, and `MVP` as `mvp`
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+fn rust_name(name: &str) -> String {
+    const KEYWORDS: [&str; 38] = [
+        "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "macro", "match", "mod",
+        "move", "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type",
+        "unsafe", "use", "where", "while", "yield",
+    ];
+    let name = snake_case(name);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else if name == "self" || name == "super" || name == "crate" {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+fn rust_scalar(scalar: Scalar) -> &'static str {
+    match scalar {
+        Scalar::Float => "f32",
+        Scalar::Int => "i32",
+        // hlsl's bool is 4 bytes
+        Scalar::Uint | Scalar::Bool => "u32",
+    }
+}
+
+/// the size of the rust type of `ty`, which fills whole registers for the
+/// aggregates so that they can be array elements
+fn rust_size(ty: &Type) -> u32 {
+    if ty.is_aggregate() {
+        round_up(ty.size())
+    } else {
+        ty.size()
+    }
+}
+
+/// the rust type of a constant buffer member
+fn rust_type(ty: &Type, struct_name: Option<&str>) -> String {
+    match *ty {
+        Type::Scalar(scalar) => rust_scalar(scalar).to_string(),
+        Type::Vector(scalar, len) => format!("Vec{}<{}>", len, rust_scalar(scalar)),
+        Type::Matrix {
+            scalar: Scalar::Float,
+            rows: 4,
+            columns: 4,
+            ..
+        } => "Mat4".to_string(),
+        Type::Matrix {
+            scalar,
+            rows,
+            columns,
+            major,
+        } => {
+            let registers = if major == Major::Row { rows } else { columns };
+            format!("[Vec4<{}>; {}]", rust_scalar(scalar), registers)
+        }
+        Type::Array(ref element, count) => {
+            let element = match **element {
+                // each element takes a whole register
+                Type::Scalar(scalar) | Type::Vector(scalar, _) => {
+                    format!("Vec4<{}>", rust_scalar(scalar))
+                }
+                ref element => rust_type(element, struct_name),
+            };
+            format!("[{}; {}]", element, count)
+        }
+        Type::Struct(_) => struct_name.unwrap_or("()").to_string(),
+    }
+}
+
+/// rust code that builds `ty`
+fn type_expr(ty: &Type, struct_name: Option<&str>) -> String {
+    const PACKING: &str = "crate::cbuffer::packing";
+    let scalar = |scalar: Scalar| format!("{}::Scalar::{:?}", PACKING, scalar);
+    match *ty {
+        Type::Scalar(s) => format!("{}::Type::Scalar({})", PACKING, scalar(s)),
+        Type::Vector(s, len) => format!("{}::Type::Vector({}, {})", PACKING, scalar(s), len),
+        Type::Matrix {
+            scalar: s,
+            rows,
+            columns,
+            major,
+        } => format!(
+            "{p}::Type::Matrix {{ scalar: {}, rows: {}, columns: {}, major: {p}::Major::{:?} }}",
+            scalar(s),
+            rows,
+            columns,
+            major,
+            p = PACKING
+        ),
+        Type::Array(ref element, count) => format!(
+            "{}::Type::Array(Box::new({}), {})",
+            PACKING,
+            type_expr(element, struct_name),
+            count
+        ),
+        Type::Struct(_) => format!(
+            "<{} as crate::cbuffer::HlslType>::hlsl_type()",
+            struct_name.unwrap_or("()")
+        ),
+    }
+}
+
+fn is_system_value(semantic: &str) -> bool {
+    semantic.to_ascii_uppercase().starts_with("SV_")
+}
+
+/// a struct with semantics describes vertices or stage outputs
+fn is_vertex_struct(s: &Struct) -> bool {
+    s.members.iter().any(|m| m.semantic.is_some())
+}
+
+/// a `#[repr(C)]` struct laid out like `members` in a constant buffer,
+/// with explicit padding and checks of every offset
+fn packed_struct(out: &mut String, name: &str, members: &[Member]) -> Result<(), String> {
+    let fields: Vec<Field> = members
+        .iter()
+        .map(|m| Field::new(&m.name, m.ty.clone()))
+        .collect();
+    let layout = Layout::pack(&fields);
+    let mut body = String::new();
+    let mut checks = String::new();
+    let (mut end, mut pads) = (0, 0);
+    let mut pad = |body: &mut String, from: u32, to: u32| {
+        if to > from {
+            writeln!(body, "    pub _pad{}: [u32; {}],", pads, (to - from) / 4).unwrap();
+            pads += 1;
+        }
+    };
+    for (member, placed) in members.iter().zip(layout.members.iter()) {
+        if placed.offset < end {
+            return Err(format!(
+                "{}.{} is packed into the last register of the member before it, \
+                 which rust cannot share; move it or pad in the shader",
+                name, member.name
+            ));
+        }
+        pad(&mut body, end, placed.offset);
+        let field = rust_name(&member.name);
+        writeln!(
+            body,
+            "    pub {}: {},",
+            field,
+            rust_type(&member.ty, member.struct_name.as_deref())
+        )
+        .unwrap();
+        writeln!(
+            checks,
+            "const _: () = assert!(std::mem::offset_of!({}, {}) == {});",
+            name, field, placed.offset
+        )
+        .unwrap();
+        end = placed.offset + rust_size(&member.ty);
+    }
+    // whole registers, to be an array element or a buffer of its own
+    let size = round_up(layout.size.max(end)).max(REGISTER_SIZE);
+    pad(&mut body, end, size);
+
+    writeln!(out, "#[repr(C)]").unwrap();
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq)]").unwrap();
+    writeln!(out, "pub struct {} {{", name).unwrap();
+    out.push_str(&body);
+    writeln!(out, "}}\n").unwrap();
+    out.push_str(&checks);
+    writeln!(
+        out,
+        "const _: () = assert!(std::mem::size_of::<{}>() == {});\n",
+        name, size
+    )
+    .unwrap();
+    writeln!(out, "impl crate::cbuffer::HlslType for {} {{", name).unwrap();
+    writeln!(out, "    const SIZE: u32 = {};", layout.size).unwrap();
+    writeln!(out, "    const AGGREGATE: bool = true;").unwrap();
+    writeln!(
+        out,
+        "    fn hlsl_type() -> crate::cbuffer::packing::Type {{"
+    )
+    .unwrap();
+    writeln!(out, "        crate::cbuffer::packing::Type::Struct(vec![").unwrap();
+    for member in members {
+        writeln!(
+            out,
+            "            crate::cbuffer::packing::Field::new({:?}, {}),",
+            member.name,
+            type_expr(&member.ty, member.struct_name.as_deref())
+        )
+        .unwrap();
+    }
+    writeln!(out, "        ])\n    }}\n}}\n").unwrap();
+    Ok(())
+}
+
+/// the dxgi format of a vertex attribute
+fn vertex_format(ty: &Type) -> Option<&'static str> {
+    let (scalar, len) = match *ty {
+        Type::Scalar(scalar) => (scalar, 1),
+        Type::Vector(scalar, len) => (scalar, len),
+        _ => return None,
+    };
+    let formats = match scalar {
+        Scalar::Float => [
+            "R32_FLOAT",
+            "R32G32_FLOAT",
+            "R32G32B32_FLOAT",
+            "R32G32B32A32_FLOAT",
+        ],
+        Scalar::Int => [
+            "R32_SINT",
+            "R32G32_SINT",
+            "R32G32B32_SINT",
+            "R32G32B32A32_SINT",
+        ],
+        Scalar::Uint => [
+            "R32_UINT",
+            "R32G32_UINT",
+            "R32G32B32_UINT",
+            "R32G32B32A32_UINT",
+        ],
+        Scalar::Bool => return None,
+    };
+    Some(formats[len as usize - 1])
+}
+
+/// `TEXCOORD1` as ("TEXCOORD", 1)
+fn split_semantic(semantic: &str) -> (&str, u32) {
+    let name = semantic.trim_end_matches(|c: char| c.is_ascii_digit());
+    (name, semantic[name.len()..].parse().unwrap_or(0))
+}
+
+/// the vertex attributes of `inputs`, expanding structs, in the order of
+/// a tightly packed vertex
+fn vertex_attributes<'a>(shader: &'a Shader, inputs: &'a [Member]) -> Vec<&'a Member> {
+    let mut attributes = vec![];
+    for input in inputs {
+        let s = input
+            .struct_name
+            .as_deref()
+            .and_then(|name| shader.find_struct(name));
+        match (s, &input.semantic) {
+            (Some(s), None) => attributes.extend(vertex_attributes(shader, &s.members)),
+            (None, Some(semantic)) if !is_system_value(semantic) => attributes.push(input),
+            _ => (),
+        }
+    }
+    attributes
+}
+
+fn input_layout(out: &mut String, name: &str, attributes: &[&Member]) -> Result<(), String> {
+    writeln!(
+        out,
+        "pub const {}_INPUT_LAYOUT: &[crate::shaders::InputElement] = &[",
+        snake_case(name).to_uppercase()
+    )
+    .unwrap();
+    let mut offset = 0;
+    for attribute in attributes {
+        let semantic = attribute.semantic.as_deref().unwrap_or("");
+        let format = vertex_format(&attribute.ty).ok_or_else(|| {
+            format!(
+                "{}: {} {} is not a vertex attribute",
+                name, attribute.ty, attribute.name
+            )
+        })?;
+        let (semantic, index) = split_semantic(semantic);
+        writeln!(
+            out,
+            "    crate::shaders::InputElement {{ semantic: {:?}, index: {}, format: crate::shaders::format::{}, offset: {} }},",
+            semantic, index, format, offset
+        )
+        .unwrap();
+        offset += attribute.ty.size();
+    }
+    writeln!(out, "];\n").unwrap();
+    Ok(())
+}
+
+/// a `#[repr(C)]` vertex with the attributes of a struct, one after another
+fn vertex_struct(out: &mut String, shader: &Shader, s: &Struct) -> Result<(), String> {
+    let attributes = vertex_attributes(shader, &s.members);
+    writeln!(out, "#[repr(C)]").unwrap();
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq)]").unwrap();
+    writeln!(out, "pub struct {} {{", s.name).unwrap();
+    for attribute in attributes.iter() {
+        writeln!(
+            out,
+            "    pub {}: {},",
+            rust_name(&attribute.name),
+            rust_type(&attribute.ty, None)
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+    input_layout(out, &s.name, &attributes)
+}
+
+fn register_consts(out: &mut String, name: &str, register: &Register) {
+    let name = snake_case(name).to_uppercase();
+    writeln!(
+        out,
+        "pub const {}_REGISTER: u32 = {};",
+        name, register.index
+    )
+    .unwrap();
+    writeln!(out, "pub const {}_SPACE: u32 = {};\n", name, register.space).unwrap();
+}
+
+/// rust bindings for `shader`, meant to go into a module of their own
+pub fn generate(shader: &Shader) -> Result<String, String> {
+    let mut out = String::new();
+    writeln!(out, "#[allow(unused_imports)]").unwrap();
+    writeln!(out, "use crate::math::{{Mat4, Vec2, Vec3, Vec4}};\n").unwrap();
+    for s in shader.structs.iter() {
+        if is_vertex_struct(s) {
+            vertex_struct(&mut out, shader, s)?;
+        } else {
+            packed_struct(&mut out, &s.name, &s.members)?;
+        }
+    }
+    for buffer in shader.constant_buffers.iter() {
+        if let Some(member) = buffer.members.iter().find(|m| {
+            m.struct_name
+                .as_deref()
+                .and_then(|name| shader.find_struct(name))
+                .is_some_and(is_vertex_struct)
+        }) {
+            return Err(format!(
+                "{}.{} is a struct with semantics",
+                buffer.name, member.name
+            ));
+        }
+        packed_struct(&mut out, &buffer.name, &buffer.members)?;
+        if let Some(ref register) = buffer.register {
+            register_consts(&mut out, &buffer.name, register);
+        }
+    }
+    for resource in shader.resources.iter() {
+        register_consts(&mut out, &resource.name, &resource.register);
+    }
+    for function in shader.functions.iter() {
+        let attributes = vertex_attributes(shader, &function.inputs);
+        if !attributes.is_empty() {
+            input_layout(&mut out, &function.name, &attributes)?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = include_str!("../../tests/fixtures/Scene.hlsl");
+    const SKINNING: &str = include_str!("../../tests/fixtures/Skinning.hlsl");
+
+    fn float_matrix(rows: u32, columns: u32, major: Major) -> Type {
+        Type::Matrix {
+            scalar: Scalar::Float,
+            rows: rows,
+            columns: columns,
+            major: major,
+        }
+    }
+
+    fn error(source: &str) -> String {
+        parse(source)
+            .and_then(|shader| generate(&shader))
+            .unwrap_err()
+    }
+
+    #[test]
+    fn generates_the_fixtures() {
+        let fixtures = [
+            (SCENE, include_str!("../../tests/fixtures/Scene.rs")),
+            (SKINNING, include_str!("../../tests/fixtures/Skinning.rs")),
+        ];
+        for (source, expected) in fixtures.iter() {
+            assert_eq!(generate(&parse(source).unwrap()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn parses_the_scene() {
+        let shader = parse(SCENE).unwrap();
+        assert_eq!(shader.structs.len(), 2);
+        assert_eq!(
+            shader.structs[1].members[3].semantic.as_deref(),
+            Some("SV_VertexID")
+        );
+        let scene = &shader.constant_buffers[0];
+        assert_eq!(
+            scene.register,
+            Some(Register {
+                class: 'b',
+                index: 1,
+                space: 2
+            })
+        );
+        // the pragma makes matrices row major unless they say otherwise
+        assert_eq!(scene.members[0].ty, float_matrix(4, 4, Major::Row));
+        assert_eq!(scene.members[1].ty, float_matrix(3, 3, Major::Column));
+        assert_eq!(scene.members[4].struct_name.as_deref(), Some("Light"));
+        assert!(matches!(scene.members[4].ty, Type::Array(_, 2)));
+        assert_eq!(scene.members[7].name, "type");
+        assert_eq!(shader.resources.len(), 2);
+        assert_eq!(
+            shader.resources[1].register,
+            Register {
+                class: 's',
+                index: 0,
+                space: 1
+            }
+        );
+        assert_eq!(shader.functions[0].name, "main");
+        assert_eq!(shader.functions[0].inputs.len(), 2);
+    }
+
+    #[test]
+    fn parses_nested_structs_and_arrays_of_matrices() {
+        let shader = parse(SKINNING).unwrap();
+        let skinning = &shader.constant_buffers[0].members;
+        assert_eq!(
+            skinning[0].ty,
+            Type::Array(Box::new(float_matrix(4, 4, Major::Column)), 4)
+        );
+        assert_eq!(
+            skinning[1].ty,
+            Type::Array(Box::new(float_matrix(2, 3, Major::Row)), 2)
+        );
+        let bone = shader.find_struct("Bone").unwrap();
+        let skeleton = &shader.find_struct("Skeleton").unwrap().members;
+        assert_eq!(skeleton[1].struct_name.as_deref(), Some("Bone"));
+        let fields: Vec<Field> = bone
+            .members
+            .iter()
+            .map(|m| Field::new(&m.name, m.ty.clone()))
+            .collect();
+        assert_eq!(
+            skeleton[2].ty,
+            Type::Array(Box::new(Type::Struct(fields)), 2)
+        );
+        // the compute entry point reads no vertices
+        assert_eq!(
+            shader.functions[0].inputs[0].semantic.as_deref(),
+            Some("SV_DispatchThreadID")
+        );
+    }
+
+    #[test]
+    fn multidimensional_arrays_are_outermost_first() {
+        let shader = parse("struct S { float a; float2 b[2][3]; }; cbuffer C { S s; };").unwrap();
+        let inner = Type::Array(Box::new(Type::Vector(Scalar::Float, 2)), 3);
+        assert_eq!(
+            shader.structs[0].members[1].ty,
+            Type::Array(Box::new(inner), 2)
+        );
+    }
+
+    #[test]
+    fn matrices_default_to_column_major() {
+        let shader =
+            parse("cbuffer B { vector<uint, 2> v; float4x3 m; matrix<int, 2, 3> n; matrix o; };")
+                .unwrap();
+        let members = &shader.constant_buffers[0].members;
+        assert_eq!(members[0].ty, Type::Vector(Scalar::Uint, 2));
+        assert_eq!(members[1].ty, float_matrix(4, 3, Major::Column));
+        assert_eq!(
+            members[2].ty,
+            Type::Matrix {
+                scalar: Scalar::Int,
+                rows: 2,
+                columns: 3,
+                major: Major::Column
+            }
+        );
+        assert_eq!(members[3].ty, float_matrix(4, 4, Major::Column));
+        let out = generate(&shader).unwrap();
+        assert!(out.contains("    pub m: [Vec4<f32>; 3],\n"));
+        assert!(out.contains("    pub n: [Vec4<i32>; 3],\n"));
+    }
+
+    #[test]
+    fn pragmas_and_attributes() {
+        let source = "#pragma warning(disable: 3571)\n\
+                      #pragma pack_matrix( row_major )\n\
+                      cbuffer B : register(b3) { float4x4 m; };\n\
+                      #pragma pack_matrix(column_major)\n\
+                      cbuffer C { float4x4 m; };\n\
+                      [numthreads(8, 8, 1)]\n\
+                      void cs(uint3 id : SV_DispatchThreadID) {}\n\
+                      RWStructuredBuffer<uint> out_ : register(u2);";
+        let shader = parse(source).unwrap();
+        assert_eq!(
+            shader.constant_buffers[0].members[0].ty,
+            float_matrix(4, 4, Major::Row)
+        );
+        assert_eq!(
+            shader.constant_buffers[1].members[0].ty,
+            float_matrix(4, 4, Major::Column)
+        );
+        assert_eq!(
+            shader.resources[0].register,
+            Register {
+                class: 'u',
+                index: 2,
+                space: 0
+            }
+        );
+        let out = generate(&shader).unwrap();
+        assert!(out.contains("pub const B_REGISTER: u32 = 3;"));
+        assert!(!out.contains("C_REGISTER"));
+        assert!(!out.contains("CS_INPUT_LAYOUT"));
+    }
+
+    #[test]
+    fn errors() {
+        let errors = [
+            (
+                "cbuffer B { Unknown a; };",
+                "line 1: unknown type 'Unknown'",
+            ),
+            (
+                "cbuffer B {\n float a\n float b; };",
+                "line 3: expected ';'",
+            ),
+            (
+                "struct S { float a; }\ncbuffer B { S s; };",
+                "line 2: expected ';'",
+            ),
+            (
+                "struct S {\n struct T { float a; } t;\n};",
+                "line 2: nested struct definitions are not supported; declare the struct first",
+            ),
+            (
+                "cbuffer B { float4x4 m[0]; };",
+                "line 1: array size must not be zero",
+            ),
+            (
+                "cbuffer B {\n float4x4 m[N]; };",
+                "line 2: expected a number; macros are not expanded",
+            ),
+            ("cbuffer B { float5 a; };", "line 1: unknown type 'float5'"),
+            (
+                "cbuffer B { matrix<float, 4, 5> m; };",
+                "line 1: invalid matrix size",
+            ),
+            (
+                "cbuffer B { vector<S, 2> v; };",
+                "line 1: invalid vector element type",
+            ),
+            (
+                "cbuffer B { float a : packoffset(c0); };",
+                "line 1: packoffset is not supported",
+            ),
+            (
+                "cbuffer B { double a; };",
+                "line 1: 64 bit type 'double' is not supported",
+            ),
+            (
+                "float loose;",
+                "line 1: global 'loose' outside of a cbuffer is not supported",
+            ),
+            ("typedef float real;", "line 1: 'typedef' is not supported"),
+            ("/* open", "line 1: unterminated comment"),
+            (
+                "cbuffer B : packoffset(c0) { float a; };",
+                "line 1: expected 'register'",
+            ),
+            (
+                "cbuffer B : register(x) { float a; };",
+                "line 1: invalid register 'x'",
+            ),
+            ("cbuffer B { float a;", "line 1: expected a name"),
+            (
+                "cbuffer B { float a[2]; float b; };",
+                "B.b is packed into the last register of the member before it, \
+                 which rust cannot share; move it or pad in the shader",
+            ),
+            (
+                "struct V { float3 p : POSITION; };\ncbuffer B { V v; };",
+                "B.v is a struct with semantics",
+            ),
+            (
+                "struct V { float3x3 m : NORMAL; };",
+                "V: float3x3 m is not a vertex attribute",
+            ),
+        ];
+        for (source, expected) in errors.iter() {
+            assert_eq!(error(source), *expected, "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(snake_case("VertexShader"), "vertex_shader");
+        assert_eq!(snake_case("MVP"), "mvp");
+        assert_eq!(snake_case("viewProjection"), "view_projection");
+        assert_eq!(snake_case("HDRTarget"), "hdr_target");
+        assert_eq!(snake_case("light2Color"), "light2_color");
+        assert_eq!(rust_name("type"), "r#type");
+        assert_eq!(rust_name("self"), "self_");
+    }
+
+    #[test]
+    fn skips_what_it_cannot_bind() {
+        let source = "#include \"common.hlsl\"\n\
+                      #define COUNT 4\n\
+                      static const float PI = 3.14159;\n\
+                      static const float2 offsets[2] = { float2(1, 0), float2(0, 1) };\n\
+                      groupshared float cache[64];\n\
+                      static float square(float x) { return x * x; }\n\
+                      cbuffer B : register(b0) { float4 tint; };\n\
+                      #ifdef DEBUG\n\
+                      #endif\n";
+        let shader = parse(source).unwrap();
+        assert_eq!(
+            shader.skipped,
+            vec![
+                "line 1: skipped directive '#include \"common.hlsl\"'",
+                "line 2: skipped directive '#define COUNT 4'",
+                "line 3: skipped 'static' declaration",
+                "line 4: skipped 'static' declaration",
+                "line 5: skipped 'groupshared' declaration",
+                "line 6: skipped 'static' declaration",
+                "line 8: skipped directive '#ifdef DEBUG'",
+                "line 9: skipped directive '#endif'",
+            ]
+        );
+        assert_eq!(shader.constant_buffers[0].members[0].name, "tint");
+        assert!(shader.functions.is_empty());
+        assert!(generate(&shader)
+            .unwrap()
+            .contains("pub const B_REGISTER: u32 = 0;"));
+    }
+
+    #[test]
+    fn skips_output_and_resource_parameters() {
+        let source = "struct V { float3 p : POSITION; };\n\
+                      void light(inout float3 color, in float3 n, out float a) {}\n\
+                      void split(float3 n : NORMAL, nointerpolation out float4 c : COLOR,\n\
+                      \x20          out matrix<float, 2, 2> m, float2 uv : TEXCOORD = 0) {}\n\
+                      float4 sample(Texture2D<float4> t, SamplerState s, float2 uv) { return 0; }\n\
+                      float4 main(V v, out float depth : SV_Depth) : SV_Target { return 0; }";
+        let shader = parse(source).unwrap();
+        let inputs = |i: usize| -> Vec<&str> {
+            shader.functions[i]
+                .inputs
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect()
+        };
+        assert_eq!(inputs(0), ["n"]);
+        assert_eq!(inputs(1), ["n", "uv"]);
+        assert_eq!(inputs(2), ["uv"]);
+        assert_eq!(inputs(3), ["v"]);
+        assert!(shader.skipped.is_empty());
+        let out = generate(&shader).unwrap();
+        assert!(out.contains("SPLIT_INPUT_LAYOUT"));
+        assert!(out.contains("MAIN_INPUT_LAYOUT"));
+        assert!(!out.contains("LIGHT_INPUT_LAYOUT"));
+    }
+}
@@ -0,0 +1,171 @@
+//! the surfaces a renderer draws into, one per window. what a surface is
+//! depends on the renderer; the set only keeps them by window and knows
+//! which ones are not to be drawn into right now.
+
+use std::collections::BTreeMap;
+
+use crate::window::id::WindowId;
+
+struct Entry<S> {
+    surface: S,
+    suspended: bool,
+}
+
+pub struct SurfaceSet<S> {
+    surfaces: BTreeMap<WindowId, Entry<S>>,
+}
+
+impl<S> Default for SurfaceSet<S> {
+    fn default() -> Self {
+        SurfaceSet {
+            surfaces: BTreeMap::new(),
+        }
+    }
+}
+
+impl<S> SurfaceSet<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds the surface of `window` and returns the one it replaces
+    pub fn insert(&mut self, window: WindowId, surface: S) -> Option<S> {
+        let entry = Entry {
+            surface: surface,
+            suspended: false,
+        };
+        self.surfaces
+            .insert(window, entry)
+            .map(|entry| entry.surface)
+    }
+
+    pub fn remove(&mut self, window: WindowId) -> Option<S> {
+        self.surfaces.remove(&window).map(|entry| entry.surface)
+    }
+
+    pub fn get(&self, window: WindowId) -> Option<&S> {
+        self.surfaces.get(&window).map(|entry| &entry.surface)
+    }
+
+    pub fn get_mut(&mut self, window: WindowId) -> Option<&mut S> {
+        self.surfaces
+            .get_mut(&window)
+            .map(|entry| &mut entry.surface)
+    }
+
+    /// like `get_mut`, with an error naming the window
+    pub fn expect_mut(&mut self, window: WindowId) -> Result<&mut S, String> {
+        self.get_mut(window)
+            .ok_or_else(|| format!("no surface for {}", window))
+    }
+
+    pub fn contains(&self, window: WindowId) -> bool {
+        self.surfaces.contains_key(&window)
+    }
+
+    pub fn len(&self) -> usize {
+        self.surfaces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.surfaces.is_empty()
+    }
+
+    /// keeps the surface of `window` from being drawn into, e.g. while the
+    /// window is minimized. returns false if there is no such surface.
+    pub fn set_suspended(&mut self, window: WindowId, suspended: bool) -> bool {
+        match self.surfaces.get_mut(&window) {
+            Some(entry) => {
+                entry.suspended = suspended;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_suspended(&self, window: WindowId) -> bool {
+        self.surfaces
+            .get(&window)
+            .is_some_and(|entry| entry.suspended)
+    }
+
+    /// every surface, in the order the windows were created
+    pub fn iter(&self) -> impl Iterator<Item = (WindowId, &S)> {
+        self.surfaces
+            .iter()
+            .map(|(window, entry)| (*window, &entry.surface))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (WindowId, &mut S)> {
+        self.surfaces
+            .iter_mut()
+            .map(|(window, entry)| (*window, &mut entry.surface))
+    }
+
+    /// the surfaces that are not suspended
+    pub fn active_mut(&mut self) -> impl Iterator<Item = (WindowId, &mut S)> {
+        self.surfaces
+            .iter_mut()
+            .filter(|(_, entry)| !entry.suspended)
+            .map(|(window, entry)| (*window, &mut entry.surface))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windows(set: &mut SurfaceSet<&str>) -> Vec<u32> {
+        set.active_mut().map(|(window, _)| window.0).collect()
+    }
+
+    #[test]
+    fn keeps_surfaces_by_window() {
+        let mut set = SurfaceSet::new();
+        assert!(set.is_empty());
+        assert_eq!(set.insert(WindowId(2), "b"), None);
+        assert_eq!(set.insert(WindowId(1), "a"), None);
+        assert_eq!(set.insert(WindowId(2), "B"), Some("b"));
+        let all: Vec<(u32, &str)> = set.iter().map(|(window, s)| (window.0, *s)).collect();
+        assert_eq!(all, vec![(1, "a"), (2, "B")]);
+        assert_eq!(set.get(WindowId(1)), Some(&"a"));
+        assert_eq!(
+            set.expect_mut(WindowId(9)),
+            Err("no surface for window 9".to_string())
+        );
+    }
+
+    #[test]
+    fn minimized_windows_are_not_active() {
+        let mut set = SurfaceSet::new();
+        set.insert(WindowId(1), "a");
+        set.insert(WindowId(2), "b");
+        assert!(set.set_suspended(WindowId(1), true));
+        assert!(!set.set_suspended(WindowId(9), true));
+        assert!(set.is_suspended(WindowId(1)) && !set.is_suspended(WindowId(2)));
+        assert_eq!(windows(&mut set), vec![2]);
+        // suspended surfaces are still there for resizes
+        assert_eq!(set.iter_mut().count(), 2);
+
+        assert!(set.set_suspended(WindowId(1), false));
+        assert_eq!(windows(&mut set), vec![1, 2]);
+
+        // a replaced surface starts out active
+        set.set_suspended(WindowId(2), true);
+        set.insert(WindowId(2), "B");
+        assert_eq!(windows(&mut set), vec![1, 2]);
+    }
+
+    #[test]
+    fn removing_a_suspended_window() {
+        let mut set = SurfaceSet::new();
+        set.insert(WindowId(1), "a");
+        set.insert(WindowId(2), "b");
+        set.set_suspended(WindowId(1), true);
+        assert_eq!(set.remove(WindowId(1)), Some("a"));
+        assert_eq!(set.remove(WindowId(1)), None);
+        assert!(!set.is_suspended(WindowId(1)) && !set.contains(WindowId(1)));
+        assert_eq!(set.len(), 1);
+        assert_eq!(windows(&mut set), vec![2]);
+    }
+}
@@ -0,0 +1,305 @@
+//! bookkeeping for work submitted to several command queues.
+//! nothing here talks to d3d; `direct3d::engine` turns the results into
+//! `Wait` calls.
+
+pub mod pool;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// a value a fence counts up to; d3d12's fences count in `u64`
+pub trait FenceValue: Copy + Ord + Default {}
+
+impl<V: Copy + Ord + Default> FenceValue for V {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueKind {
+    Direct,
+    Compute,
+    Copy,
+}
+
+pub const QUEUE_COUNT: usize = 3;
+
+impl QueueKind {
+    pub const ALL: [QueueKind; QUEUE_COUNT] =
+        [QueueKind::Direct, QueueKind::Compute, QueueKind::Copy];
+
+    pub fn index(self) -> usize {
+        match self {
+            QueueKind::Direct => 0,
+            QueueKind::Compute => 1,
+            QueueKind::Copy => 2,
+        }
+    }
+}
+
+/// the point in a queue's timeline at which its fence reaches `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncPoint<V = u64> {
+    pub queue: QueueKind,
+    pub value: V,
+}
+
+impl<V> SyncPoint<V> {
+    pub fn new(queue: QueueKind, value: V) -> Self {
+        SyncPoint {
+            queue: queue,
+            value: value,
+        }
+    }
+}
+
+/// remembers which fence values each queue has already been told to wait for
+#[derive(Debug, Default)]
+pub struct WaitTracker<V = u64> {
+    // waited[target][source]
+    waited: [[V; QUEUE_COUNT]; QUEUE_COUNT],
+}
+
+impl<V: FenceValue> WaitTracker<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the highest value of `source` that `target` has waited for
+    pub fn waited(&self, target: QueueKind, source: QueueKind) -> V {
+        self.waited[target.index()][source.index()]
+    }
+
+    /// returns the waits `target` has to insert before work that depends on
+    /// `deps` can start. there is at most one wait per source queue, for the
+    /// highest value needed. dependencies on `target` itself are ordered by the
+    /// queue already, and values that are `completed` or were waited for
+    /// earlier are skipped.
+    pub fn resolve(
+        &mut self,
+        target: QueueKind,
+        deps: &[SyncPoint<V>],
+        completed: [V; QUEUE_COUNT],
+    ) -> Vec<SyncPoint<V>> {
+        let mut needed = [V::default(); QUEUE_COUNT];
+        for dep in deps.iter().filter(|dep| dep.queue != target) {
+            let slot = &mut needed[dep.queue.index()];
+            *slot = (*slot).max(dep.value);
+        }
+        let mut waits = vec![];
+        for source in QueueKind::ALL.iter() {
+            let value = needed[source.index()];
+            let already = &mut self.waited[target.index()][source.index()];
+            if value > *already && value > completed[source.index()] {
+                *already = value;
+                waits.push(SyncPoint::new(*source, value));
+            }
+        }
+        waits
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ownership<V> {
+    Owned(QueueKind),
+    Releasing {
+        from: QueueKind,
+        to: QueueKind,
+        at: SyncPoint<V>,
+    },
+}
+
+/// tracks which queue may use a resource. a resource moves between queues by
+/// being released on one queue and then acquired on the other.
+pub struct OwnershipTracker<R: Copy + Eq + Hash, V = u64> {
+    owners: HashMap<R, Ownership<V>>,
+}
+
+impl<R: Copy + Eq + Hash, V> Default for OwnershipTracker<R, V> {
+    fn default() -> Self {
+        OwnershipTracker {
+            owners: HashMap::new(),
+        }
+    }
+}
+
+impl<R: Copy + Eq + Hash, V: FenceValue> OwnershipTracker<R, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, resource: R, queue: QueueKind) {
+        self.owners.insert(resource, Ownership::Owned(queue));
+    }
+
+    pub fn unregister(&mut self, resource: R) {
+        self.owners.remove(&resource);
+    }
+
+    pub fn owner(&self, resource: R) -> Option<QueueKind> {
+        match self.owners.get(&resource) {
+            Some(Ownership::Owned(queue)) => Some(*queue),
+            _ => None,
+        }
+    }
+
+    /// checks that `queue` may record work using `resource`
+    pub fn check(&self, resource: R, queue: QueueKind) -> Result<(), String> {
+        match self.owners.get(&resource) {
+            Some(Ownership::Owned(owner)) if *owner == queue => Ok(()),
+            Some(Ownership::Owned(owner)) => Err(format!(
+                "invalid use: resource is owned by {:?}, not {:?}",
+                owner, queue
+            )),
+            Some(Ownership::Releasing { from, to, .. }) => Err(format!(
+                "invalid use: resource is being handed from {:?} to {:?}",
+                from, to
+            )),
+            None => Err("invalid use: resource is not registered".to_string()),
+        }
+    }
+
+    /// hands `resource` from `from` to `to` once `from` reaches `at`
+    pub fn release(
+        &mut self,
+        resource: R,
+        from: QueueKind,
+        to: QueueKind,
+        at: SyncPoint<V>,
+    ) -> Result<(), String> {
+        self.check(resource, from)?;
+        if at.queue != from {
+            return Err(format!(
+                "invalid release: sync point is on {:?}, not on releasing queue {:?}",
+                at.queue, from
+            ));
+        }
+        self.owners
+            .insert(resource, Ownership::Releasing { from, to, at });
+        Ok(())
+    }
+
+    /// takes ownership of a released resource on `to`. returns the sync point
+    /// that `to` has to wait for before using it.
+    pub fn acquire(&mut self, resource: R, to: QueueKind) -> Result<SyncPoint<V>, String> {
+        match self.owners.get(&resource).copied() {
+            Some(Ownership::Releasing { to: target, at, .. }) if target == to => {
+                self.owners.insert(resource, Ownership::Owned(to));
+                Ok(at)
+            }
+            Some(Ownership::Releasing { to: target, .. }) => Err(format!(
+                "invalid acquire: resource was released to {:?}, not {:?}",
+                target, to
+            )),
+            Some(Ownership::Owned(owner)) => Err(format!(
+                "invalid acquire: resource is still owned by {:?}",
+                owner
+            )),
+            None => Err("invalid acquire: resource is not registered".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use QueueKind::*;
+
+    #[test]
+    fn waits_on_other_queues() {
+        let mut waits = WaitTracker::new();
+        let deps = [
+            SyncPoint::new(Compute, 3u64),
+            SyncPoint::new(Copy, 2),
+            SyncPoint::new(Compute, 5),
+        ];
+        assert_eq!(
+            waits.resolve(Direct, &deps, [0, 0, 0]),
+            vec![SyncPoint::new(Compute, 5), SyncPoint::new(Copy, 2)]
+        );
+        assert_eq!(waits.waited(Direct, Compute), 5);
+        assert_eq!(waits.waited(Direct, Copy), 2);
+        // each target tracks its own waits
+        assert_eq!(waits.waited(Copy, Compute), 0);
+        assert_eq!(
+            waits.resolve(Copy, &deps, [0, 0, 0]),
+            vec![SyncPoint::new(Compute, 5)]
+        );
+    }
+
+    #[test]
+    fn skips_waits_that_are_not_needed() {
+        let mut waits = WaitTracker::new();
+        // the queue orders its own work
+        assert!(waits
+            .resolve(Direct, &[SyncPoint::new(Direct, 7u64)], [0, 0, 0])
+            .is_empty());
+        // already completed
+        assert!(waits
+            .resolve(Direct, &[SyncPoint::new(Compute, 4)], [0, 4, 0])
+            .is_empty());
+        assert_eq!(waits.waited(Direct, Compute), 0);
+
+        assert_eq!(
+            waits.resolve(Direct, &[SyncPoint::new(Compute, 6)], [0, 4, 0]),
+            vec![SyncPoint::new(Compute, 6)]
+        );
+        // waited for earlier, or covered by a later wait
+        for value in [5, 6].iter() {
+            assert!(waits
+                .resolve(Direct, &[SyncPoint::new(Compute, *value)], [0, 4, 0])
+                .is_empty());
+        }
+        assert_eq!(
+            waits.resolve(Direct, &[SyncPoint::new(Compute, 8)], [0, 4, 0]),
+            vec![SyncPoint::new(Compute, 8)]
+        );
+    }
+
+    #[test]
+    fn hands_resources_between_queues() {
+        let mut owners: OwnershipTracker<u32> = OwnershipTracker::new();
+        owners.register(1, Copy);
+        assert_eq!(owners.owner(1), Some(Copy));
+        assert!(owners.check(1, Copy).is_ok());
+        assert!(owners.check(1, Direct).is_err());
+
+        // the release has to happen on the owning queue, at one of its points
+        assert!(owners
+            .release(1, Direct, Compute, SyncPoint::new(Direct, 1))
+            .is_err());
+        assert!(owners
+            .release(1, Copy, Direct, SyncPoint::new(Direct, 1))
+            .is_err());
+        assert!(owners
+            .release(1, Copy, Direct, SyncPoint::new(Copy, 9))
+            .is_ok());
+
+        // nobody may use it while it is in transit
+        assert_eq!(owners.owner(1), None);
+        assert!(owners.check(1, Copy).is_err());
+        assert!(owners.check(1, Direct).is_err());
+        assert!(owners.acquire(1, Compute).is_err());
+
+        assert_eq!(owners.acquire(1, Direct), Ok(SyncPoint::new(Copy, 9)));
+        assert_eq!(owners.owner(1), Some(Direct));
+        assert!(owners.acquire(1, Direct).is_err());
+
+        owners.unregister(1);
+        assert!(owners.check(1, Direct).is_err());
+        assert!(owners.acquire(1, Direct).is_err());
+    }
+
+    #[test]
+    fn acquire_waits_are_resolved_like_any_other() {
+        let mut owners: OwnershipTracker<u32> = OwnershipTracker::new();
+        let mut waits = WaitTracker::new();
+        owners.register(1, Copy);
+        owners
+            .release(1, Copy, Direct, SyncPoint::new(Copy, 4))
+            .unwrap();
+        let point = owners.acquire(1, Direct).unwrap();
+        assert_eq!(
+            waits.resolve(Direct, &[point], [0, 0, 3]),
+            vec![SyncPoint::new(Copy, 4)]
+        );
+        assert!(waits.resolve(Direct, &[point], [0, 0, 3]).is_empty());
+    }
+}
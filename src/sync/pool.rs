@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+use super::FenceValue;
+
+/// objects that the gpu may still be using, released in fence order.
+/// an object handed back with fence value `n` can be reused once the fence
+/// has completed `n`.
+pub struct FencedPool<T, V = u64> {
+    free: Vec<T>,
+    in_flight: VecDeque<(V, T)>,
+}
+
+impl<T, V> Default for FencedPool<T, V> {
+    fn default() -> Self {
+        FencedPool {
+            free: vec![],
+            in_flight: VecDeque::new(),
+        }
+    }
+}
+
+impl<T, V: FenceValue> FencedPool<T, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// moves everything finished by `completed` to the free list
+    pub fn reclaim(&mut self, completed: V) {
+        while let Some((value, _)) = self.in_flight.front() {
+            if *value > completed {
+                break;
+            }
+            let (_, item) = self.in_flight.pop_front().unwrap();
+            self.free.push(item);
+        }
+    }
+
+    /// takes a reusable object, or `None` if every object is still in flight
+    pub fn acquire(&mut self, completed: V) -> Option<T> {
+        self.reclaim(completed);
+        self.free.pop()
+    }
+
+    /// returns `item` to the pool once the fence reaches `fence_value`
+    pub fn release(&mut self, item: T, fence_value: V) {
+        // keep in_flight sorted so that reclaim can stop at the first pending value
+        let pos = self
+            .in_flight
+            .iter()
+            .position(|(value, _)| *value > fence_value)
+            .unwrap_or(self.in_flight.len());
+        self.in_flight.insert(pos, (fence_value, item));
+    }
+
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// removes every object, for destruction once the gpu is idle
+    pub fn drain(&mut self) -> Vec<T> {
+        let mut items: Vec<T> = self.free.drain(..).collect();
+        items.extend(self.in_flight.drain(..).map(|(_, item)| item));
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_only_after_the_fence_passes() {
+        let mut pool = FencedPool::new();
+        pool.release("a", 1u64);
+        assert_eq!(pool.acquire(0), None);
+        assert_eq!(pool.in_flight_count(), 1);
+        assert_eq!(pool.acquire(1), Some("a"));
+        assert_eq!(pool.acquire(1), None);
+
+        pool.release("b", 3);
+        pool.release("c", 2);
+        assert_eq!(pool.acquire(1), None);
+        assert_eq!(pool.acquire(2), Some("c"));
+        assert_eq!(pool.acquire(2), None);
+        pool.reclaim(5);
+        assert_eq!(pool.free_count(), 1);
+        assert_eq!(pool.in_flight_count(), 0);
+        assert_eq!(pool.acquire(5), Some("b"));
+    }
+
+    #[test]
+    fn release_keeps_fence_order() {
+        let mut pool = FencedPool::new();
+        for (item, value) in [(4u32, 4u32), (2, 2), (3, 3), (1, 1)].iter() {
+            pool.release(*item, *value);
+        }
+        for completed in 1..=4 {
+            pool.reclaim(completed);
+            assert_eq!(pool.free_count(), completed as usize);
+            assert_eq!(pool.free.last(), Some(&completed));
+        }
+    }
+
+    #[test]
+    fn drain_takes_free_and_in_flight() {
+        let mut pool = FencedPool::new();
+        pool.release(1, 1u64);
+        pool.release(2, 10);
+        pool.reclaim(1);
+        let mut items = pool.drain();
+        items.sort();
+        assert_eq!(items, vec![1, 2]);
+        assert_eq!(pool.free_count() + pool.in_flight_count(), 0);
+    }
+}
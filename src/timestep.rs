@@ -0,0 +1,213 @@
+//! fixed timestep simulation: the loop runs `update` in steps of the same
+//! length however long frames take, and renders in between with an
+//! interpolation factor.
+
+use std::time::Duration;
+
+/// what the loop has to do for one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// number of fixed updates to run
+    pub steps: u32,
+    /// how far rendering is between the last step and the next, in [0, 1)
+    pub alpha: f32,
+    /// time thrown away because the frame took longer than `max_frame_time`
+    pub dropped: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    step: Duration,
+    max_frame_time: Duration,
+    frame_cap: Option<Duration>,
+    accumulator: Duration,
+    last: Option<Duration>,
+}
+
+impl FixedTimestep {
+    /// steps of `step`, which must not be zero. frames longer than a quarter
+    /// of a second are clamped so that a stall does not snowball.
+    pub fn new(step: Duration) -> Result<Self, String> {
+        if step == Duration::from_secs(0) {
+            return Err("invalid timestep 0".to_string());
+        }
+        Ok(FixedTimestep {
+            step: step,
+            max_frame_time: Duration::from_millis(250),
+            frame_cap: None,
+            accumulator: Duration::from_secs(0),
+            last: None,
+        })
+    }
+
+    /// `rate` steps per second, which must not be zero
+    pub fn from_rate(rate: u32) -> Result<Self, String> {
+        if rate == 0 {
+            return Err("invalid update rate 0".to_string());
+        }
+        Self::new(Duration::from_secs(1) / rate)
+    }
+
+    pub fn with_max_frame_time(mut self, max_frame_time: Duration) -> Self {
+        self.max_frame_time = max_frame_time;
+        self
+    }
+
+    /// limits rendering to `fps` frames per second, which must not be zero
+    pub fn with_frame_cap(mut self, fps: u32) -> Result<Self, String> {
+        if fps == 0 {
+            return Err("invalid frame cap 0".to_string());
+        }
+        self.frame_cap = Some(Duration::from_secs(1) / fps);
+        Ok(self)
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// forgets the time of the last frame, e.g. after the loop was paused
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    /// called at the start of every frame with the current time
+    pub fn advance(&mut self, now: Duration) -> Frame {
+        let elapsed = match self.last {
+            Some(last) => now.saturating_sub(last),
+            None => Duration::from_secs(0),
+        };
+        self.last = Some(now);
+        let dropped = elapsed.saturating_sub(self.max_frame_time);
+        self.accumulator += elapsed - dropped;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        Frame {
+            steps: steps,
+            alpha: self.accumulator.as_secs_f32() / self.step.as_secs_f32(),
+            dropped: dropped,
+        }
+    }
+
+    /// how long to wait after a frame that started at `frame_start` so that
+    /// the frame cap holds
+    pub fn cap_delay(&self, frame_start: Duration, now: Duration) -> Option<Duration> {
+        let cap = self.frame_cap?;
+        let spent = now.saturating_sub(frame_start);
+        if spent < cap {
+            Some(cap - spent)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, FakeClock};
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn runs_whole_steps() {
+        let clock = FakeClock::new();
+        let mut timestep = FixedTimestep::new(ms(10)).unwrap();
+        assert_eq!(timestep.advance(clock.now()).steps, 0);
+        let mut steps = vec![];
+        for frame_time in [5, 5, 25, 9, 1, 5].iter() {
+            clock.advance(ms(*frame_time));
+            steps.push(timestep.advance(clock.now()).steps);
+        }
+        assert_eq!(steps, vec![0, 1, 2, 1, 0, 1]);
+        assert_eq!(FixedTimestep::from_rate(50).unwrap().step(), ms(20));
+    }
+
+    #[test]
+    fn alpha_is_the_leftover_fraction() {
+        let clock = FakeClock::new();
+        let mut timestep = FixedTimestep::new(ms(10)).unwrap();
+        timestep.advance(clock.now());
+        clock.advance(ms(15));
+        let frame = timestep.advance(clock.now());
+        assert_eq!(frame.steps, 1);
+        assert!((frame.alpha - 0.5).abs() < 1e-6);
+        clock.advance(ms(2));
+        assert!((timestep.advance(clock.now()).alpha - 0.7).abs() < 1e-6);
+        clock.advance(ms(3));
+        let frame = timestep.advance(clock.now());
+        assert_eq!((frame.steps, frame.alpha), (1, 0.0));
+    }
+
+    #[test]
+    fn long_frames_are_clamped() {
+        let clock = FakeClock::new();
+        let mut timestep = FixedTimestep::new(ms(10))
+            .unwrap()
+            .with_max_frame_time(ms(100));
+        timestep.advance(clock.now());
+        clock.advance(ms(1000));
+        let frame = timestep.advance(clock.now());
+        assert_eq!(frame.steps, 10);
+        assert_eq!(frame.dropped, ms(900));
+
+        // the default clamps at a quarter of a second
+        let mut timestep = FixedTimestep::new(ms(10)).unwrap();
+        timestep.advance(clock.now());
+        clock.advance(ms(5000));
+        assert_eq!(timestep.advance(clock.now()).steps, 25);
+    }
+
+    #[test]
+    fn reset_forgets_the_pause() {
+        let clock = FakeClock::new();
+        let mut timestep = FixedTimestep::new(ms(10)).unwrap();
+        timestep.advance(clock.now());
+        clock.advance(ms(200));
+        timestep.reset();
+        assert_eq!(timestep.advance(clock.now()).steps, 0);
+    }
+
+    #[test]
+    fn frame_cap_waits_out_the_frame() {
+        let mut clock = FakeClock::new();
+        let timestep = FixedTimestep::new(ms(10))
+            .unwrap()
+            .with_frame_cap(50)
+            .unwrap();
+        let start = clock.now();
+        clock.advance(ms(5));
+        let delay = timestep.cap_delay(start, clock.now());
+        assert_eq!(delay, Some(ms(15)));
+        clock.sleep(delay.unwrap());
+        assert_eq!(clock.now(), ms(20));
+        assert_eq!(timestep.cap_delay(start, clock.now()), None);
+        assert_eq!(timestep.cap_delay(start, ms(30)), None);
+        assert_eq!(
+            FixedTimestep::new(ms(10)).unwrap().cap_delay(start, ms(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn zeros_are_invalid() {
+        assert_eq!(
+            FixedTimestep::new(ms(0)).err(),
+            Some("invalid timestep 0".to_string())
+        );
+        assert_eq!(
+            FixedTimestep::from_rate(0).err(),
+            Some("invalid update rate 0".to_string())
+        );
+        assert_eq!(
+            FixedTimestep::new(ms(10)).unwrap().with_frame_cap(0).err(),
+            Some("invalid frame cap 0".to_string())
+        );
+    }
+}
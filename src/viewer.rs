@@ -0,0 +1,137 @@
+use dx12_in_rust::{
+    app::{self, App, Context},
+    direct3d::Direct3D,
+    event::Event,
+    input::actions::ActionMap,
+    options::Options,
+    replay::{Playback, Recording, Replay},
+    timestep::FixedTimestep,
+    util,
+    window::{id::WindowId, Window},
+};
+
+fn enable_debug_layer() {
+    use std::ptr::null_mut;
+    use winapi::ctypes::c_void;
+    use winapi::um::d3d12::D3D12GetDebugInterface;
+    use winapi::um::d3d12sdklayers::ID3D12Debug;
+    use winapi::Interface;
+    let mut debug_layer: *mut ID3D12Debug = null_mut();
+    let result = unsafe {
+        D3D12GetDebugInterface(
+            &ID3D12Debug::uuidof(),
+            &mut debug_layer as *mut *mut _ as *mut *mut c_void,
+        )
+    };
+    if util::is_succeeded(result) {
+        eprintln!("enable debug layer");
+        unsafe {
+            (*debug_layer).EnableDebugLayer();
+            (*debug_layer).Release();
+        }
+    }
+}
+
+struct Viewer {
+    actions: ActionMap,
+}
+
+impl App<Window, Direct3D> for Viewer {
+    fn init(&mut self, _context: &mut Context<Window, Direct3D>) -> Result<(), String> {
+        self.actions = ActionMap::load("resource/actions.cfg")?;
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        context: &mut Context<Window, Direct3D>,
+        _dt: std::time::Duration,
+    ) -> Result<(), String> {
+        if self.actions.was_triggered("quit", &context.input) {
+            context.quit();
+        }
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        context: &mut Context<Window, Direct3D>,
+        _window: WindowId,
+        event: &Event,
+    ) -> Result<(), String> {
+        if event.is_alt_enter() {
+            context.renderer.toggle_window_mode(&mut context.platform)?;
+        }
+        if let Event::FileDropped(ref path) = *event {
+            eprintln!("dropped {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+pub fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+    if options.list_displays {
+        match dx12_in_rust::direct3d::list_displays(&options.adapter) {
+            Ok((adapter, outputs)) => {
+                println!("{}", adapter);
+                for output in outputs {
+                    print!("{}", output);
+                }
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let mut replay = if let Some(ref path) = options.playback {
+        match Recording::load(path) {
+            Ok(recording) => Replay::Playback(Playback::new(recording)),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    } else if options.record.is_some() {
+        let step = FixedTimestep::from_rate(app::UPDATE_RATE).unwrap().step();
+        Replay::Record(Recording::new(step))
+    } else {
+        Replay::Live
+    };
+
+    let mut window = Window::create("dx12 test in rust", 640, 480);
+    enable_debug_layer();
+
+    let direct3d = Direct3D::create(&window, &options.adapter, options.present).unwrap();
+
+    window.register_raw_mouse().unwrap();
+    window.accept_files(true);
+    window.show();
+
+    let mut context = Context::new(window, direct3d);
+    let result = app::run_replay(
+        &mut context,
+        &mut Viewer {
+            actions: ActionMap::new(),
+        },
+        &mut replay,
+    );
+    // a run that failed is the one worth replaying, so save it either way
+    if let (Some(path), Some(recording)) = (&options.record, replay.recording()) {
+        if let Err(err) = recording.save(path) {
+            eprintln!("{}", err);
+        }
+    }
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
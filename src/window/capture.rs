@@ -0,0 +1,204 @@
+//! what the cursor does while the window has the focus. the state machine
+//! here decides, `Window` carries out the commands.
+
+use super::rect::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureMode {
+    /// the cursor moves freely
+    Free,
+    /// the cursor is visible but cannot leave the client area
+    Confined,
+    /// the cursor is hidden and held in the client area; motion is read from
+    /// raw input, as for a first person camera
+    Relative,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureCommand {
+    ShowCursor(bool),
+    /// clip the cursor to a screen rectangle, or release it
+    Clip(Option<Rect>),
+    SaveCursorPosition,
+    RestoreCursorPosition,
+    CenterCursor,
+}
+
+pub struct Capture {
+    wanted: CaptureMode,
+    /// what is applied right now; `Free` while the window is in the background
+    applied: CaptureMode,
+    focused: bool,
+    client: Rect,
+}
+
+impl Capture {
+    /// `client` is the client area in screen coordinates
+    pub fn new(client: Rect) -> Self {
+        Capture {
+            wanted: CaptureMode::Free,
+            applied: CaptureMode::Free,
+            focused: true,
+            client: client,
+        }
+    }
+
+    pub fn mode(&self) -> CaptureMode {
+        self.wanted
+    }
+
+    /// whether the cursor is captured right now
+    pub fn applied(&self) -> CaptureMode {
+        self.applied
+    }
+
+    pub fn set_mode(&mut self, mode: CaptureMode) -> Vec<CaptureCommand> {
+        self.wanted = mode;
+        self.apply()
+    }
+
+    /// the window got or lost the focus, e.g. by alt+tab
+    pub fn focus(&mut self, focused: bool) -> Vec<CaptureCommand> {
+        self.focused = focused;
+        self.apply()
+    }
+
+    /// the client area moved or changed size; the clip rectangle follows
+    pub fn client_moved(&mut self, client: Rect) -> Vec<CaptureCommand> {
+        self.client = client;
+        match self.applied {
+            CaptureMode::Free => vec![],
+            _ => vec![CaptureCommand::Clip(Some(client))],
+        }
+    }
+
+    fn apply(&mut self) -> Vec<CaptureCommand> {
+        use self::CaptureCommand::*;
+        let target = if self.focused {
+            self.wanted
+        } else {
+            CaptureMode::Free
+        };
+        let from = self.applied;
+        self.applied = target;
+        if from == target {
+            return vec![];
+        }
+        let mut commands = vec![];
+        if from == CaptureMode::Relative {
+            commands.push(ShowCursor(true));
+        }
+        match target {
+            CaptureMode::Free => commands.push(Clip(None)),
+            CaptureMode::Confined => commands.push(Clip(Some(self.client))),
+            CaptureMode::Relative => {
+                commands.push(SaveCursorPosition);
+                commands.push(ShowCursor(false));
+                commands.push(Clip(Some(self.client)));
+                commands.push(CenterCursor);
+            }
+        }
+        // after the clip changed, so that the position is not clamped to it
+        if from == CaptureMode::Relative {
+            commands.push(RestoreCursorPosition);
+        }
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CaptureCommand::*;
+    use super::*;
+
+    fn client() -> Rect {
+        Rect::new(100, 100, 740, 580)
+    }
+
+    #[test]
+    fn starts_free() {
+        let mut capture = Capture::new(client());
+        assert_eq!(capture.mode(), CaptureMode::Free);
+        assert_eq!(capture.set_mode(CaptureMode::Free), vec![]);
+        assert_eq!(capture.focus(false), vec![]);
+        assert_eq!(capture.client_moved(client()), vec![]);
+    }
+
+    #[test]
+    fn confined_clips_to_the_client_area() {
+        let mut capture = Capture::new(client());
+        assert_eq!(
+            capture.set_mode(CaptureMode::Confined),
+            vec![Clip(Some(client()))]
+        );
+        assert_eq!(capture.set_mode(CaptureMode::Confined), vec![]);
+        let moved = Rect::new(0, 0, 640, 480);
+        assert_eq!(capture.client_moved(moved), vec![Clip(Some(moved))]);
+        assert_eq!(capture.set_mode(CaptureMode::Free), vec![Clip(None)]);
+    }
+
+    #[test]
+    fn relative_hides_and_restores_the_cursor() {
+        let mut capture = Capture::new(client());
+        assert_eq!(
+            capture.set_mode(CaptureMode::Relative),
+            vec![
+                SaveCursorPosition,
+                ShowCursor(false),
+                Clip(Some(client())),
+                CenterCursor
+            ]
+        );
+        assert_eq!(capture.applied(), CaptureMode::Relative);
+        // the position is restored after the clip is gone
+        assert_eq!(
+            capture.set_mode(CaptureMode::Free),
+            vec![ShowCursor(true), Clip(None), RestoreCursorPosition]
+        );
+    }
+
+    #[test]
+    fn relative_to_confined_keeps_the_clip() {
+        let mut capture = Capture::new(client());
+        capture.set_mode(CaptureMode::Relative);
+        assert_eq!(
+            capture.set_mode(CaptureMode::Confined),
+            vec![
+                ShowCursor(true),
+                Clip(Some(client())),
+                RestoreCursorPosition
+            ]
+        );
+        assert_eq!(
+            capture.set_mode(CaptureMode::Relative),
+            vec![
+                SaveCursorPosition,
+                ShowCursor(false),
+                Clip(Some(client())),
+                CenterCursor
+            ]
+        );
+    }
+
+    #[test]
+    fn losing_the_focus_releases_until_it_comes_back() {
+        let mut capture = Capture::new(client());
+        capture.set_mode(CaptureMode::Relative);
+        assert_eq!(
+            capture.focus(false),
+            vec![ShowCursor(true), Clip(None), RestoreCursorPosition]
+        );
+        assert_eq!(capture.applied(), CaptureMode::Free);
+        assert_eq!(capture.mode(), CaptureMode::Relative);
+
+        // while in the background, changes are only remembered
+        let moved = Rect::new(0, 0, 640, 480);
+        assert_eq!(capture.client_moved(moved), vec![]);
+        assert_eq!(capture.set_mode(CaptureMode::Confined), vec![]);
+        assert_eq!(capture.applied(), CaptureMode::Free);
+
+        assert_eq!(capture.focus(true), vec![Clip(Some(moved))]);
+        assert_eq!(capture.applied(), CaptureMode::Confined);
+        assert_eq!(capture.focus(true), vec![]);
+    }
+}
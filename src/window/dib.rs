@@ -0,0 +1,319 @@
+//! `CF_DIB` clipboard data: a `BITMAPINFOHEADER`, the color masks and
+//! table, and the pixel rows, decoded into rgba
+
+use std::convert::TryInto;
+
+// biCompression
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// size of `BITMAPINFOHEADER`; later versions of the header are longer
+const INFO_HEADER_SIZE: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// rgba, 8 bits per channel, rows from the top
+    pub pixels: Vec<u8>,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn truncated() -> String {
+    "truncated bitmap".to_string()
+}
+
+/// a color channel given by a bit mask
+#[derive(Debug, Clone, Copy)]
+struct Channel {
+    shift: u32,
+    max: u32,
+}
+
+impl Channel {
+    fn new(mask: u32) -> Channel {
+        if mask == 0 {
+            return Channel { shift: 0, max: 0 };
+        }
+        let shift = mask.trailing_zeros();
+        Channel {
+            shift: shift,
+            max: mask >> shift,
+        }
+    }
+
+    /// the channel of `pixel` scaled to 8 bits, or `missing` without a mask
+    fn get(&self, pixel: u32, missing: u8) -> u8 {
+        if self.max == 0 {
+            return missing;
+        }
+        let value = ((pixel >> self.shift) & self.max) as u64;
+        ((value * 255 + self.max as u64 / 2) / self.max as u64) as u8
+    }
+}
+
+pub fn decode_dib(bytes: &[u8]) -> Result<Image, String> {
+    let header_size = u32_at(bytes, 0).ok_or_else(truncated)? as usize;
+    if header_size < INFO_HEADER_SIZE {
+        return Err(format!(
+            "unsupported bitmap header of {} bytes",
+            header_size
+        ));
+    }
+    let width = u32_at(bytes, 4).ok_or_else(truncated)? as i32;
+    let height = u32_at(bytes, 8).ok_or_else(truncated)? as i32;
+    let bit_count = u16_at(bytes, 14).ok_or_else(truncated)? as u32;
+    let compression = u32_at(bytes, 16).ok_or_else(truncated)?;
+    let colors_used = u32_at(bytes, 32).ok_or_else(truncated)? as usize;
+    if width <= 0 || height == 0 {
+        return Err(format!("invalid bitmap size {}x{}", width, height));
+    }
+    // positive heights are stored bottom row first
+    let bottom_up = height > 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+
+    let mask = |at| u32_at(bytes, at).ok_or_else(truncated);
+    let mut offset = header_size;
+    let masks = match compression {
+        BI_RGB => match bit_count {
+            16 => [0x7c00, 0x03e0, 0x001f, 0],
+            // the top byte is reserved, but often holds alpha
+            32 => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+            _ => [0; 4],
+        },
+        BI_BITFIELDS | BI_ALPHABITFIELDS => {
+            let alpha = compression == BI_ALPHABITFIELDS;
+            // later headers carry the masks, the first one is followed by them
+            let at = if header_size > INFO_HEADER_SIZE {
+                INFO_HEADER_SIZE
+            } else {
+                offset += if alpha { 16 } else { 12 };
+                header_size
+            };
+            let has_alpha = alpha || header_size >= INFO_HEADER_SIZE + 16;
+            [
+                mask(at)?,
+                mask(at + 4)?,
+                mask(at + 8)?,
+                if has_alpha { mask(at + 12)? } else { 0 },
+            ]
+        }
+        _ => return Err(format!("unsupported bitmap compression {}", compression)),
+    };
+
+    let palette_len = if bit_count <= 8 && colors_used == 0 {
+        1 << bit_count
+    } else {
+        colors_used
+    };
+    let palette = bytes
+        .get(offset..offset + palette_len * 4)
+        .ok_or_else(truncated)?;
+    offset += palette_len * 4;
+
+    let stride = (width * bit_count as usize).div_ceil(32) * 4;
+    let size = stride.checked_mul(height).ok_or_else(truncated)?;
+    let data = bytes
+        .get(offset..offset.checked_add(size).ok_or_else(truncated)?)
+        .ok_or_else(truncated)?;
+
+    let [red, green, blue, alpha] = masks.map(Channel::new);
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row = if bottom_up { height - 1 - y } else { y };
+        let row = &data[row * stride..(row + 1) * stride];
+        for x in 0..width {
+            let rgba = match bit_count {
+                1 | 4 | 8 => {
+                    let bit = x * bit_count as usize;
+                    let byte = row[bit / 8];
+                    let index = (byte >> (8 - bit_count as usize - bit % 8)) as usize
+                        & ((1 << bit_count) - 1);
+                    match palette.get(index * 4..index * 4 + 3) {
+                        Some(bgr) => [bgr[2], bgr[1], bgr[0], 255],
+                        None => [0, 0, 0, 255],
+                    }
+                }
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+                16 | 32 => {
+                    let pixel = if bit_count == 16 {
+                        u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32
+                    } else {
+                        u32::from_le_bytes(row[x * 4..x * 4 + 4].try_into().unwrap())
+                    };
+                    [
+                        red.get(pixel, 0),
+                        green.get(pixel, 0),
+                        blue.get(pixel, 0),
+                        alpha.get(pixel, 255),
+                    ]
+                }
+                _ => return Err(format!("unsupported bitmap depth {}", bit_count)),
+            };
+            pixels.extend_from_slice(&rgba);
+        }
+    }
+    // most applications leave the reserved byte at 0, which is not transparent
+    if compression == BI_RGB && bit_count == 32 && pixels.chunks(4).all(|p| p[3] == 0) {
+        for pixel in pixels.chunks_mut(4) {
+            pixel[3] = 255;
+        }
+    }
+    Ok(Image {
+        width: width as u32,
+        height: height as u32,
+        pixels: pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a `BITMAPINFOHEADER`, padded to `size` bytes
+    fn header(
+        width: i32,
+        height: i32,
+        bit_count: u16,
+        compression: u32,
+        size: u32,
+        colors_used: u32,
+    ) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(size.to_le_bytes());
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(bit_count.to_le_bytes());
+        bytes.extend(compression.to_le_bytes());
+        bytes.extend([0; 12]);
+        bytes.extend(colors_used.to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.resize(size as usize, 0);
+        bytes
+    }
+
+    #[test]
+    fn decodes_a_screenshot() {
+        // how windows hands over a screenshot: 32 bits with color masks
+        // after the header, bottom row first
+        let image = decode_dib(include_bytes!("../../tests/fixtures/screenshot.dib")).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        #[rustfmt::skip]
+        let pixels = vec![
+            255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255,
+            255, 255, 255, 255, 0, 0, 0, 255, 128, 128, 128, 255,
+        ];
+        assert_eq!(image.pixels, pixels);
+    }
+
+    #[test]
+    fn rgb_24_bottom_up() {
+        let mut bytes = header(2, 2, 24, BI_RGB, 40, 0);
+        // rows are padded to 4 bytes
+        bytes.extend([255, 0, 0, 0, 255, 0, 0, 0]);
+        bytes.extend([0, 0, 255, 255, 255, 255, 0, 0]);
+        let image = decode_dib(&bytes).unwrap();
+        #[rustfmt::skip]
+        let pixels = vec![
+            255, 0, 0, 255, 255, 255, 255, 255,
+            0, 0, 255, 255, 0, 255, 0, 255,
+        ];
+        assert_eq!(image.pixels, pixels);
+    }
+
+    #[test]
+    fn reserved_byte_is_alpha_unless_all_zero() {
+        let mut bytes = header(1, -2, 32, BI_RGB, 40, 0);
+        bytes.extend([1, 2, 3, 0, 4, 5, 6, 0]);
+        assert_eq!(
+            decode_dib(&bytes).unwrap().pixels,
+            vec![3, 2, 1, 255, 6, 5, 4, 255]
+        );
+        let mut bytes = header(1, -2, 32, BI_RGB, 40, 0);
+        bytes.extend([1, 2, 3, 128, 4, 5, 6, 0]);
+        assert_eq!(
+            decode_dib(&bytes).unwrap().pixels,
+            vec![3, 2, 1, 128, 6, 5, 4, 0]
+        );
+    }
+
+    #[test]
+    fn masks_after_and_inside_the_header() {
+        let mut bytes = header(1, 1, 16, BI_BITFIELDS, 40, 0);
+        for mask in [0xf800u32, 0x07e0, 0x001f].iter() {
+            bytes.extend(mask.to_le_bytes());
+        }
+        bytes.extend([0x00, 0xf8, 0, 0]);
+        assert_eq!(decode_dib(&bytes).unwrap().pixels, vec![255, 0, 0, 255]);
+
+        // a BITMAPV5HEADER carries the masks, alpha included
+        let mut bytes = header(1, 1, 32, BI_BITFIELDS, 124, 0);
+        for (i, mask) in [0xffu32, 0xff00, 0xff_0000, 0xff00_0000].iter().enumerate() {
+            bytes[40 + i * 4..44 + i * 4].copy_from_slice(&mask.to_le_bytes());
+        }
+        bytes.extend([10, 20, 30, 40]);
+        assert_eq!(decode_dib(&bytes).unwrap().pixels, vec![10, 20, 30, 40]);
+
+        // 16 bits without masks are 555
+        let mut bytes = header(1, 1, 16, BI_RGB, 40, 0);
+        bytes.extend([0x1f, 0, 0, 0]);
+        assert_eq!(decode_dib(&bytes).unwrap().pixels, vec![0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn palettes() {
+        let mut bytes = header(10, 1, 1, BI_RGB, 40, 0);
+        bytes.extend([0, 0, 0, 0, 255, 255, 255, 0]);
+        bytes.extend([0b1010_0000, 0b0100_0000, 0, 0]);
+        let image = decode_dib(&bytes).unwrap();
+        let bits: Vec<u8> = image.pixels.chunks(4).map(|pixel| pixel[0] / 255).collect();
+        assert_eq!(bits, vec![1, 0, 1, 0, 0, 0, 0, 0, 0, 1]);
+
+        // indices past a short palette are black
+        let mut bytes = header(3, 1, 4, BI_RGB, 40, 2);
+        bytes.extend([0, 0, 255, 0, 0, 255, 0, 0]);
+        bytes.extend([0x10, 0x50, 0, 0]);
+        #[rustfmt::skip]
+        let pixels = vec![0, 255, 0, 255, 255, 0, 0, 255, 0, 0, 0, 255];
+        assert_eq!(decode_dib(&bytes).unwrap().pixels, pixels);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(decode_dib(&[]), Err("truncated bitmap".to_string()));
+        assert_eq!(
+            decode_dib(&header(1, 1, 24, BI_RGB, 12, 0)),
+            Err("unsupported bitmap header of 12 bytes".to_string())
+        );
+        assert_eq!(
+            decode_dib(&header(0, 1, 24, BI_RGB, 40, 0)),
+            Err("invalid bitmap size 0x1".to_string())
+        );
+        assert_eq!(
+            decode_dib(&header(4, 4, 24, BI_RGB, 40, 0)),
+            Err("truncated bitmap".to_string())
+        );
+        let mut bytes = header(1, 1, 24, 1, 40, 0);
+        bytes.extend([0; 4]);
+        assert_eq!(
+            decode_dib(&bytes),
+            Err("unsupported bitmap compression 1".to_string())
+        );
+        let mut bytes = header(1, 1, 2, BI_RGB, 40, 0);
+        bytes.extend([0; 20]);
+        assert_eq!(
+            decode_dib(&bytes),
+            Err("unsupported bitmap depth 2".to_string())
+        );
+        assert!(decode_dib(&header(i32::MAX, i32::MAX, 32, BI_RGB, 40, 0)).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! hands window events to the handlers the application registered.
+//! nothing here touches win32, so it works with any `PlatformWindow`.
+
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::event::Event;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    Continue,
+    /// keeps the window open when the event is `CloseRequested`
+    VetoClose,
+}
+
+pub type Handler = Box<dyn FnMut(&Event) -> Reply>;
+
+/// what the dispatcher needs from a native window
+pub trait PlatformWindow {
+    fn destroy(&mut self);
+}
+
+#[derive(Default)]
+pub struct Dispatcher {
+    handlers: Vec<Handler>,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_handler<F: FnMut(&Event) -> Reply + 'static>(&mut self, handler: F) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// runs every handler on `event`. a close request that no handler vetoed
+    /// destroys `window`. returns true if the event was fully handled here,
+    /// so the platform must not apply its default behaviour.
+    ///
+    /// a panicking handler is caught and kept for `take_panic`, since it
+    /// must not unwind into the platform's window procedure. the handlers
+    /// after it still run; only the first panic is kept.
+    pub fn dispatch<W: PlatformWindow>(&mut self, window: &mut W, event: &Event) -> bool {
+        let mut reply = Reply::Continue;
+        for handler in self.handlers.iter_mut() {
+            match catch_unwind(AssertUnwindSafe(|| handler(event))) {
+                Ok(Reply::VetoClose) => reply = Reply::VetoClose,
+                Ok(Reply::Continue) => (),
+                Err(panic) => {
+                    self.panic.get_or_insert(panic);
+                }
+            }
+        }
+        match (reply, event) {
+            (Reply::VetoClose, Event::CloseRequested) => true,
+            (_, Event::CloseRequested) => {
+                window.destroy();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// the panic of a handler, to be resumed outside the window procedure
+    pub fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.panic.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Default)]
+    struct FakeWindow {
+        destroyed: u32,
+    }
+
+    impl PlatformWindow for FakeWindow {
+        fn destroy(&mut self) {
+            self.destroyed += 1;
+        }
+    }
+
+    fn recorder(log: &Rc<RefCell<Vec<(u32, Event)>>>, id: u32) -> impl FnMut(&Event) -> Reply {
+        let log = log.clone();
+        move |event: &Event| {
+            log.borrow_mut().push((id, event.clone()));
+            Reply::Continue
+        }
+    }
+
+    #[test]
+    fn every_handler_sees_every_event() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_handler(recorder(&log, 1));
+        dispatcher.add_handler(recorder(&log, 2));
+        let mut window = FakeWindow::default();
+        assert!(!dispatcher.dispatch(&mut window, &Event::Focus(true)));
+        assert_eq!(
+            *log.borrow(),
+            vec![(1, Event::Focus(true)), (2, Event::Focus(true))]
+        );
+        assert_eq!(window.destroyed, 0);
+    }
+
+    #[test]
+    fn a_panic_is_reported_and_later_handlers_run() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_handler(recorder(&log, 1));
+        dispatcher.add_handler(|event: &Event| match event {
+            Event::Focus(_) => panic!("handler failed"),
+            _ => Reply::Continue,
+        });
+        dispatcher.add_handler(recorder(&log, 3));
+        let mut window = FakeWindow::default();
+
+        assert!(!dispatcher.dispatch(&mut window, &Event::Focus(true)));
+        assert!(!dispatcher.dispatch(&mut window, &Event::Focus(false)));
+        assert_eq!(log.borrow().len(), 4);
+        assert_eq!(log.borrow()[1], (3, Event::Focus(true)));
+
+        // the first panic is the one reported
+        let panic = dispatcher.take_panic().unwrap();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"handler failed"));
+        assert!(dispatcher.take_panic().is_none());
+
+        // and the window still closes
+        assert!(dispatcher.dispatch(&mut window, &Event::CloseRequested));
+        assert_eq!(window.destroyed, 1);
+        assert!(dispatcher.take_panic().is_none());
+    }
+
+    #[test]
+    fn close_destroys_unless_vetoed() {
+        let veto = Rc::new(RefCell::new(true));
+        let mut dispatcher = Dispatcher::new();
+        let handler_veto = veto.clone();
+        dispatcher.add_handler(move |event: &Event| {
+            if *event == Event::CloseRequested && *handler_veto.borrow() {
+                Reply::VetoClose
+            } else {
+                Reply::Continue
+            }
+        });
+        dispatcher.add_handler(|_: &Event| Reply::Continue);
+        let mut window = FakeWindow::default();
+
+        assert!(dispatcher.dispatch(&mut window, &Event::CloseRequested));
+        assert_eq!(window.destroyed, 0);
+        // a veto only matters for close requests
+        assert!(!dispatcher.dispatch(&mut window, &Event::Destroyed));
+
+        *veto.borrow_mut() = false;
+        assert!(dispatcher.dispatch(&mut window, &Event::CloseRequested));
+        assert_eq!(window.destroyed, 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! sizes in logical units, which are pixels at 96 dpi, and in physical
+//! pixels. windows and ui are laid out in logical units; swapchains and
+//! viewports are in physical pixels.
+
+use super::rect::Rect;
+
+/// the dpi at which a logical unit is one pixel
+pub const DEFAULT_DPI: u32 = 96;
+
+/// physical pixels per logical unit
+pub fn scale_factor(dpi: u32) -> f32 {
+    dpi as f32 / DEFAULT_DPI as f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogicalSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl LogicalSize {
+    pub fn new(width: f32, height: f32) -> Self {
+        LogicalSize {
+            width: width,
+            height: height,
+        }
+    }
+
+    /// rounded to the nearest pixel
+    pub fn to_physical(&self, scale: f32) -> PhysicalSize {
+        PhysicalSize {
+            width: (self.width * scale).round().max(0.0) as u32,
+            height: (self.height * scale).round().max(0.0) as u32,
+        }
+    }
+}
+
+impl PhysicalSize {
+    pub fn new(width: u32, height: u32) -> Self {
+        PhysicalSize {
+            width: width,
+            height: height,
+        }
+    }
+
+    pub fn to_logical(&self, scale: f32) -> LogicalSize {
+        LogicalSize {
+            width: self.width as f32 / scale,
+            height: self.height as f32 / scale,
+        }
+    }
+}
+
+/// the frame around the client area: borders and title bar, whose size
+/// depends on the dpi
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Insets {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Insets {
+    /// from what `AdjustWindowRectExForDpi` makes of an empty client rect
+    pub fn from_adjusted(empty: &Rect) -> Self {
+        Insets {
+            left: -empty.left,
+            top: -empty.top,
+            right: empty.right,
+            bottom: empty.bottom,
+        }
+    }
+
+    pub fn window_rect(&self, client: &Rect) -> Rect {
+        Rect::new(
+            client.left - self.left,
+            client.top - self.top,
+            client.right + self.right,
+            client.bottom + self.bottom,
+        )
+    }
+
+    pub fn client_rect(&self, window: &Rect) -> Rect {
+        Rect::new(
+            window.left + self.left,
+            window.top + self.top,
+            window.right - self.right,
+            window.bottom - self.bottom,
+        )
+    }
+
+    /// outer size of a window whose client area is `client`
+    pub fn window_size(&self, client: PhysicalSize) -> (i32, i32) {
+        (
+            client.width as i32 + self.left + self.right,
+            client.height as i32 + self.top + self.bottom,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_factors() {
+        assert_eq!(scale_factor(96), 1.0);
+        assert_eq!(scale_factor(120), 1.25);
+        assert_eq!(scale_factor(144), 1.5);
+        assert_eq!(scale_factor(192), 2.0);
+    }
+
+    #[test]
+    fn logical_to_physical() {
+        let size = LogicalSize::new(640.0, 480.0);
+        assert_eq!(
+            size.to_physical(scale_factor(96)),
+            PhysicalSize::new(640, 480)
+        );
+        assert_eq!(
+            size.to_physical(scale_factor(144)),
+            PhysicalSize::new(960, 720)
+        );
+        assert_eq!(
+            size.to_physical(scale_factor(192)),
+            PhysicalSize::new(1280, 960)
+        );
+    }
+
+    #[test]
+    fn rounds_to_the_nearest_pixel() {
+        // halves round away from zero
+        let scale = scale_factor(144);
+        assert_eq!(
+            LogicalSize::new(1.0, 3.0).to_physical(scale),
+            PhysicalSize::new(2, 5)
+        );
+        assert_eq!(
+            LogicalSize::new(0.3, 0.4).to_physical(scale),
+            PhysicalSize::new(0, 1)
+        );
+        assert_eq!(
+            LogicalSize::new(100.2, 100.5).to_physical(scale_factor(96)),
+            PhysicalSize::new(100, 101)
+        );
+        assert_eq!(
+            LogicalSize::new(0.25, 0.75).to_physical(scale_factor(192)),
+            PhysicalSize::new(1, 2)
+        );
+        // negative sizes are empty
+        assert_eq!(
+            LogicalSize::new(-4.0, -0.2).to_physical(scale),
+            PhysicalSize::new(0, 0)
+        );
+    }
+
+    #[test]
+    fn physical_to_logical() {
+        let size = PhysicalSize::new(960, 720);
+        assert_eq!(
+            size.to_logical(scale_factor(96)),
+            LogicalSize::new(960.0, 720.0)
+        );
+        assert_eq!(
+            size.to_logical(scale_factor(144)),
+            LogicalSize::new(640.0, 480.0)
+        );
+        assert_eq!(
+            size.to_logical(scale_factor(192)),
+            LogicalSize::new(480.0, 360.0)
+        );
+        let odd = PhysicalSize::new(1, 3).to_logical(scale_factor(144));
+        assert!((odd.width - 2.0 / 3.0).abs() < 1e-6 && odd.height == 2.0);
+    }
+
+    #[test]
+    fn round_trips() {
+        for &dpi in [96, 144, 192].iter() {
+            let scale = scale_factor(dpi);
+            for &(width, height) in [(1, 1), (3, 7), (641, 479), (3840, 2160)].iter() {
+                let physical = PhysicalSize::new(width, height);
+                assert_eq!(
+                    physical.to_logical(scale).to_physical(scale),
+                    physical,
+                    "{}",
+                    dpi
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn insets() {
+        let insets = Insets::from_adjusted(&Rect::new(-8, -31, 8, 8));
+        assert_eq!(
+            insets,
+            Insets {
+                left: 8,
+                top: 31,
+                right: 8,
+                bottom: 8
+            }
+        );
+        let client = Rect::new(100, 100, 740, 580);
+        let window = insets.window_rect(&client);
+        assert_eq!(window, Rect::new(92, 69, 748, 588));
+        assert_eq!(insets.client_rect(&window), client);
+        assert_eq!(insets.window_size(PhysicalSize::new(640, 480)), (656, 519));
+    }
+}
@@ -0,0 +1,22 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// names a top-level window in events and to the renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(pub u32);
+
+impl WindowId {
+    /// an id no other window of the process has. ids count up from 1 in
+    /// the order windows are created, so a run that creates the same
+    /// windows gives them the same ids.
+    pub fn next() -> WindowId {
+        static NEXT: AtomicU32 = AtomicU32::new(1);
+        WindowId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for WindowId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "window {}", self.0)
+    }
+}
@@ -0,0 +1,322 @@
+//! windowed, borderless and exclusive fullscreen modes.
+//! `ModeState` decides which steps a mode change takes; `Direct3D` and
+//! `Window` carry them out.
+
+use super::rect::Rect;
+
+// win32 window styles, mirrored here so the mode logic does not need winapi
+pub const WS_OVERLAPPEDWINDOW: u32 = 0x00cf_0000;
+pub const WS_POPUP: u32 = 0x8000_0000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    Borderless,
+    Exclusive,
+}
+
+/// window style and placement to go back to when leaving fullscreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub style: u32,
+    pub rect: Rect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeStep {
+    /// remember the current windowed placement
+    SavePlacement,
+    /// put the window back to a saved placement
+    RestorePlacement(Placement),
+    /// turn the window into a popup covering the whole monitor
+    Borderless,
+    EnterExclusive,
+    LeaveExclusive,
+}
+
+pub struct ModeState {
+    mode: WindowMode,
+    last_fullscreen: WindowMode,
+    saved: Option<Placement>,
+}
+
+impl Default for ModeState {
+    fn default() -> Self {
+        ModeState {
+            mode: WindowMode::Windowed,
+            last_fullscreen: WindowMode::Borderless,
+            saved: None,
+        }
+    }
+}
+
+impl ModeState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> WindowMode {
+        self.mode
+    }
+
+    pub fn saved(&self) -> Option<Placement> {
+        self.saved
+    }
+
+    /// the mode alt+enter switches to: fullscreen modes go back to windowed,
+    /// and windowed goes to whichever fullscreen mode was used last
+    pub fn toggle_target(&self) -> WindowMode {
+        match self.mode {
+            WindowMode::Windowed => self.last_fullscreen,
+            _ => WindowMode::Windowed,
+        }
+    }
+
+    /// steps needed to switch to `to`, in order
+    pub fn plan(&self, to: WindowMode) -> Vec<ModeStep> {
+        use WindowMode::*;
+        let restore = match self.saved {
+            Some(placement) => vec![ModeStep::RestorePlacement(placement)],
+            None => vec![],
+        };
+        match (self.mode, to) {
+            (Windowed, Borderless) => vec![ModeStep::SavePlacement, ModeStep::Borderless],
+            (Windowed, Exclusive) => vec![ModeStep::SavePlacement, ModeStep::EnterExclusive],
+            (Borderless, Windowed) => restore,
+            (Borderless, Exclusive) => vec![ModeStep::EnterExclusive],
+            (Exclusive, Windowed) => {
+                let mut steps = vec![ModeStep::LeaveExclusive];
+                steps.extend(restore);
+                steps
+            }
+            (Exclusive, Borderless) => vec![ModeStep::LeaveExclusive, ModeStep::Borderless],
+            _ => vec![],
+        }
+    }
+
+    /// records that the steps planned for `to` were carried out.
+    /// `placement` is the windowed placement at the time of `SavePlacement`.
+    pub fn finish(&mut self, to: WindowMode, placement: Option<Placement>) {
+        if let Some(placement) = placement {
+            self.saved = Some(placement);
+        }
+        if to != WindowMode::Windowed {
+            self.last_fullscreen = to;
+        }
+        self.mode = to;
+    }
+
+    /// dxgi left exclusive fullscreen on its own (e.g. on alt+tab)
+    pub fn exclusive_lost(&mut self) {
+        if self.mode == WindowMode::Exclusive {
+            self.mode = WindowMode::Windowed;
+        }
+    }
+}
+
+/// style for a borderless window, keeping unrelated bits of `style`
+pub fn borderless_style(style: u32) -> u32 {
+    (style & !WS_OVERLAPPEDWINDOW) | WS_POPUP
+}
+
+/// where a saved window goes back to: its old rectangle if that is still
+/// visible on `monitor`, otherwise the same size centered on it
+pub fn restore_rect(saved: &Rect, monitor: &Rect) -> Rect {
+    if saved.intersects(monitor) {
+        saved.clamped_into(monitor)
+    } else {
+        saved.centered_in(monitor).clamped_into(monitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOWED: Placement = Placement {
+        style: WS_OVERLAPPEDWINDOW,
+        rect: Rect {
+            left: 100,
+            top: 50,
+            right: 900,
+            bottom: 650,
+        },
+    };
+
+    /// plans a switch and records it as carried out, the way `Surface` does,
+    /// with `WINDOWED` as the placement a `SavePlacement` finds
+    fn switch(state: &mut ModeState, to: WindowMode) -> Vec<ModeStep> {
+        let steps = state.plan(to);
+        let placement = if steps.contains(&ModeStep::SavePlacement) {
+            Some(WINDOWED)
+        } else {
+            None
+        };
+        state.finish(to, placement);
+        steps
+    }
+
+    #[test]
+    fn starts_windowed() {
+        let state = ModeState::new();
+        assert_eq!(state.mode(), WindowMode::Windowed);
+        assert_eq!(state.saved(), None);
+        assert_eq!(state.toggle_target(), WindowMode::Borderless);
+        assert_eq!(state.plan(WindowMode::Windowed), vec![]);
+    }
+
+    #[test]
+    fn windowed_and_borderless() {
+        let mut state = ModeState::new();
+        assert_eq!(
+            switch(&mut state, WindowMode::Borderless),
+            vec![ModeStep::SavePlacement, ModeStep::Borderless]
+        );
+        assert_eq!(state.mode(), WindowMode::Borderless);
+        assert_eq!(state.saved(), Some(WINDOWED));
+        assert_eq!(state.plan(WindowMode::Borderless), vec![]);
+        assert_eq!(
+            switch(&mut state, WindowMode::Windowed),
+            vec![ModeStep::RestorePlacement(WINDOWED)]
+        );
+        assert_eq!(state.mode(), WindowMode::Windowed);
+    }
+
+    #[test]
+    fn windowed_and_exclusive() {
+        let mut state = ModeState::new();
+        assert_eq!(
+            switch(&mut state, WindowMode::Exclusive),
+            vec![ModeStep::SavePlacement, ModeStep::EnterExclusive]
+        );
+        assert_eq!(state.mode(), WindowMode::Exclusive);
+        assert_eq!(
+            switch(&mut state, WindowMode::Windowed),
+            vec![
+                ModeStep::LeaveExclusive,
+                ModeStep::RestorePlacement(WINDOWED)
+            ]
+        );
+        assert_eq!(state.mode(), WindowMode::Windowed);
+    }
+
+    #[test]
+    fn borderless_and_exclusive() {
+        let mut state = ModeState::new();
+        switch(&mut state, WindowMode::Borderless);
+        assert_eq!(
+            switch(&mut state, WindowMode::Exclusive),
+            vec![ModeStep::EnterExclusive]
+        );
+        assert_eq!(
+            switch(&mut state, WindowMode::Borderless),
+            vec![ModeStep::LeaveExclusive, ModeStep::Borderless]
+        );
+        // the placement saved from windowed survives the fullscreen switches
+        assert_eq!(state.saved(), Some(WINDOWED));
+        assert_eq!(
+            switch(&mut state, WindowMode::Windowed),
+            vec![ModeStep::RestorePlacement(WINDOWED)]
+        );
+    }
+
+    #[test]
+    fn leaving_fullscreen_without_a_placement() {
+        let mut state = ModeState::new();
+        state.finish(WindowMode::Exclusive, None);
+        assert_eq!(
+            state.plan(WindowMode::Windowed),
+            vec![ModeStep::LeaveExclusive]
+        );
+        state.finish(WindowMode::Borderless, None);
+        assert_eq!(state.plan(WindowMode::Windowed), vec![]);
+    }
+
+    #[test]
+    fn alt_enter_toggles_with_the_last_fullscreen_mode() {
+        let mut state = ModeState::new();
+        let target = state.toggle_target();
+        switch(&mut state, target);
+        assert_eq!(state.mode(), WindowMode::Borderless);
+        assert_eq!(state.toggle_target(), WindowMode::Windowed);
+        let target = state.toggle_target();
+        switch(&mut state, target);
+        assert_eq!(state.mode(), WindowMode::Windowed);
+
+        switch(&mut state, WindowMode::Exclusive);
+        assert_eq!(state.toggle_target(), WindowMode::Windowed);
+        let target = state.toggle_target();
+        switch(&mut state, target);
+        // windowed does not count as the last fullscreen mode
+        assert_eq!(state.toggle_target(), WindowMode::Exclusive);
+        let target = state.toggle_target();
+        assert_eq!(
+            switch(&mut state, target),
+            vec![ModeStep::SavePlacement, ModeStep::EnterExclusive]
+        );
+    }
+
+    #[test]
+    fn exclusive_lost_on_alt_tab() {
+        let mut state = ModeState::new();
+        switch(&mut state, WindowMode::Exclusive);
+        state.exclusive_lost();
+        assert_eq!(state.mode(), WindowMode::Windowed);
+        assert_eq!(state.saved(), Some(WINDOWED));
+        // dxgi already left, so alt+enter goes straight back in
+        assert_eq!(state.toggle_target(), WindowMode::Exclusive);
+        assert_eq!(
+            state.plan(WindowMode::Exclusive),
+            vec![ModeStep::SavePlacement, ModeStep::EnterExclusive]
+        );
+    }
+
+    #[test]
+    fn exclusive_lost_only_affects_exclusive() {
+        let mut state = ModeState::new();
+        switch(&mut state, WindowMode::Borderless);
+        state.exclusive_lost();
+        assert_eq!(state.mode(), WindowMode::Borderless);
+        let mut state = ModeState::new();
+        state.exclusive_lost();
+        assert_eq!(state.mode(), WindowMode::Windowed);
+    }
+
+    #[test]
+    fn borderless_style_replaces_the_frame() {
+        const WS_VISIBLE: u32 = 0x1000_0000;
+        assert_eq!(
+            borderless_style(WS_OVERLAPPEDWINDOW | WS_VISIBLE),
+            WS_POPUP | WS_VISIBLE
+        );
+        assert_eq!(borderless_style(WS_POPUP), WS_POPUP);
+        assert_eq!(borderless_style(0), WS_POPUP);
+    }
+
+    #[test]
+    fn restore_rect_keeps_visible_windows() {
+        let monitor = Rect::new(0, 0, 1920, 1080);
+        assert_eq!(restore_rect(&WINDOWED.rect, &monitor), WINDOWED.rect);
+        // partly off screen is pulled back in
+        let partly = Rect::from_size(1800, 900, 800, 600);
+        assert_eq!(
+            restore_rect(&partly, &monitor),
+            Rect::from_size(1120, 480, 800, 600)
+        );
+    }
+
+    #[test]
+    fn restore_rect_centers_lost_windows() {
+        // the window was on a monitor that is gone
+        let monitor = Rect::new(0, 0, 1920, 1080);
+        let lost = Rect::from_size(-2000, 100, 800, 600);
+        assert_eq!(
+            restore_rect(&lost, &monitor),
+            Rect::from_size(560, 240, 800, 600)
+        );
+        // and was bigger than the one that is left
+        let small = Rect::new(1920, 0, 3200, 1024);
+        let big = Rect::from_size(-3000, 0, 2560, 1440);
+        assert_eq!(restore_rect(&big, &small), small);
+    }
+}
@@ -0,0 +1,141 @@
+//! mouse packets of `WM_INPUT`, read from the bytes `GetRawInputData` returns
+
+use std::convert::TryInto;
+
+pub const RIM_TYPEMOUSE: u32 = 0;
+pub const MOUSE_MOVE_ABSOLUTE: u16 = 0x01;
+
+/// size of `RAWINPUTHEADER`, after which the device data starts
+#[cfg(windows)]
+pub const HEADER_SIZE: usize = std::mem::size_of::<winapi::um::winuser::RAWINPUTHEADER>();
+#[cfg(not(windows))]
+pub const HEADER_SIZE: usize = std::mem::size_of::<RawInputHeader>();
+
+/// `RAWINPUTHEADER` mirrored, so that packets can be parsed without windows
+#[cfg(not(windows))]
+#[repr(C)]
+struct RawInputHeader {
+    _kind: u32,
+    _size: u32,
+    _device: *mut std::ffi::c_void,
+    _wparam: usize,
+}
+
+/// the fields of `RAWMOUSE` that matter for motion and buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawMouse {
+    pub flags: u16,
+    pub button_flags: u16,
+    pub button_data: u16,
+    pub last_x: i32,
+    pub last_y: i32,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// parses a whole `RAWINPUT`. returns `None` for other devices and for
+/// truncated data.
+pub fn parse_mouse(bytes: &[u8]) -> Option<RawMouse> {
+    if u32_at(bytes, 0)? != RIM_TYPEMOUSE {
+        return None;
+    }
+    // RAWMOUSE: usFlags, padding, usButtonFlags, usButtonData, ulRawButtons,
+    // lLastX, lLastY, ulExtraInformation
+    let mouse = HEADER_SIZE;
+    Some(RawMouse {
+        flags: u16_at(bytes, mouse)?,
+        button_flags: u16_at(bytes, mouse + 4)?,
+        button_data: u16_at(bytes, mouse + 6)?,
+        last_x: u32_at(bytes, mouse + 12)? as i32,
+        last_y: u32_at(bytes, mouse + 16)? as i32,
+    })
+}
+
+/// turns packets into relative motion. most mice report relative motion
+/// already; tablets and remote desktop report absolute positions, which
+/// are differenced here.
+#[derive(Debug, Default)]
+pub struct MotionTracker {
+    last_absolute: Option<(i32, i32)>,
+}
+
+impl MotionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn delta(&mut self, packet: &RawMouse) -> (i32, i32) {
+        if packet.flags & MOUSE_MOVE_ABSOLUTE == 0 {
+            self.last_absolute = None;
+            return (packet.last_x, packet.last_y);
+        }
+        let position = (packet.last_x, packet.last_y);
+        let delta = match self.last_absolute {
+            Some((x, y)) => (position.0 - x, position.1 - y),
+            None => (0, 0),
+        };
+        self.last_absolute = Some(position);
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a RAWINPUT of a mouse, laid out as on this target
+    fn packet(flags: u16, button_flags: u16, x: i32, y: i32) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_SIZE + 24];
+        bytes[HEADER_SIZE..HEADER_SIZE + 2].copy_from_slice(&flags.to_le_bytes());
+        bytes[HEADER_SIZE + 4..HEADER_SIZE + 6].copy_from_slice(&button_flags.to_le_bytes());
+        bytes[HEADER_SIZE + 12..HEADER_SIZE + 16].copy_from_slice(&x.to_le_bytes());
+        bytes[HEADER_SIZE + 16..HEADER_SIZE + 20].copy_from_slice(&y.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn header_size() {
+        // two dwords, a handle and a wparam
+        assert_eq!(HEADER_SIZE, 8 + 2 * std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn parses_mouse_packets() {
+        let mouse = parse_mouse(&packet(0, 1, -3, 5)).unwrap();
+        assert_eq!(
+            mouse,
+            RawMouse {
+                flags: 0,
+                button_flags: 1,
+                button_data: 0,
+                last_x: -3,
+                last_y: 5
+            }
+        );
+        // RIM_TYPEKEYBOARD
+        let mut keyboard = packet(0, 0, 1, 1);
+        keyboard[0] = 1;
+        assert_eq!(parse_mouse(&keyboard), None);
+        assert_eq!(parse_mouse(&packet(0, 0, 1, 1)[..HEADER_SIZE + 10]), None);
+        assert_eq!(parse_mouse(&[]), None);
+    }
+
+    #[test]
+    fn absolute_positions_become_motion() {
+        let mut tracker = MotionTracker::new();
+        let relative = parse_mouse(&packet(0, 0, -3, 5)).unwrap();
+        assert_eq!(tracker.delta(&relative), (-3, 5));
+        let absolute = |x, y| parse_mouse(&packet(MOUSE_MOVE_ABSOLUTE, 0, x, y)).unwrap();
+        assert_eq!(tracker.delta(&absolute(100, 100)), (0, 0));
+        assert_eq!(tracker.delta(&absolute(110, 95)), (10, -5));
+        // relative motion in between starts the differencing over
+        assert_eq!(tracker.delta(&relative), (-3, 5));
+        assert_eq!(tracker.delta(&absolute(0, 0)), (0, 0));
+    }
+}
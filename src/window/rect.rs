@@ -0,0 +1,126 @@
+/// screen rectangle in pixels, laid out like win32's `RECT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Rect {
+            left: left,
+            top: top,
+            right: right,
+            bottom: bottom,
+        }
+    }
+
+    pub fn from_size(left: i32, top: i32, width: i32, height: i32) -> Self {
+        Rect::new(left, top, left + width, top + height)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.left < other.right
+            && other.left < self.right
+            && self.top < other.bottom
+            && other.top < self.bottom
+    }
+
+    /// a rectangle of this size centered in `area`
+    pub fn centered_in(&self, area: &Rect) -> Rect {
+        Rect::from_size(
+            area.left + (area.width() - self.width()) / 2,
+            area.top + (area.height() - self.height()) / 2,
+            self.width(),
+            self.height(),
+        )
+    }
+
+    /// moves (and if needed shrinks) this rectangle so that it lies inside `area`
+    pub fn clamped_into(&self, area: &Rect) -> Rect {
+        let width = self.width().min(area.width());
+        let height = self.height().min(area.height());
+        let left = self.left.max(area.left).min(area.right - width);
+        let top = self.top.max(area.top).min(area.bottom - height);
+        Rect::from_size(left, top, width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        let rect = Rect::from_size(-10, 20, 300, 200);
+        assert_eq!(rect, Rect::new(-10, 20, 290, 220));
+        assert_eq!(rect.width(), 300);
+        assert_eq!(rect.height(), 200);
+        assert_eq!(Rect::default().width(), 0);
+    }
+
+    #[test]
+    fn intersects() {
+        let a = Rect::new(0, 0, 100, 100);
+        assert!(a.intersects(&a));
+        assert!(a.intersects(&Rect::new(50, 50, 150, 150)));
+        assert!(a.intersects(&Rect::new(-50, 10, 1, 20)));
+        assert!(a.intersects(&Rect::new(10, 10, 20, 20)));
+        assert!(Rect::new(10, 10, 20, 20).intersects(&a));
+        // touching edges do not overlap
+        assert!(!a.intersects(&Rect::new(100, 0, 200, 100)));
+        assert!(!a.intersects(&Rect::new(0, 100, 100, 200)));
+        assert!(!a.intersects(&Rect::new(-100, -100, 0, 0)));
+        assert!(!a.intersects(&Rect::new(200, 200, 300, 300)));
+    }
+
+    #[test]
+    fn centered_in() {
+        let area = Rect::new(100, 0, 1100, 600);
+        assert_eq!(
+            Rect::from_size(-500, -500, 400, 200).centered_in(&area),
+            Rect::from_size(400, 200, 400, 200)
+        );
+        // odd leftovers round toward the top left
+        assert_eq!(
+            Rect::from_size(0, 0, 401, 201).centered_in(&area),
+            Rect::from_size(399, 199, 401, 201)
+        );
+        // bigger than the area hangs over both sides
+        assert_eq!(
+            Rect::from_size(0, 0, 1200, 600).centered_in(&area),
+            Rect::new(0, 0, 1200, 600)
+        );
+    }
+
+    #[test]
+    fn clamped_into() {
+        let area = Rect::new(0, 0, 1000, 800);
+        let inside = Rect::new(10, 20, 110, 120);
+        assert_eq!(inside.clamped_into(&area), inside);
+        assert_eq!(
+            Rect::from_size(-50, -30, 100, 100).clamped_into(&area),
+            Rect::from_size(0, 0, 100, 100)
+        );
+        assert_eq!(
+            Rect::from_size(950, 750, 100, 100).clamped_into(&area),
+            Rect::from_size(900, 700, 100, 100)
+        );
+        // too big shrinks to the area
+        assert_eq!(
+            Rect::from_size(-100, 100, 2000, 700).clamped_into(&area),
+            Rect::from_size(0, 100, 1000, 700)
+        );
+        assert_eq!(Rect::new(-5, -5, 5000, 5000).clamped_into(&area), area);
+    }
+}
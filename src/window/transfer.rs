@@ -0,0 +1,96 @@
+//! data that reaches a window from other applications: dropped files and
+//! clipboard text. windows hands both over as utf-16.
+
+use std::path::PathBuf;
+
+/// the units before the first nul
+fn until_nul(units: &[u16]) -> &[u16] {
+    let len = units
+        .iter()
+        .position(|unit| *unit == 0)
+        .unwrap_or(units.len());
+    &units[..len]
+}
+
+/// a path as `DragQueryFileW` returns it. on windows every path survives,
+/// even one that is not valid utf-16; elsewhere such units are replaced.
+pub fn path_from_wide(units: &[u16]) -> PathBuf {
+    let units = until_nul(units);
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_wide(units))
+    }
+    #[cfg(not(windows))]
+    {
+        PathBuf::from(String::from_utf16_lossy(units))
+    }
+}
+
+/// `CF_UNICODETEXT` data, which ends lines with `\r\n`, as `\n` text
+pub fn text_from_wide(units: &[u16]) -> String {
+    String::from_utf16_lossy(until_nul(units)).replace("\r\n", "\n")
+}
+
+/// `text` as `CF_UNICODETEXT` wants it: `\r\n` line ends and a nul
+pub fn text_to_wide(text: &str) -> Vec<u16> {
+    let mut units = Vec::with_capacity(text.len() + 1);
+    let mut previous = None;
+    for c in text.chars() {
+        if c == '\n' && previous != Some('\r') {
+            units.push('\r' as u16);
+        }
+        let mut buffer = [0; 2];
+        units.extend_from_slice(c.encode_utf16(&mut buffer));
+        previous = Some(c);
+    }
+    units.push(0);
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().collect()
+    }
+
+    #[test]
+    fn paths_end_at_the_nul() {
+        let mut units = wide("C:\\meshes\\ünï 😀.obj");
+        units.push(0);
+        units.extend(wide("junk"));
+        assert_eq!(
+            path_from_wide(&units),
+            PathBuf::from("C:\\meshes\\ünï 😀.obj")
+        );
+        assert_eq!(path_from_wide(&wide("a.png")), PathBuf::from("a.png"));
+        assert_eq!(path_from_wide(&[0]), PathBuf::new());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn lone_surrogates_are_replaced() {
+        assert_eq!(
+            path_from_wide(&[0x61, 0xd800, 0x62]),
+            PathBuf::from("a\u{fffd}b")
+        );
+        assert_eq!(text_from_wide(&[0xdc00, 0]), "\u{fffd}");
+    }
+
+    #[test]
+    fn text_line_ends() {
+        let units = text_to_wide("a\nb\r\nc😀\n");
+        assert_eq!(units.last(), Some(&0));
+        assert_eq!(
+            String::from_utf16(&units[..units.len() - 1]).unwrap(),
+            "a\r\nb\r\nc😀\r\n"
+        );
+        assert_eq!(text_from_wide(&units), "a\nb\nc😀\n");
+        // clipboard data need not be terminated
+        assert_eq!(text_from_wide(&wide("x\r\ny")), "x\ny");
+        assert_eq!(text_to_wide(""), vec![0]);
+        assert_eq!(text_from_wide(&[]), "");
+    }
+}
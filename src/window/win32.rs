@@ -0,0 +1,805 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::ptr::{null, null_mut};
+use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::{HMONITOR, HWND, POINT, RECT};
+use winapi::um::shellapi::HDROP;
+use winapi::um::winuser::WNDCLASSEXW;
+
+use super::capture::{Capture, CaptureCommand, CaptureMode};
+use super::dib::{self, Image};
+use super::dispatch::{Dispatcher, PlatformWindow, Reply};
+use super::dpi::{self, Insets, LogicalSize, PhysicalSize};
+use super::id::WindowId;
+use super::mode::{self, Placement};
+use super::raw_input::{self, MotionTracker};
+use super::rect::Rect;
+use super::transfer;
+use crate::app::Platform;
+use crate::event::{Event, Translator, WindowEvent};
+
+use widestring::U16CString;
+
+thread_local! {
+    // the application quits once the last of its windows is destroyed
+    static OPEN_WINDOWS: Cell<usize> = const { Cell::new(0) };
+}
+
+pub struct Window {
+    pub width: usize,
+    pub height: usize,
+    pub handle: HWND,
+    pub class: WNDCLASSEXW,
+    id: WindowId,
+    // `class.lpszClassName` points into it
+    class_name: U16CString,
+    // boxed so that the pointer in GWLP_USERDATA stays valid when `Window` moves
+    state: Box<RefCell<State>>,
+}
+
+/// what the window procedure reaches through GWLP_USERDATA
+struct State {
+    window: WindowId,
+    translator: Translator,
+    dispatcher: Dispatcher,
+    capture: Capture,
+    saved_cursor: Option<POINT>,
+    motion: MotionTracker,
+    /// where `WM_DPICHANGED` asked the window to go, applied by the
+    /// procedure once the state is no longer borrowed
+    suggested_rect: Option<Rect>,
+}
+
+struct NativeWindow(HWND);
+
+impl PlatformWindow for NativeWindow {
+    fn destroy(&mut self) {
+        use winapi::um::winuser::DestroyWindow;
+        unsafe { DestroyWindow(self.0) };
+    }
+}
+
+/// the client area in screen coordinates
+fn client_screen_rect(hwnd: HWND) -> Rect {
+    use winapi::um::winuser::{ClientToScreen, GetClientRect};
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: 0,
+        bottom: 0,
+    };
+    unsafe { GetClientRect(hwnd, &mut rect) };
+    let mut top_left = POINT {
+        x: rect.left,
+        y: rect.top,
+    };
+    let mut bottom_right = POINT {
+        x: rect.right,
+        y: rect.bottom,
+    };
+    unsafe {
+        ClientToScreen(hwnd, &mut top_left);
+        ClientToScreen(hwnd, &mut bottom_right);
+    }
+    Rect::new(top_left.x, top_left.y, bottom_right.x, bottom_right.y)
+}
+
+/// the `RAWINPUT` that a `WM_INPUT` message refers to
+fn read_raw_input(lparam: LPARAM) -> Option<Vec<u8>> {
+    use winapi::um::winuser::{GetRawInputData, HRAWINPUT, RAWINPUTHEADER, RID_INPUT};
+    let header_size = std::mem::size_of::<RAWINPUTHEADER>() as UINT;
+    let mut size: UINT = 0;
+    let result = unsafe {
+        GetRawInputData(
+            lparam as HRAWINPUT,
+            RID_INPUT,
+            null_mut(),
+            &mut size,
+            header_size,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    // u64 words keep the buffer aligned like a RAWINPUT
+    let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+    let result = unsafe {
+        GetRawInputData(
+            lparam as HRAWINPUT,
+            RID_INPUT,
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+            header_size,
+        )
+    };
+    if result != size {
+        return None;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, size as usize) };
+    Some(bytes.to_vec())
+}
+
+/// the paths of a `WM_DROPFILES` message, whose `HDROP` is released
+fn read_dropped_files(drop: HDROP) -> Vec<PathBuf> {
+    use winapi::um::shellapi::{DragFinish, DragQueryFileW};
+    let count = unsafe { DragQueryFileW(drop, 0xffff_ffff, null_mut(), 0) };
+    let mut paths = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        // the length leaves out the terminating nul
+        let len = unsafe { DragQueryFileW(drop, index, null_mut(), 0) };
+        let mut buffer = vec![0u16; len as usize + 1];
+        let copied =
+            unsafe { DragQueryFileW(drop, index, buffer.as_mut_ptr(), buffer.len() as UINT) };
+        if copied != 0 {
+            paths.push(transfer::path_from_wide(&buffer[..copied as usize]));
+        }
+    }
+    unsafe { DragFinish(drop) };
+    paths
+}
+
+/// the clipboard, open until this is dropped
+struct Clipboard;
+
+impl Clipboard {
+    fn open(owner: HWND) -> Result<Clipboard, String> {
+        use winapi::um::winuser::OpenClipboard;
+        if unsafe { OpenClipboard(owner) } == 0 {
+            return Err("failed: OpenClipboard".to_string());
+        }
+        Ok(Clipboard)
+    }
+
+    /// a copy of the data in `format`, or none if the clipboard has no such data
+    fn get(&self, format: UINT) -> Result<Option<Vec<u8>>, String> {
+        use winapi::um::winbase::{GlobalLock, GlobalSize, GlobalUnlock};
+        use winapi::um::winuser::{GetClipboardData, IsClipboardFormatAvailable};
+        if unsafe { IsClipboardFormatAvailable(format) } == 0 {
+            return Ok(None);
+        }
+        let memory = unsafe { GetClipboardData(format) };
+        if memory.is_null() {
+            return Err("failed: GetClipboardData".to_string());
+        }
+        let data = unsafe { GlobalLock(memory) } as *const u8;
+        if data.is_null() {
+            return Err("failed: GlobalLock".to_string());
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(data, GlobalSize(memory)) }.to_vec();
+        unsafe { GlobalUnlock(memory) };
+        Ok(Some(bytes))
+    }
+
+    /// replaces the whole content of the clipboard with `bytes` in `format`
+    fn set(&self, format: UINT, bytes: &[u8]) -> Result<(), String> {
+        use winapi::um::winbase::{
+            GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE,
+        };
+        use winapi::um::winuser::{EmptyClipboard, SetClipboardData};
+        if unsafe { EmptyClipboard() } == 0 {
+            return Err("failed: EmptyClipboard".to_string());
+        }
+        let memory = unsafe { GlobalAlloc(GMEM_MOVEABLE, bytes.len()) };
+        if memory.is_null() {
+            return Err("failed: GlobalAlloc".to_string());
+        }
+        let data = unsafe { GlobalLock(memory) } as *mut u8;
+        if data.is_null() {
+            unsafe { GlobalFree(memory) };
+            return Err("failed: GlobalLock".to_string());
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+            GlobalUnlock(memory);
+        }
+        // on success the clipboard owns the memory
+        if unsafe { SetClipboardData(format, memory) }.is_null() {
+            unsafe { GlobalFree(memory) };
+            return Err("failed: SetClipboardData".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        use winapi::um::winuser::CloseClipboard;
+        unsafe { CloseClipboard() };
+    }
+}
+
+impl State {
+    /// returns true if the message must not reach DefWindowProc
+    fn handle(&mut self, hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> bool {
+        use winapi::um::winuser::{WM_DROPFILES, WM_INPUT};
+        if msg == WM_DROPFILES {
+            for path in read_dropped_files(wparam as HDROP) {
+                self.emit(hwnd, Event::FileDropped(path));
+            }
+            return true;
+        }
+        let event = if msg == WM_INPUT {
+            read_raw_input(lparam)
+                .and_then(|bytes| raw_input::parse_mouse(&bytes))
+                .map(|packet| {
+                    let (dx, dy) = self.motion.delta(&packet);
+                    Event::RawMouseMotion { dx: dx, dy: dy }
+                })
+        } else {
+            self.translator.translate(msg, wparam, lparam)
+        };
+        let event = match event {
+            Some(event) => event,
+            None => return false,
+        };
+        match event {
+            Event::Focus(focused) => {
+                let commands = self.capture.focus(focused);
+                self.apply_capture(hwnd, &commands);
+            }
+            Event::Resized { .. } | Event::Moved { .. } => {
+                let commands = self.capture.client_moved(client_screen_rect(hwnd));
+                self.apply_capture(hwnd, &commands);
+            }
+            Event::DpiChanged { .. } => {
+                // lparam points to the window rect that keeps the logical size
+                self.suggested_rect = Some(from_win32(unsafe { &*(lparam as *const RECT) }));
+            }
+            _ => (),
+        }
+        self.emit(hwnd, event)
+    }
+
+    /// hands `event` to the handlers and then to the loop
+    fn emit(&mut self, hwnd: HWND, event: Event) -> bool {
+        let handled = self.dispatcher.dispatch(&mut NativeWindow(hwnd), &event);
+        crate::msg::push(WindowEvent::new(self.window, event));
+        handled
+    }
+
+    fn apply_capture(&mut self, hwnd: HWND, commands: &[CaptureCommand]) {
+        use winapi::um::winuser::{ClipCursor, GetCursorPos, SetCursorPos, ShowCursor};
+        for command in commands {
+            match *command {
+                CaptureCommand::ShowCursor(show) => {
+                    unsafe { ShowCursor(show as i32) };
+                }
+                CaptureCommand::Clip(Some(rect)) => {
+                    unsafe { ClipCursor(&to_win32(&rect)) };
+                }
+                CaptureCommand::Clip(None) => {
+                    unsafe { ClipCursor(null()) };
+                }
+                CaptureCommand::SaveCursorPosition => {
+                    let mut point = POINT { x: 0, y: 0 };
+                    if unsafe { GetCursorPos(&mut point) } != 0 {
+                        self.saved_cursor = Some(point);
+                    }
+                }
+                CaptureCommand::RestoreCursorPosition => {
+                    if let Some(point) = self.saved_cursor.take() {
+                        unsafe { SetCursorPos(point.x, point.y) };
+                    }
+                }
+                CaptureCommand::CenterCursor => {
+                    let rect = client_screen_rect(hwnd);
+                    unsafe {
+                        SetCursorPos(rect.left + rect.width() / 2, rect.top + rect.height() / 2)
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl Window {
+    /// a window whose client area is `width` x `height` in logical units,
+    /// so that it looks the same size on every monitor
+    pub fn create(name: &str, width: usize, height: usize) -> Window {
+        let id = WindowId::next();
+        let name = U16CString::from_str(name).unwrap();
+        // a class per window, so that windows may share a title
+        let class_name = U16CString::from_str(format!("dx12-in-rust {}", id.0)).unwrap();
+        use winapi::shared::windef::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2;
+        use winapi::um::{
+            libloaderapi::GetModuleHandleW,
+            winuser::{
+                CreateWindowExW, RegisterClassExW, SetProcessDpiAwarenessContext, CW_USEDEFAULT,
+                WS_EX_LEFT, WS_OVERLAPPEDWINDOW,
+            },
+        };
+
+        // this fails if the awareness is already set, e.g. by a manifest or
+        // an earlier window, which is fine
+        unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
+
+        use std::mem;
+        let class = WNDCLASSEXW {
+            cbSize: mem::size_of::<WNDCLASSEXW>() as UINT,
+            lpfnWndProc: Some(procedure),
+            lpszClassName: class_name.as_ptr(),
+            hInstance: unsafe { GetModuleHandleW(null()) },
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            style: 0,
+            hIcon: null_mut(),
+            hCursor: null_mut(),
+            hbrBackground: null_mut(),
+            lpszMenuName: null(),
+            hIconSm: null_mut(),
+        };
+
+        unsafe { RegisterClassExW(&class) };
+
+        let state = Box::new(RefCell::new(State {
+            window: id,
+            translator: Translator::new(),
+            dispatcher: Dispatcher::new(),
+            capture: Capture::new(Rect::default()),
+            saved_cursor: None,
+            motion: MotionTracker::new(),
+            suggested_rect: None,
+        }));
+        let handle = unsafe {
+            CreateWindowExW(
+                WS_EX_LEFT,
+                class_name.as_ptr(),
+                name.as_ptr(),
+                WS_OVERLAPPEDWINDOW,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                null_mut(),
+                null_mut(),
+                class.hInstance,
+                &*state as *const RefCell<State> as *mut _,
+            )
+        };
+        if !handle.is_null() {
+            OPEN_WINDOWS.with(|open| open.set(open.get() + 1));
+        }
+        // the dpi is known once the window is on a monitor
+        let mut window = Window {
+            width: 0,
+            height: 0,
+            handle: handle,
+            class: class,
+            id: id,
+            class_name: class_name,
+            state: state,
+        };
+        window.set_logical_size(LogicalSize::new(width as f32, height as f32));
+        window
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+
+    /// calls `handler` for every event of this window. a handler returning
+    /// `Reply::VetoClose` keeps the window open on `Event::CloseRequested`.
+    pub fn add_handler<F: FnMut(&Event) -> Reply + 'static>(&mut self, handler: F) {
+        self.state.borrow_mut().dispatcher.add_handler(handler);
+    }
+
+    /// a panic caught in a handler, which should be resumed by the caller
+    pub fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.state.borrow_mut().dispatcher.take_panic()
+    }
+
+    /// delivers unaccelerated mouse motion as `Event::RawMouseMotion`
+    pub fn register_raw_mouse(&mut self) -> Result<(), String> {
+        use winapi::um::winuser::{RegisterRawInputDevices, RAWINPUTDEVICE};
+        // generic desktop page, mouse usage
+        let device = RAWINPUTDEVICE {
+            usUsagePage: 0x01,
+            usUsage: 0x02,
+            dwFlags: 0,
+            hwndTarget: self.handle,
+        };
+        let result = unsafe {
+            RegisterRawInputDevices(&device, 1, std::mem::size_of::<RAWINPUTDEVICE>() as UINT)
+        };
+        if result == 0 {
+            return Err("failed: RegisterRawInputDevices".to_string());
+        }
+        Ok(())
+    }
+
+    /// delivers files dropped onto the window as `Event::FileDropped`
+    pub fn accept_files(&mut self, accept: bool) {
+        use winapi::um::shellapi::DragAcceptFiles;
+        unsafe { DragAcceptFiles(self.handle, accept as i32) };
+    }
+
+    /// the text on the clipboard, with `\n` line ends
+    pub fn clipboard_text(&self) -> Result<Option<String>, String> {
+        use winapi::um::winuser::CF_UNICODETEXT;
+        let bytes = Clipboard::open(self.handle)?.get(CF_UNICODETEXT)?;
+        Ok(bytes.map(|bytes| {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            transfer::text_from_wide(&units)
+        }))
+    }
+
+    pub fn set_clipboard_text(&mut self, text: &str) -> Result<(), String> {
+        use winapi::um::winuser::CF_UNICODETEXT;
+        let bytes: Vec<u8> = transfer::text_to_wide(text)
+            .iter()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        Clipboard::open(self.handle)?.set(CF_UNICODETEXT, &bytes)
+    }
+
+    /// the bitmap on the clipboard as rgba. windows converts other bitmap
+    /// formats to `CF_DIB` on request.
+    pub fn clipboard_image(&self) -> Result<Option<Image>, String> {
+        use winapi::um::winuser::CF_DIB;
+        match Clipboard::open(self.handle)?.get(CF_DIB)? {
+            Some(bytes) => dib::decode_dib(&bytes).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn capture_mode(&self) -> CaptureMode {
+        self.state.borrow().capture.mode()
+    }
+
+    /// hides, confines or frees the cursor. the capture is released while
+    /// the window is in the background and comes back with the focus.
+    pub fn set_capture_mode(&mut self, mode: CaptureMode) {
+        let mut state = self.state.borrow_mut();
+        let client = client_screen_rect(self.handle);
+        let mut commands = state.capture.client_moved(client);
+        commands.extend(state.capture.set_mode(mode));
+        state.apply_capture(self.handle, &commands);
+    }
+
+    /// dpi of the monitor the window is on
+    pub fn dpi(&self) -> u32 {
+        use winapi::um::winuser::GetDpiForWindow;
+        match unsafe { GetDpiForWindow(self.handle) } {
+            0 => dpi::DEFAULT_DPI,
+            dpi => dpi,
+        }
+    }
+
+    /// physical pixels per logical unit
+    pub fn scale_factor(&self) -> f32 {
+        dpi::scale_factor(self.dpi())
+    }
+
+    pub fn physical_size(&self) -> PhysicalSize {
+        let (width, height) = self.client_size();
+        PhysicalSize::new(width, height)
+    }
+
+    pub fn logical_size(&self) -> LogicalSize {
+        self.physical_size().to_logical(self.scale_factor())
+    }
+
+    /// resizes the client area, keeping the window's position
+    pub fn set_logical_size(&mut self, size: LogicalSize) {
+        use winapi::um::winuser::{SetWindowPos, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOZORDER};
+        let physical = size.to_physical(self.scale_factor());
+        let (width, height) = self.insets().window_size(physical);
+        unsafe {
+            SetWindowPos(
+                self.handle,
+                null_mut(),
+                0,
+                0,
+                width,
+                height,
+                SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+            )
+        };
+        self.width = physical.width as usize;
+        self.height = physical.height as usize;
+    }
+
+    /// the frame around the client area at the window's style and dpi
+    fn insets(&self) -> Insets {
+        use winapi::shared::minwindef::DWORD;
+        use winapi::um::winuser::{
+            AdjustWindowRectExForDpi, GetWindowLongW, GWL_EXSTYLE, GWL_STYLE,
+        };
+        let style = unsafe { GetWindowLongW(self.handle, GWL_STYLE) } as DWORD;
+        let ex_style = unsafe { GetWindowLongW(self.handle, GWL_EXSTYLE) } as DWORD;
+        let mut rect = to_win32(&Rect::default());
+        unsafe { AdjustWindowRectExForDpi(&mut rect, style, 0, ex_style, self.dpi()) };
+        Insets::from_adjusted(&from_win32(&rect))
+    }
+
+    /// current size of the client area in pixels, 0x0 while minimized
+    pub fn client_size(&self) -> (u32, u32) {
+        use winapi::um::winuser::GetClientRect;
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        unsafe { GetClientRect(self.handle, &mut rect) };
+        (
+            (rect.right - rect.left).max(0) as u32,
+            (rect.bottom - rect.top).max(0) as u32,
+        )
+    }
+
+    /// the monitor that most of the window is on
+    pub fn monitor(&self) -> HMONITOR {
+        use winapi::um::winuser::{MonitorFromWindow, MONITOR_DEFAULTTONEAREST};
+        unsafe { MonitorFromWindow(self.handle, MONITOR_DEFAULTTONEAREST) }
+    }
+
+    /// whole area and work area (without the taskbar) of `monitor`
+    fn monitor_rects(monitor: HMONITOR) -> (Rect, Rect) {
+        use winapi::um::winuser::{GetMonitorInfoW, MONITORINFO};
+        let mut info: MONITORINFO = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<MONITORINFO>() as UINT;
+        unsafe { GetMonitorInfoW(monitor, &mut info) };
+        (from_win32(&info.rcMonitor), from_win32(&info.rcWork))
+    }
+
+    pub fn placement(&self) -> Placement {
+        use winapi::um::winuser::{GetWindowLongW, GetWindowRect, GWL_STYLE};
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        unsafe { GetWindowRect(self.handle, &mut rect) };
+        Placement {
+            style: unsafe { GetWindowLongW(self.handle, GWL_STYLE) } as u32,
+            rect: from_win32(&rect),
+        }
+    }
+
+    fn apply(&mut self, style: u32, rect: &Rect) {
+        use winapi::um::winuser::{
+            SetWindowLongW, SetWindowPos, GWL_STYLE, HWND_TOP, SWP_FRAMECHANGED, SWP_NOACTIVATE,
+            SWP_SHOWWINDOW,
+        };
+        unsafe {
+            SetWindowLongW(self.handle, GWL_STYLE, style as i32);
+            SetWindowPos(
+                self.handle,
+                HWND_TOP,
+                rect.left,
+                rect.top,
+                rect.width(),
+                rect.height(),
+                SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            );
+        }
+    }
+
+    /// puts the window back to `placement`, moving it onto the nearest
+    /// monitor if that monitor is gone
+    pub fn restore_placement(&mut self, placement: &Placement) {
+        use winapi::um::winuser::{MonitorFromRect, MONITOR_DEFAULTTONEAREST};
+        let rect = to_win32(&placement.rect);
+        let monitor = unsafe { MonitorFromRect(&rect, MONITOR_DEFAULTTONEAREST) };
+        let (_, work) = Window::monitor_rects(monitor);
+        self.apply(placement.style, &mode::restore_rect(&placement.rect, &work));
+    }
+
+    /// removes the frame and stretches the window over its monitor
+    pub fn set_borderless(&mut self) {
+        let style = mode::borderless_style(self.placement().style);
+        let (area, _) = Window::monitor_rects(self.monitor());
+        self.apply(style, &area);
+    }
+
+    pub fn show(&mut self) {
+        use winapi::um::winuser::{ShowWindow, SW_SHOW};
+        unsafe { ShowWindow(self.handle, SW_SHOW) };
+    }
+}
+
+fn wait_message() {
+    use winapi::um::winuser::WaitMessage;
+    unsafe { WaitMessage() };
+}
+
+/// a single window as the platform. events of other windows created by the
+/// application still come through `poll`, but their sizes are not followed.
+impl Platform for Window {
+    fn poll(&mut self, events: &mut Vec<WindowEvent>) -> bool {
+        let running = crate::msg::pump(events);
+        if let Some(panic) = self.take_panic() {
+            std::panic::resume_unwind(panic);
+        }
+        running
+    }
+
+    fn wait(&mut self) {
+        wait_message();
+    }
+
+    fn client_sizes(&self, sizes: &mut Vec<(WindowId, u32, u32)>) {
+        let (width, height) = self.client_size();
+        sizes.push((self.id, width, height));
+    }
+
+    fn scale_factor(&self, _window: WindowId) -> f32 {
+        Window::scale_factor(self)
+    }
+
+    fn resized(&mut self, _window: WindowId, width: u32, height: u32) {
+        self.width = width as usize;
+        self.height = height as usize;
+    }
+}
+
+/// several top-level windows on one message loop, e.g. the scene view and
+/// texture preview of a tool. a window is dropped once it is destroyed.
+#[derive(Default)]
+pub struct Windows {
+    windows: Vec<Window>,
+}
+
+impl Windows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, window: Window) -> WindowId {
+        let id = window.id;
+        self.windows.push(window);
+        id
+    }
+
+    pub fn get(&self, id: WindowId) -> Option<&Window> {
+        self.windows.iter().find(|window| window.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: WindowId) -> Option<&mut Window> {
+        self.windows.iter_mut().find(|window| window.id == id)
+    }
+
+    pub fn remove(&mut self, id: WindowId) -> Option<Window> {
+        let index = self.windows.iter().position(|window| window.id == id)?;
+        Some(self.windows.remove(index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Window> {
+        self.windows.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+}
+
+impl Platform for Windows {
+    fn poll(&mut self, events: &mut Vec<WindowEvent>) -> bool {
+        let first = events.len();
+        let running = crate::msg::pump(events);
+        for window in self.windows.iter_mut() {
+            if let Some(panic) = window.take_panic() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        for event in events[first..].iter() {
+            if event.event == Event::Destroyed {
+                self.remove(event.window);
+            }
+        }
+        running
+    }
+
+    fn wait(&mut self) {
+        wait_message();
+    }
+
+    fn client_sizes(&self, sizes: &mut Vec<(WindowId, u32, u32)>) {
+        for window in self.windows.iter() {
+            let (width, height) = window.client_size();
+            sizes.push((window.id, width, height));
+        }
+    }
+
+    fn scale_factor(&self, window: WindowId) -> f32 {
+        self.get(window).map_or(1.0, |window| window.scale_factor())
+    }
+
+    fn resized(&mut self, window: WindowId, width: u32, height: u32) {
+        if let Some(window) = self.get_mut(window) {
+            window.width = width as usize;
+            window.height = height as usize;
+        }
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        use winapi::um::winuser::{SetWindowLongPtrW, UnregisterClassW, GWLP_USERDATA};
+        // the clip rectangle is global, so it must not outlive the window
+        self.set_capture_mode(CaptureMode::Free);
+        // the procedure must not reach the state once it is freed
+        unsafe { SetWindowLongPtrW(self.handle, GWLP_USERDATA, 0) };
+        unsafe { UnregisterClassW(self.class_name.as_ptr(), self.class.hInstance) };
+    }
+}
+
+fn from_win32(rect: &RECT) -> Rect {
+    Rect::new(rect.left, rect.top, rect.right, rect.bottom)
+}
+
+fn to_win32(rect: &Rect) -> RECT {
+    RECT {
+        left: rect.left,
+        top: rect.top,
+        right: rect.right,
+        bottom: rect.bottom,
+    }
+}
+
+extern "system" fn procedure(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    use winapi::um::winuser::{
+        DefWindowProcW, GetWindowLongPtrW, PostQuitMessage, SetWindowLongPtrW, CREATESTRUCTW,
+        GWLP_USERDATA, VK_RETURN, WM_DESTROY, WM_NCCREATE, WM_NCDESTROY, WM_SYSCHAR,
+    };
+    match msg {
+        WM_NCCREATE => {
+            let create = lparam as *const CREATESTRUCTW;
+            unsafe { SetWindowLongPtrW(hwnd, GWLP_USERDATA, (*create).lpCreateParams as isize) };
+        }
+        WM_NCDESTROY => {
+            unsafe { SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0) };
+        }
+        WM_DESTROY => {
+            let open = OPEN_WINDOWS.with(|open| {
+                open.set(open.get().saturating_sub(1));
+                open.get()
+            });
+            if open == 0 {
+                unsafe { PostQuitMessage(0) };
+            }
+        }
+        // swallow the character of alt+enter so that it does not beep
+        WM_SYSCHAR if wparam == VK_RETURN as WPARAM => return 0,
+        _ => (),
+    }
+    let state = unsafe { GetWindowLongPtrW(hwnd, GWLP_USERDATA) } as *const RefCell<State>;
+    let mut handled = false;
+    let mut suggested_rect = None;
+    if !state.is_null() {
+        // messages sent while a handler runs are left to DefWindowProc
+        if let Ok(mut state) = unsafe { &*state }.try_borrow_mut() {
+            handled = state.handle(hwnd, msg, wparam, lparam);
+            suggested_rect = state.suggested_rect.take();
+        }
+    }
+    // moved here so that the WM_SIZE it sends reaches the state
+    if let Some(rect) = suggested_rect {
+        use winapi::um::winuser::{SetWindowPos, SWP_NOACTIVATE, SWP_NOZORDER};
+        unsafe {
+            SetWindowPos(
+                hwnd,
+                null_mut(),
+                rect.left,
+                rect.top,
+                rect.width(),
+                rect.height(),
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            )
+        };
+    }
+    if handled {
+        return 0;
+    }
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
@@ -0,0 +1,35 @@
+// the bindings generated from this file are in Scene.rs
+#pragma pack_matrix(row_major)
+
+struct Light {
+    float3 direction;
+    float intensity;
+    float3 color;
+};
+
+struct VertexIn {
+    float3 position : POSITION;
+    float3 normal : NORMAL;
+    float2 uv : TEXCOORD0;
+    uint id : SV_VertexID;
+};
+
+cbuffer Scene : register(b1, space2) {
+    float4x4 viewProjection;
+    column_major float3x3 normalMatrix;
+    float3 eye;
+    float time;
+    Light lights[2];
+    int2 size;
+    bool enabled, type;
+    float weights[3];
+};
+
+Texture2D<float4> albedo : register(t0);
+SamplerState linearSampler : register(s0, space1);
+
+[RootSignature("")]
+float4 main(VertexIn v, float4 tint : COLOR1) : SV_POSITION
+{
+    return mul(float4(v.position, 1), viewProjection) * tint;
+}
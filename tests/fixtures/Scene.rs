@@ -0,0 +1,102 @@
+#[allow(unused_imports)]
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub direction: Vec3<f32>,
+    pub intensity: f32,
+    pub color: Vec3<f32>,
+    pub _pad0: [u32; 1],
+}
+
+const _: () = assert!(std::mem::offset_of!(Light, direction) == 0);
+const _: () = assert!(std::mem::offset_of!(Light, intensity) == 12);
+const _: () = assert!(std::mem::offset_of!(Light, color) == 16);
+const _: () = assert!(std::mem::size_of::<Light>() == 32);
+
+impl crate::cbuffer::HlslType for Light {
+    const SIZE: u32 = 28;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> crate::cbuffer::packing::Type {
+        crate::cbuffer::packing::Type::Struct(vec![
+            crate::cbuffer::packing::Field::new("direction", crate::cbuffer::packing::Type::Vector(crate::cbuffer::packing::Scalar::Float, 3)),
+            crate::cbuffer::packing::Field::new("intensity", crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Float)),
+            crate::cbuffer::packing::Field::new("color", crate::cbuffer::packing::Type::Vector(crate::cbuffer::packing::Scalar::Float, 3)),
+        ])
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VertexIn {
+    pub position: Vec3<f32>,
+    pub normal: Vec3<f32>,
+    pub uv: Vec2<f32>,
+}
+
+pub const VERTEX_IN_INPUT_LAYOUT: &[crate::shaders::InputElement] = &[
+    crate::shaders::InputElement { semantic: "POSITION", index: 0, format: crate::shaders::format::R32G32B32_FLOAT, offset: 0 },
+    crate::shaders::InputElement { semantic: "NORMAL", index: 0, format: crate::shaders::format::R32G32B32_FLOAT, offset: 12 },
+    crate::shaders::InputElement { semantic: "TEXCOORD", index: 0, format: crate::shaders::format::R32G32_FLOAT, offset: 24 },
+];
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scene {
+    pub view_projection: Mat4,
+    pub normal_matrix: [Vec4<f32>; 3],
+    pub eye: Vec3<f32>,
+    pub time: f32,
+    pub lights: [Light; 2],
+    pub size: Vec2<i32>,
+    pub enabled: u32,
+    pub r#type: u32,
+    pub weights: [Vec4<f32>; 3],
+}
+
+const _: () = assert!(std::mem::offset_of!(Scene, view_projection) == 0);
+const _: () = assert!(std::mem::offset_of!(Scene, normal_matrix) == 64);
+const _: () = assert!(std::mem::offset_of!(Scene, eye) == 112);
+const _: () = assert!(std::mem::offset_of!(Scene, time) == 124);
+const _: () = assert!(std::mem::offset_of!(Scene, lights) == 128);
+const _: () = assert!(std::mem::offset_of!(Scene, size) == 192);
+const _: () = assert!(std::mem::offset_of!(Scene, enabled) == 200);
+const _: () = assert!(std::mem::offset_of!(Scene, r#type) == 204);
+const _: () = assert!(std::mem::offset_of!(Scene, weights) == 208);
+const _: () = assert!(std::mem::size_of::<Scene>() == 256);
+
+impl crate::cbuffer::HlslType for Scene {
+    const SIZE: u32 = 244;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> crate::cbuffer::packing::Type {
+        crate::cbuffer::packing::Type::Struct(vec![
+            crate::cbuffer::packing::Field::new("viewProjection", crate::cbuffer::packing::Type::Matrix { scalar: crate::cbuffer::packing::Scalar::Float, rows: 4, columns: 4, major: crate::cbuffer::packing::Major::Row }),
+            crate::cbuffer::packing::Field::new("normalMatrix", crate::cbuffer::packing::Type::Matrix { scalar: crate::cbuffer::packing::Scalar::Float, rows: 3, columns: 3, major: crate::cbuffer::packing::Major::Column }),
+            crate::cbuffer::packing::Field::new("eye", crate::cbuffer::packing::Type::Vector(crate::cbuffer::packing::Scalar::Float, 3)),
+            crate::cbuffer::packing::Field::new("time", crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Float)),
+            crate::cbuffer::packing::Field::new("lights", crate::cbuffer::packing::Type::Array(Box::new(<Light as crate::cbuffer::HlslType>::hlsl_type()), 2)),
+            crate::cbuffer::packing::Field::new("size", crate::cbuffer::packing::Type::Vector(crate::cbuffer::packing::Scalar::Int, 2)),
+            crate::cbuffer::packing::Field::new("enabled", crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Bool)),
+            crate::cbuffer::packing::Field::new("type", crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Bool)),
+            crate::cbuffer::packing::Field::new("weights", crate::cbuffer::packing::Type::Array(Box::new(crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Float)), 3)),
+        ])
+    }
+}
+
+pub const SCENE_REGISTER: u32 = 1;
+pub const SCENE_SPACE: u32 = 2;
+
+pub const ALBEDO_REGISTER: u32 = 0;
+pub const ALBEDO_SPACE: u32 = 0;
+
+pub const LINEAR_SAMPLER_REGISTER: u32 = 0;
+pub const LINEAR_SAMPLER_SPACE: u32 = 1;
+
+pub const MAIN_INPUT_LAYOUT: &[crate::shaders::InputElement] = &[
+    crate::shaders::InputElement { semantic: "POSITION", index: 0, format: crate::shaders::format::R32G32B32_FLOAT, offset: 0 },
+    crate::shaders::InputElement { semantic: "NORMAL", index: 0, format: crate::shaders::format::R32G32B32_FLOAT, offset: 12 },
+    crate::shaders::InputElement { semantic: "TEXCOORD", index: 0, format: crate::shaders::format::R32G32_FLOAT, offset: 24 },
+    crate::shaders::InputElement { semantic: "COLOR", index: 1, format: crate::shaders::format::R32G32B32A32_FLOAT, offset: 32 },
+];
+
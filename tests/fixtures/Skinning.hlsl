@@ -0,0 +1,31 @@
+// the bindings generated from this file are in Skinning.rs. matrices are
+// column major, and structs are nested in structs and arrays.
+
+struct Bone {
+    float scale;
+    float4x3 offset;
+    float3x4 pose;
+};
+
+struct Skeleton {
+    uint boneCount;
+    Bone root;
+    Bone bones[2];
+};
+
+cbuffer Skinning : register(b0) {
+    float4x4 palette[4];
+    row_major float2x3 uvTransforms[2];
+    Skeleton skeleton;
+    float2 jitter;
+    uint2 frame;
+};
+
+StructuredBuffer<float4> positions : register(t0);
+RWStructuredBuffer<float4> skinned : register(u0);
+
+[numthreads(64, 1, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    skinned[id.x] = mul(positions[id.x], palette[0]);
+}
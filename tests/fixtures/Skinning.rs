@@ -0,0 +1,95 @@
+#[allow(unused_imports)]
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bone {
+    pub scale: f32,
+    pub _pad0: [u32; 3],
+    pub offset: [Vec4<f32>; 3],
+    pub pose: [Vec4<f32>; 4],
+}
+
+const _: () = assert!(std::mem::offset_of!(Bone, scale) == 0);
+const _: () = assert!(std::mem::offset_of!(Bone, offset) == 16);
+const _: () = assert!(std::mem::offset_of!(Bone, pose) == 64);
+const _: () = assert!(std::mem::size_of::<Bone>() == 128);
+
+impl crate::cbuffer::HlslType for Bone {
+    const SIZE: u32 = 124;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> crate::cbuffer::packing::Type {
+        crate::cbuffer::packing::Type::Struct(vec![
+            crate::cbuffer::packing::Field::new("scale", crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Float)),
+            crate::cbuffer::packing::Field::new("offset", crate::cbuffer::packing::Type::Matrix { scalar: crate::cbuffer::packing::Scalar::Float, rows: 4, columns: 3, major: crate::cbuffer::packing::Major::Column }),
+            crate::cbuffer::packing::Field::new("pose", crate::cbuffer::packing::Type::Matrix { scalar: crate::cbuffer::packing::Scalar::Float, rows: 3, columns: 4, major: crate::cbuffer::packing::Major::Column }),
+        ])
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skeleton {
+    pub bone_count: u32,
+    pub _pad0: [u32; 3],
+    pub root: Bone,
+    pub bones: [Bone; 2],
+}
+
+const _: () = assert!(std::mem::offset_of!(Skeleton, bone_count) == 0);
+const _: () = assert!(std::mem::offset_of!(Skeleton, root) == 16);
+const _: () = assert!(std::mem::offset_of!(Skeleton, bones) == 144);
+const _: () = assert!(std::mem::size_of::<Skeleton>() == 400);
+
+impl crate::cbuffer::HlslType for Skeleton {
+    const SIZE: u32 = 396;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> crate::cbuffer::packing::Type {
+        crate::cbuffer::packing::Type::Struct(vec![
+            crate::cbuffer::packing::Field::new("boneCount", crate::cbuffer::packing::Type::Scalar(crate::cbuffer::packing::Scalar::Uint)),
+            crate::cbuffer::packing::Field::new("root", <Bone as crate::cbuffer::HlslType>::hlsl_type()),
+            crate::cbuffer::packing::Field::new("bones", crate::cbuffer::packing::Type::Array(Box::new(<Bone as crate::cbuffer::HlslType>::hlsl_type()), 2)),
+        ])
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skinning {
+    pub palette: [Mat4; 4],
+    pub uv_transforms: [[Vec4<f32>; 2]; 2],
+    pub skeleton: Skeleton,
+    pub jitter: Vec2<f32>,
+    pub frame: Vec2<u32>,
+}
+
+const _: () = assert!(std::mem::offset_of!(Skinning, palette) == 0);
+const _: () = assert!(std::mem::offset_of!(Skinning, uv_transforms) == 256);
+const _: () = assert!(std::mem::offset_of!(Skinning, skeleton) == 320);
+const _: () = assert!(std::mem::offset_of!(Skinning, jitter) == 720);
+const _: () = assert!(std::mem::offset_of!(Skinning, frame) == 728);
+const _: () = assert!(std::mem::size_of::<Skinning>() == 736);
+
+impl crate::cbuffer::HlslType for Skinning {
+    const SIZE: u32 = 736;
+    const AGGREGATE: bool = true;
+    fn hlsl_type() -> crate::cbuffer::packing::Type {
+        crate::cbuffer::packing::Type::Struct(vec![
+            crate::cbuffer::packing::Field::new("palette", crate::cbuffer::packing::Type::Array(Box::new(crate::cbuffer::packing::Type::Matrix { scalar: crate::cbuffer::packing::Scalar::Float, rows: 4, columns: 4, major: crate::cbuffer::packing::Major::Column }), 4)),
+            crate::cbuffer::packing::Field::new("uvTransforms", crate::cbuffer::packing::Type::Array(Box::new(crate::cbuffer::packing::Type::Matrix { scalar: crate::cbuffer::packing::Scalar::Float, rows: 2, columns: 3, major: crate::cbuffer::packing::Major::Row }), 2)),
+            crate::cbuffer::packing::Field::new("skeleton", <Skeleton as crate::cbuffer::HlslType>::hlsl_type()),
+            crate::cbuffer::packing::Field::new("jitter", crate::cbuffer::packing::Type::Vector(crate::cbuffer::packing::Scalar::Float, 2)),
+            crate::cbuffer::packing::Field::new("frame", crate::cbuffer::packing::Type::Vector(crate::cbuffer::packing::Scalar::Uint, 2)),
+        ])
+    }
+}
+
+pub const SKINNING_REGISTER: u32 = 0;
+pub const SKINNING_SPACE: u32 = 0;
+
+pub const POSITIONS_REGISTER: u32 = 0;
+pub const POSITIONS_SPACE: u32 = 0;
+
+pub const SKINNED_REGISTER: u32 = 0;
+pub const SKINNED_SPACE: u32 = 0;
+
@@ -0,0 +1,10 @@
+// numthreads.dxbc is this shader as `fxc /T cs_5_0` lays it out. it was
+// assembled by hand, since there is no compiler where the tests run, so
+// its checksum is zero and it only carries the chunks the reader needs.
+RWByteAddressBuffer output : register(u0);
+
+[numthreads(8, 4, 2)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    output.Store(id.x * 4, id.x);
+}